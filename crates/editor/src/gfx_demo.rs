@@ -6,8 +6,8 @@ use gfx::render_pass::{RenderPassAttachment, RenderPassCreateInfos};
 use gfx::shader::PassID;
 use gfx::surface::GfxSurface;
 use gfx::types::*;
+use maths::color::LinearRgba;
 use maths::vec2::{Vec2f32, Vec2u32};
-use maths::vec4::Vec4F32;
 
 pub fn _demo_objects(gfx: &GfxRef, surface: &Arc<dyn GfxSurface>) {
     // GPU Buffer example
@@ -27,22 +27,22 @@ pub fn _demo_objects(gfx: &GfxRef, surface: &Arc<dyn GfxSurface>) {
         color_attachments: vec![
             RenderPassAttachment {
                 name: "albedo".to_string(),
-                clear_value: ClearValues::Color(Vec4F32::new(0.0, 0.0, 0.0, 1.0)),
+                clear_value: ClearValues::Color(LinearRgba::BLACK),
                 image_format: PixelFormat::R8G8B8A8_UNORM,
             },
             RenderPassAttachment {
                 name: "roughness_metalness_ao".to_string(),
-                clear_value: ClearValues::Color(Vec4F32::new(0.0, 0.0, 0.0, 1.0)),
+                clear_value: ClearValues::Color(LinearRgba::BLACK),
                 image_format: PixelFormat::R8G8_UNORM,
             },
             RenderPassAttachment {
                 name: "normal".to_string(),
-                clear_value: ClearValues::Color(Vec4F32::new(0.0, 0.0, 0.0, 1.0)),
+                clear_value: ClearValues::Color(LinearRgba::BLACK),
                 image_format: PixelFormat::R8G8B8A8_UNORM,
             },
             RenderPassAttachment {
                 name: "velocity".to_string(),
-                clear_value: ClearValues::Color(Vec4F32::new(0.0, 0.0, 0.0, 1.0)),
+                clear_value: ClearValues::Color(LinearRgba::BLACK),
                 image_format: PixelFormat::R16G16B16A16_SFLOAT,
             }],
         depth_attachment: Some(
@@ -58,7 +58,7 @@ pub fn _demo_objects(gfx: &GfxRef, surface: &Arc<dyn GfxSurface>) {
         pass_id: PassID::new("deferred_combine"),
         color_attachments: vec![RenderPassAttachment {
             name: "color".to_string(),
-            clear_value: ClearValues::Color(Vec4F32::new(0.0, 0.0, 0.0, 1.0)),
+            clear_value: ClearValues::Color(LinearRgba::BLACK),
             image_format: PixelFormat::R8G8B8A8_UNORM,
         }],
         depth_attachment: None,
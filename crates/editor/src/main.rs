@@ -13,9 +13,9 @@ use gfx::shader::{PassID, ShaderStage};
 use gfx::shader_instance::BindPoint;
 use gfx::types::{ClearValues, PixelFormat};
 use imgui::ImGUiContext;
+use maths::color::LinearRgba;
 use maths::rect2d::Rect2D;
 use maths::vec2::Vec2u32;
-use maths::vec4::Vec4F32;
 use plateform::input_system::{InputAction, InputMapping, KeyboardKey};
 use plateform::window::{PlatformEvent, WindowCreateInfos, WindowFlagBits, WindowFlags};
 use third_party_io::image::read_image_from_file;
@@ -56,7 +56,7 @@ fn main() {
         pass_id: PassID::new("deferred_combine"),
         color_attachments: vec![RenderPassAttachment {
             name: "color".to_string(),
-            clear_value: ClearValues::Color(Vec4F32::new(0.0, 0.0, 0.0, 1.0)),
+            clear_value: ClearValues::Color(LinearRgba::BLACK),
             image_format: PixelFormat::R8G8B8A8_UNORM,
         }],
         depth_attachment: None,
@@ -66,7 +66,7 @@ fn main() {
     let imgui_pass = imgui_context.instantiate_for_surface(&main_window_surface);
 
     // Create framegraph
-    let main_framegraph = FrameGraph::from_surface(&engine.gfx, &main_window_surface, Vec4F32::new(1.0, 0.0, 0.0, 1.0));
+    let main_framegraph = FrameGraph::from_surface(&engine.gfx, &main_window_surface, LinearRgba::rgb(1.0, 0.0, 0.0));
     main_framegraph.main_pass().attach(def_combine.clone());
     main_framegraph.main_pass().attach(imgui_pass.clone());
 
@@ -181,14 +181,21 @@ impl RenderPassInstance for VkRenderPassInstance {
         
         vk_check!(unsafe { (*device).handle.begin_command_buffer(command_buffer, &vk::CommandBufferBeginInfo::default()) });
 
+        // Clear colors are linear : swapchain images expect values encoded for the surface color space
+        let config = self.owner.get_config();
+        let color_space = self.surface.get_surface_color_space();
         let mut clear_values = Vec::new();
-        for clear_value in &self.clear_value {
+        for (index, clear_value) in self.clear_value.iter().enumerate() {
             clear_values.push(match clear_value {
                 ClearValues::DontClear => { vk::ClearValue::default() }
                 ClearValues::Color(color) => {
+                    let float32 = match config.color_attachments.get(index) {
+                        Some(attachment) if config.is_present_pass => { color_space.encode_for_format(*color, attachment.image_format) }
+                        _ => { color.to_array() }
+                    };
                     vk::ClearValue {
                         color: vk::ClearColorValue {
-                            float32: [color.x, color.y, color.z, color.w]
+                            float32
                         }
                     }
                 }
@@ -460,4 +460,39 @@ impl From<ColorSpace> for VkColorSpace {
             }
         }
     }
-}
\ No newline at end of file
+}
+
+pub struct GfxColorSpace(ColorSpace);
+
+impl Deref for GfxColorSpace {
+    type Target = ColorSpace;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl From<ColorSpaceKHR> for GfxColorSpace {
+    fn from(color_space: ColorSpaceKHR) -> Self {
+        GfxColorSpace {
+            0: match color_space {
+                ColorSpaceKHR::SRGB_NONLINEAR => { ColorSpace::SRGB_NONLINEAR }
+                ColorSpaceKHR::DISPLAY_P3_NONLINEAR_EXT => { ColorSpace::DISPLAY_P3_NONLINEAR }
+                ColorSpaceKHR::EXTENDED_SRGB_LINEAR_EXT => { ColorSpace::EXTENDED_SRGB_LINEAR }
+                ColorSpaceKHR::DISPLAY_P3_LINEAR_EXT => { ColorSpace::DISPLAY_P3_LINEAR }
+                ColorSpaceKHR::DCI_P3_NONLINEAR_EXT => { ColorSpace::DCI_P3_NONLINEAR }
+                ColorSpaceKHR::BT709_LINEAR_EXT => { ColorSpace::BT709_LINEAR }
+                ColorSpaceKHR::BT709_NONLINEAR_EXT => { ColorSpace::BT709_NONLINEAR }
+                ColorSpaceKHR::BT2020_LINEAR_EXT => { ColorSpace::BT2020_LINEAR }
+                ColorSpaceKHR::HDR10_ST2084_EXT => { ColorSpace::HDR10_ST2084 }
+                ColorSpaceKHR::DOLBYVISION_EXT => { ColorSpace::DOLBYVISION }
+                ColorSpaceKHR::HDR10_HLG_EXT => { ColorSpace::HDR10_HLG }
+                ColorSpaceKHR::ADOBERGB_LINEAR_EXT => { ColorSpace::ADOBERGB_LINEAR }
+                ColorSpaceKHR::ADOBERGB_NONLINEAR_EXT => { ColorSpace::ADOBERGB_NONLINEAR }
+                ColorSpaceKHR::PASS_THROUGH_EXT => { ColorSpace::PASS_THROUGH }
+                ColorSpaceKHR::EXTENDED_SRGB_NONLINEAR_EXT => { ColorSpace::EXTENDED_SRGB_NONLINEAR }
+                ColorSpaceKHR::DISPLAY_NATIVE_AMD => { ColorSpace::DISPLAY_NATIVE }
+                _ => { ColorSpace::PASS_THROUGH }
+            }
+        }
+    }
+}
@@ -10,13 +10,13 @@ use backend_vulkan::{g_vulkan, G_VULKAN, GfxVulkan, vk_check};
 use backend_vulkan::vk_device::VkQueue;
 use backend_vulkan::vk_image::VkImage;
 use backend_vulkan::vk_render_pass_instance::{RbSemaphore, VkRenderPassInstance};
-use backend_vulkan::vk_types::GfxPixelFormat;
+use backend_vulkan::vk_types::{GfxColorSpace, GfxPixelFormat};
 use gfx::gfx_resource::{GfxImageBuilder, GfxResource};
 use gfx::GfxRef;
 use gfx::image::{GfxImage, GfxImageUsageFlags, ImageParams, ImageType};
 use gfx::render_pass::RenderPassInstance;
 use gfx::surface::{GfxImageID, GfxSurface, SurfaceAcquireResult};
-use gfx::types::PixelFormat;
+use gfx::types::{ColorSpace, PixelFormat};
 use maths::vec2::Vec2u32;
 use plateform::window::Window;
 
@@ -132,6 +132,10 @@ impl GfxSurface for VkSurfaceWin32 {
         *GfxPixelFormat::from(self.surface_format.format)
     }
 
+    fn get_surface_color_space(&self) -> ColorSpace {
+        *GfxColorSpace::from(self.surface_format.color_space)
+    }
+
     fn get_image_count(&self) -> u8 {
         self.image_count
     }
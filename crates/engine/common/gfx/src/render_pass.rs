@@ -1,7 +1,7 @@
 ﻿use std::sync::Arc;
 
+use maths::color::LinearRgba;
 use maths::vec2::Vec2u32;
-use maths::vec4::Vec4F32;
 
 use crate::{GfxCast, GfxCommandBuffer, GfxImage, GfxRef, GfxSurface, PassID};
 use crate::surface::SurfaceAcquireResult;
@@ -59,7 +59,7 @@ pub struct FrameGraph {
 pub type GraphRenderCallback = Box<dyn FnMut(&Arc<dyn GfxCommandBuffer>)>;
 
 impl FrameGraph {
-    pub fn from_surface(_gfx: &GfxRef, surface: &Arc<dyn GfxSurface>, clear_value: Vec4F32) -> Arc<Self> {
+    pub fn from_surface(_gfx: &GfxRef, surface: &Arc<dyn GfxSurface>, clear_value: LinearRgba) -> Arc<Self> {
         let render_pass_ci = RenderPassCreateInfos {
             pass_id: PassID::new("surface_pass"),
             color_attachments: vec![RenderPassAttachment {
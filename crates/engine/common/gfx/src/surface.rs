@@ -9,7 +9,7 @@ use plateform::window::Window;
 use crate::{GfxCast, GfxRef};
 use crate::image::GfxImage;
 use crate::render_pass::RenderPassInstance;
-use crate::types::PixelFormat;
+use crate::types::{ColorSpace, PixelFormat};
 
 pub struct GfxImageID {
     reference: AtomicU16,
@@ -76,6 +76,7 @@ pub trait GfxSurface: GfxCast {
     fn create_or_recreate(&self);
     fn get_owning_window(&self) -> &Arc<dyn Window>;
     fn get_surface_pixel_format(&self) -> PixelFormat;
+    fn get_surface_color_space(&self) -> ColorSpace;
     fn get_image_count(&self) -> u8;
     fn get_current_ref(&self) -> &GfxImageID;
     fn get_surface_texture(&self) -> Arc<dyn GfxImage>;
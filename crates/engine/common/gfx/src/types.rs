@@ -1,8 +1,9 @@
 ﻿use std::any::Any;
+use maths::color::{LinearRgba, Primaries, TransferFunction};
 use maths::vec2::Vec2f32;
-use maths::vec4::Vec4F32;
 
 #[allow(non_camel_case_types)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum ColorSpace {
     SRGB_NONLINEAR,
     DISPLAY_P3_NONLINEAR,
@@ -23,6 +24,64 @@ pub enum ColorSpace {
     DCI_P3_LINEAR,
 }
 
+impl ColorSpace {
+    // None when the presentation engine does not interpret the values
+    pub fn transfer_function(&self) -> Option<TransferFunction> {
+        match self {
+            ColorSpace::SRGB_NONLINEAR => { Some(TransferFunction::Srgb) }
+            ColorSpace::DISPLAY_P3_NONLINEAR => { Some(TransferFunction::Srgb) }
+            ColorSpace::EXTENDED_SRGB_LINEAR => { Some(TransferFunction::Linear) }
+            ColorSpace::DISPLAY_P3_LINEAR => { Some(TransferFunction::Linear) }
+            ColorSpace::DCI_P3_NONLINEAR => { Some(TransferFunction::DciP3) }
+            ColorSpace::BT709_LINEAR => { Some(TransferFunction::Linear) }
+            ColorSpace::BT709_NONLINEAR => { Some(TransferFunction::Bt709) }
+            ColorSpace::BT2020_LINEAR => { Some(TransferFunction::Linear) }
+            ColorSpace::HDR10_ST2084 => { Some(TransferFunction::Pq) }
+            ColorSpace::DOLBYVISION => { Some(TransferFunction::Pq) }
+            ColorSpace::HDR10_HLG => { Some(TransferFunction::Hlg) }
+            ColorSpace::ADOBERGB_LINEAR => { Some(TransferFunction::Linear) }
+            ColorSpace::ADOBERGB_NONLINEAR => { Some(TransferFunction::AdobeRgb) }
+            ColorSpace::PASS_THROUGH => { None }
+            ColorSpace::EXTENDED_SRGB_NONLINEAR => { Some(TransferFunction::Srgb) }
+            ColorSpace::DISPLAY_NATIVE => { None }
+            // Vulkan alias of DISPLAY_P3_LINEAR
+            ColorSpace::DCI_P3_LINEAR => { Some(TransferFunction::Linear) }
+        }
+    }
+
+    pub fn primaries(&self) -> Option<Primaries> {
+        match self {
+            ColorSpace::SRGB_NONLINEAR | ColorSpace::EXTENDED_SRGB_LINEAR | ColorSpace::EXTENDED_SRGB_NONLINEAR
+            | ColorSpace::BT709_LINEAR | ColorSpace::BT709_NONLINEAR => { Some(Primaries::Bt709) }
+            ColorSpace::DISPLAY_P3_NONLINEAR | ColorSpace::DISPLAY_P3_LINEAR | ColorSpace::DCI_P3_LINEAR => { Some(Primaries::DisplayP3) }
+            ColorSpace::DCI_P3_NONLINEAR => { Some(Primaries::DciP3) }
+            ColorSpace::BT2020_LINEAR | ColorSpace::HDR10_ST2084 | ColorSpace::DOLBYVISION | ColorSpace::HDR10_HLG => { Some(Primaries::Bt2020) }
+            ColorSpace::ADOBERGB_LINEAR | ColorSpace::ADOBERGB_NONLINEAR => { Some(Primaries::AdobeRgb) }
+            ColorSpace::PASS_THROUGH | ColorSpace::DISPLAY_NATIVE => { None }
+        }
+    }
+
+    // Values to write in a surface of this color space for a linear color, returned unchanged when the space
+    // is unknown. For PQ the color is absolute luminance (1.0 is 10000 nits).
+    pub fn encode(&self, color: LinearRgba) -> [f32; 4] {
+        let (Some(transfer), Some(primaries)) = (self.transfer_function(), self.primaries()) else { return color.to_array(); };
+        let color = color.to_primaries(primaries);
+        let encode = |value: f32| transfer.encode(value);
+        [encode(color.r), encode(color.g), encode(color.b), color.a]
+    }
+
+    // Values to write in an image of the given format. sRGB formats apply the sRGB curve on write, so it is
+    // removed from the encoded values.
+    pub fn encode_for_format(&self, color: LinearRgba, format: PixelFormat) -> [f32; 4] {
+        let [r, g, b, a] = self.encode(color);
+        if !format.is_srgb() || self.transfer_function().is_none() {
+            return [r, g, b, a];
+        }
+        let decode = |value: f32| TransferFunction::Srgb.decode(value);
+        [decode(r), decode(g), decode(b), a]
+    }
+}
+
 #[allow(non_camel_case_types)]
 #[derive(Copy, Clone, Debug)]
 pub enum PixelFormat {
@@ -420,13 +479,47 @@ impl PixelFormat {
             _ => { false }
         };
     }
+
+    // The hardware converts values written to these formats from linear to sRGB
+    pub fn is_srgb(&self) -> bool {
+        matches!(self,
+            PixelFormat::R8_SRGB
+            | PixelFormat::R8G8_SRGB
+            | PixelFormat::R8G8B8_SRGB
+            | PixelFormat::B8G8R8_SRGB
+            | PixelFormat::R8G8B8A8_SRGB
+            | PixelFormat::B8G8R8A8_SRGB
+            | PixelFormat::A8B8G8R8_SRGB_PACK32
+            | PixelFormat::BC1_RGB_SRGB_BLOCK
+            | PixelFormat::BC1_RGBA_SRGB_BLOCK
+            | PixelFormat::BC2_SRGB_BLOCK
+            | PixelFormat::BC3_SRGB_BLOCK
+            | PixelFormat::BC7_SRGB_BLOCK
+            | PixelFormat::ETC2_R8G8B8_SRGB_BLOCK
+            | PixelFormat::ETC2_R8G8B8A1_SRGB_BLOCK
+            | PixelFormat::ETC2_R8G8B8A8_SRGB_BLOCK
+            | PixelFormat::ASTC_4X4_SRGB_BLOCK
+            | PixelFormat::ASTC_5X4_SRGB_BLOCK
+            | PixelFormat::ASTC_5X5_SRGB_BLOCK
+            | PixelFormat::ASTC_6X5_SRGB_BLOCK
+            | PixelFormat::ASTC_6X6_SRGB_BLOCK
+            | PixelFormat::ASTC_8X5_SRGB_BLOCK
+            | PixelFormat::ASTC_8X6_SRGB_BLOCK
+            | PixelFormat::ASTC_8X8_SRGB_BLOCK
+            | PixelFormat::ASTC_10X5_SRGB_BLOCK
+            | PixelFormat::ASTC_10X6_SRGB_BLOCK
+            | PixelFormat::ASTC_10X8_SRGB_BLOCK
+            | PixelFormat::ASTC_10X10_SRGB_BLOCK
+            | PixelFormat::ASTC_12X10_SRGB_BLOCK
+            | PixelFormat::ASTC_12X12_SRGB_BLOCK)
+    }
 }
 
 
 #[derive(Copy, Clone)]
 pub enum ClearValues {
     DontClear,
-    Color(Vec4F32),
+    Color(LinearRgba),
     DepthStencil(Vec2f32),
 }
 
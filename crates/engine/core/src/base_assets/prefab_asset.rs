@@ -0,0 +1,95 @@
+use std::path::Path;
+use std::sync::{Arc, RwLock, Weak};
+
+use ecs::prefab::{Prefab, PrefabSource};
+use ecs::scene::SceneRegistry;
+
+use crate::asset::{AssetFactory, AssetMetaData, GameAsset};
+use crate::asset_manager::AssetManager;
+use crate::asset_type_id::AssetTypeID;
+
+pub struct PrefabAsset {
+    meta_data: AssetMetaData,
+    registry: Arc<SceneRegistry>,
+    source: RwLock<Option<Arc<PrefabSource>>>,
+}
+
+impl PrefabAsset {
+    pub fn new(asset_manager: &Arc<AssetManager>, registry: &Arc<SceneRegistry>) -> Arc<Self> {
+        Arc::new(Self {
+            meta_data: AssetMetaData::new(asset_manager),
+            registry: registry.clone(),
+            source: RwLock::default(),
+        })
+    }
+
+    // Instances created from the previous source are refreshed by Ecs::refresh_prefabs
+    pub fn set_prefab(&self, prefab: Prefab) {
+        let mut source = self.source.write().unwrap();
+        match &*source {
+            None => { *source = Some(PrefabSource::new(prefab)) }
+            Some(source) => { source.set(prefab) }
+        }
+    }
+
+    // Source to instantiate from, None until the prefab is loaded
+    pub fn source(&self) -> Option<Arc<PrefabSource>> {
+        self.source.read().unwrap().clone()
+    }
+
+    pub fn registry(&self) -> &Arc<SceneRegistry> {
+        &self.registry
+    }
+}
+
+impl GameAsset for PrefabAsset {
+    fn save(&self) -> Result<(), String> {
+        let path = self.meta_data.get_save_path().ok_or("cannot save a transient prefab")?;
+        let source = self.source().ok_or("prefab is not loaded")?;
+        let data = source.get().save_ron().map_err(|error| error.to_string())?;
+        std::fs::write(&path, data).map_err(|error| format!("failed to write '{path}' : {error}"))
+    }
+
+    fn reload(&self) -> Result<(), String> {
+        let path = self.meta_data.get_save_path().ok_or("cannot reload a transient prefab")?;
+        let data = std::fs::read_to_string(&path).map_err(|error| format!("failed to read '{path}' : {error}"))?;
+        self.set_prefab(Prefab::load_ron(&self.registry, &data).map_err(|error| error.to_string())?);
+        Ok(())
+    }
+
+    fn meta_data(&self) -> &AssetMetaData {
+        &self.meta_data
+    }
+}
+
+pub struct PrefabAssetFactory {
+    asset_manager: Weak<AssetManager>,
+    registry: Arc<SceneRegistry>,
+}
+
+impl PrefabAssetFactory {
+    // Components stored in prefab files are declared by the game, so this factory is not registered by default
+    pub fn new(asset_manager: &Arc<AssetManager>, registry: &Arc<SceneRegistry>) -> Arc<Self> {
+        Arc::new(Self {
+            asset_manager: Arc::downgrade(asset_manager),
+            registry: registry.clone(),
+        })
+    }
+}
+
+impl AssetFactory for PrefabAssetFactory {
+    fn instantiate_from_asset_path(&self, path: &Path) -> Arc<dyn GameAsset> {
+        let asset_manager = self.asset_manager.upgrade().expect("asset manager was destroyed");
+        let asset = PrefabAsset::new(&asset_manager, &self.registry);
+        asset.meta_data.set_name(path.file_stem().and_then(|name| name.to_str()).unwrap_or_default().to_string());
+        asset.meta_data.set_save_path(path);
+        if let Err(error) = asset.reload() {
+            panic!("failed to load prefab : \n{error}")
+        }
+        asset
+    }
+
+    fn asset_id(&self) -> AssetTypeID {
+        AssetTypeID::from("prefab")
+    }
+}
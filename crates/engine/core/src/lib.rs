@@ -8,4 +8,5 @@ pub mod base_assets {
     pub mod material_asset;
     pub mod material_instance_asset;
     pub mod mesh_asset;
+    pub mod prefab_asset;
 }
\ No newline at end of file
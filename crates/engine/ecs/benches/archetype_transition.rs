@@ -0,0 +1,78 @@
+use criterion::{BenchmarkId, black_box, Criterion, criterion_group, criterion_main};
+
+use ecs::ecs::Ecs;
+use ecs::entity::EntityID;
+use ecs::reflect::DynamicComponent;
+
+struct Comp<const N: usize>(#[allow(dead_code)] [f32; 4]);
+
+struct Toggled(#[allow(dead_code)] u64);
+
+struct Sparse<const N: usize>;
+
+// Create an entity owning the given number of components
+fn spawn(ecs: &mut Ecs, component_count: usize) -> EntityID {
+    let entity = ecs.create();
+    macro_rules! add_components {
+        ($($n:literal)*) => {
+            $(if $n < component_count { ecs.add(entity, Comp::<$n>([0.0; 4])).unwrap(); })*
+        };
+    }
+    add_components!(0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31);
+    entity
+}
+
+// Once transitions are cached, add / remove only depends on the number of components moved
+fn add_remove(c: &mut Criterion) {
+    let mut group = c.benchmark_group("add_remove");
+    for component_count in [1, 4, 8, 16, 32] {
+        let mut ecs = Ecs::default();
+        // Fill archetypes to make moves realistic
+        for _ in 0..1000 {
+            spawn(&mut ecs, component_count);
+        }
+        let entity = spawn(&mut ecs, component_count);
+
+        group.bench_with_input(BenchmarkId::from_parameter(component_count), &component_count, |b, _| {
+            b.iter(|| {
+                ecs.add(black_box(entity), Toggled(0)).unwrap();
+                ecs.remove::<Toggled>(black_box(entity)).unwrap();
+            })
+        });
+    }
+    group.finish();
+}
+
+// Component data lives in a hash map : it must not be looked up when adding or removing components.
+// Timings stay flat whatever the number of registered components, sparse ones and hooks included.
+fn add_remove_registered(c: &mut Criterion) {
+    let mut group = c.benchmark_group("add_remove_registered");
+    for registered in [0, 1_000, 100_000] {
+        let mut ecs = Ecs::default();
+        for i in 0..registered {
+            ecs.register_dynamic(DynamicComponent::new(format!("Dynamic{i}"))).unwrap();
+        }
+        if registered > 0 {
+            ecs.register_sparse::<Sparse<0>>();
+            ecs.register_sparse::<Sparse<1>>();
+            ecs.register_sparse::<Sparse<2>>();
+            ecs.component_hooks::<Sparse<0>>().on_add(|_, _, _| {}).on_remove(|_, _, _| {});
+            ecs.component_hooks::<Comp<31>>().on_insert(|_, _, _| {});
+        }
+        for _ in 0..1000 {
+            spawn(&mut ecs, 8);
+        }
+        let entity = spawn(&mut ecs, 8);
+
+        group.bench_with_input(BenchmarkId::from_parameter(registered), &registered, |b, _| {
+            b.iter(|| {
+                ecs.add(black_box(entity), Toggled(0)).unwrap();
+                ecs.remove::<Toggled>(black_box(entity)).unwrap();
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, add_remove, add_remove_registered);
+criterion_main!(benches);
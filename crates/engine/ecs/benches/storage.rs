@@ -0,0 +1,81 @@
+use criterion::{BenchmarkId, black_box, Criterion, criterion_group, criterion_main};
+
+use ecs::ecs::Ecs;
+use ecs::entity::EntityID;
+
+struct Position(#[allow(dead_code)] [f32; 4]);
+
+struct Velocity(#[allow(dead_code)] [f32; 4]);
+
+struct Mesh(#[allow(dead_code)] u64);
+
+struct Selected;
+
+const ENTITY_COUNT: usize = 10_000;
+
+fn spawn(ecs: &mut Ecs) -> Vec<EntityID> {
+    (0..ENTITY_COUNT).map(|_| {
+        let entity = ecs.create();
+        ecs.add(entity, Position([0.0; 4])).unwrap();
+        ecs.add(entity, Velocity([1.0; 4])).unwrap();
+        ecs.add(entity, Mesh(0)).unwrap();
+        entity
+    }).collect()
+}
+
+fn world(sparse: bool) -> (Ecs, Vec<EntityID>) {
+    let mut ecs = Ecs::default();
+    if sparse {
+        ecs.register_sparse::<Selected>();
+    }
+    let entities = spawn(&mut ecs);
+    (ecs, entities)
+}
+
+// Table markers move the whole entity row, sparse markers only touch their set
+fn toggle(c: &mut Criterion) {
+    let mut group = c.benchmark_group("toggle");
+    for (name, sparse) in [("table", false), ("sparse", true)] {
+        let (mut ecs, entities) = world(sparse);
+        group.bench_function(BenchmarkId::from_parameter(name), |b| {
+            b.iter(|| {
+                for entity in entities.iter().step_by(10) {
+                    ecs.add(black_box(*entity), Selected).unwrap();
+                }
+                for entity in entities.iter().step_by(10) {
+                    ecs.remove::<Selected>(black_box(*entity)).unwrap();
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+// Iterating table components is not slowed down by sparse markers, filtering on them costs a lookup per entity
+fn iterate(c: &mut Criterion) {
+    let mut group = c.benchmark_group("iterate");
+    for (name, sparse) in [("table", false), ("sparse", true)] {
+        let (mut ecs, entities) = world(sparse);
+        for entity in entities.iter().step_by(10) {
+            ecs.add(*entity, Selected).unwrap();
+        }
+        group.bench_function(BenchmarkId::new("all", name), |b| {
+            b.iter(|| {
+                for (position, velocity) in ecs.query::<(&mut Position, &Velocity)>() {
+                    position.0[0] += velocity.0[0];
+                }
+            })
+        });
+        group.bench_function(BenchmarkId::new("selected", name), |b| {
+            b.iter(|| {
+                for (position, velocity, _) in ecs.query::<(&mut Position, &Velocity, &Selected)>() {
+                    position.0[0] += velocity.0[0];
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, toggle, iterate);
+criterion_main!(benches);
@@ -1,50 +1,234 @@
-﻿use std::collections::HashMap;
+﻿use std::alloc::{alloc, dealloc, handle_alloc_error, Layout, realloc};
+use std::cell::UnsafeCell;
+use std::collections::HashMap;
+use std::ptr::NonNull;
 
-use crate::component::{ComponentID, ComponentRegistry};
+use crate::component::{ChangeTick, CloneFn, ComponentID, ComponentRegistry, DropFn};
 use crate::entity::EntityID;
 
 pub type ArchetypeID = u32;
 
 pub struct ComponentData {
     id: ComponentID,
-    data: Vec<u8>,
-    type_size: usize,
+    layout: Layout,
+    drop: Option<DropFn>,
+    data: NonNull<u8>,
+    capacity: usize,
     entity_count: usize,
+    added_ticks: Vec<ChangeTick>,
+    // Updated through shared references by mutable queries
+    changed_ticks: Vec<UnsafeCell<ChangeTick>>,
 }
 
 impl ComponentData {
-    pub fn new(id: ComponentID, type_size: usize) -> ComponentData {
-        Self { id, data: vec![], type_size, entity_count: 0 }
+    pub fn new(id: ComponentID, layout: Layout, drop: Option<DropFn>) -> ComponentData {
+        Self {
+            id,
+            layout,
+            drop,
+            // Dangling pointer that still respect the component alignment
+            data: unsafe { NonNull::new_unchecked(layout.align() as *mut u8) },
+            capacity: if layout.size() == 0 { usize::MAX } else { 0 },
+            entity_count: 0,
+            added_ticks: vec![],
+            changed_ticks: vec![],
+        }
+    }
+
+    pub fn id(&self) -> &ComponentID {
+        &self.id
+    }
+
+    pub fn len(&self) -> usize {
+        self.entity_count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entity_count == 0
+    }
+
+    fn array_layout(&self, count: usize) -> Layout {
+        Layout::from_size_align(self.layout.size() * count, self.layout.align()).expect("component array is too large")
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        let required = self.entity_count + additional;
+        if required <= self.capacity {
+            return;
+        }
+        let new_capacity = required.max(self.capacity * 2).max(4);
+        let new_layout = self.array_layout(new_capacity);
+        let new_data = unsafe {
+            if self.capacity == 0 {
+                alloc(new_layout)
+            } else {
+                realloc(self.data.as_ptr(), self.array_layout(self.capacity), new_layout.size())
+            }
+        };
+        self.data = NonNull::new(new_data).unwrap_or_else(|| handle_alloc_error(new_layout));
+        self.capacity = new_capacity;
+    }
+
+    /// # Safety
+    /// `src` must point to a valid component of this column type. Ownership is transferred to the column.
+    pub unsafe fn push(&mut self, src: *const u8, tick: ChangeTick) {
+        self.push_with_ticks(src, tick, tick);
     }
 
-    pub fn extend(&mut self) {
-        self.resize(self.entity_count + 1);
+    unsafe fn push_with_ticks(&mut self, src: *const u8, added_tick: ChangeTick, changed_tick: ChangeTick) {
+        self.reserve(1);
+        std::ptr::copy_nonoverlapping(src, self.get_ptr(self.entity_count), self.layout.size());
+        self.added_ticks.push(added_tick);
+        self.changed_ticks.push(UnsafeCell::new(changed_tick));
+        self.entity_count += 1;
+    }
+
+    /// # Safety
+    /// `src` must point to a valid component of this column type. Ownership is transferred to the column.
+    pub unsafe fn replace(&mut self, entity_index: &usize, src: *const u8, tick: ChangeTick) {
+        let dst = self.get_ptr(*entity_index);
+        if let Some(drop) = self.drop {
+            drop(dst);
+        }
+        std::ptr::copy_nonoverlapping(src, dst, self.layout.size());
+        self.mark_changed(entity_index, tick);
+    }
+
+    // Move the last element into the removed slot to match entities' swap_remove
+    fn swap_remove_forget(&mut self, entity_index: &usize) {
+        let last_index = self.entity_count - 1;
+        if *entity_index != last_index {
+            unsafe { std::ptr::copy_nonoverlapping(self.get_ptr(last_index), self.get_ptr(*entity_index), self.layout.size()); }
+        }
+        self.added_ticks.swap_remove(*entity_index);
+        self.changed_ticks.swap_remove(*entity_index);
+        self.entity_count -= 1;
     }
 
     pub fn drop_index(&mut self, entity_index: &usize) {
+        if let Some(drop) = self.drop {
+            unsafe { drop(self.get_ptr(*entity_index)); }
+        }
+        self.swap_remove_forget(entity_index);
+    }
+
+    // Move component into another column of the same type, keeping its change ticks
+    fn move_index(&mut self, entity_index: &usize, dst: &mut ComponentData) {
+        unsafe { dst.push_with_ticks(self.get_ptr(*entity_index), self.added_ticks[*entity_index], self.changed_tick(*entity_index)); }
+        self.swap_remove_forget(entity_index);
+    }
+
+    // Copy of the column, keeping change ticks unless a new one is given. Components without clone function are copied bitwise.
+    pub(crate) fn duplicate(&self, clone: Option<CloneFn>, tick: Option<ChangeTick>) -> ComponentData {
+        let mut copy = ComponentData::new(self.id, self.layout, self.drop);
+        copy.reserve(self.entity_count);
         unsafe {
-            let dst = self.data.as_mut_ptr().offset((entity_index * self.type_size) as isize);
-            let src = self.data.as_ptr().offset((entity_index * self.type_size) as isize);
-            std::ptr::copy(src, dst, self.type_size);
+            match clone {
+                Some(clone) => {
+                    for i in 0..self.entity_count {
+                        clone(self.get_ptr(i), copy.get_ptr(i));
+                    }
+                }
+                None => { std::ptr::copy_nonoverlapping(self.data.as_ptr(), copy.data.as_ptr(), self.layout.size() * self.entity_count) }
+            }
         }
+        copy.entity_count = self.entity_count;
+        copy.added_ticks = (0..self.entity_count).map(|i| tick.unwrap_or(self.added_ticks[i])).collect();
+        copy.changed_ticks = (0..self.entity_count).map(|i| UnsafeCell::new(tick.unwrap_or_else(|| self.changed_tick(i)))).collect();
+        copy
+    }
 
-        self.resize(self.entity_count - 1);
+    pub fn mark_changed(&mut self, entity_index: &usize, tick: ChangeTick) {
+        *self.changed_ticks[*entity_index].get_mut() = tick;
     }
 
-    fn resize(&mut self, new_entity_count: usize) {
-        self.entity_count = new_entity_count;
-        self.data.resize(self.entity_count * self.type_size, 0);
+    pub fn added_tick(&self, entity_index: usize) -> ChangeTick {
+        self.added_ticks[entity_index]
     }
 
-    pub fn get_component_data(&self, index: &usize) -> &[u8] {
-        &self.data.as_slice()[*index * self.type_size..(*index + 1) * self.type_size]
+    pub fn changed_tick(&self, entity_index: usize) -> ChangeTick {
+        unsafe { *self.changed_ticks[entity_index].get() }
     }
 
-    pub fn update_component_data(&mut self, entity_index: &usize, data: &[u8]) {
-        unsafe {
-            let dst = self.data.as_mut_ptr().offset((entity_index * self.type_size) as isize);
-            let src = data.as_ptr();
-            std::ptr::copy(src, dst, self.type_size)
+    pub fn added_ticks_ptr(&self) -> *const ChangeTick {
+        self.added_ticks.as_ptr()
+    }
+
+    // Writing through this pointer requires an exclusive access to the column, as granted to mutable queries
+    pub fn changed_ticks_ptr(&self) -> *mut ChangeTick {
+        UnsafeCell::raw_get(self.changed_ticks.as_ptr())
+    }
+
+    pub fn data_ptr(&self) -> *mut u8 {
+        self.data.as_ptr()
+    }
+
+    pub fn get_ptr(&self, entity_index: usize) -> *mut u8 {
+        unsafe { self.data.as_ptr().add(entity_index * self.layout.size()) }
+    }
+}
+
+impl Drop for ComponentData {
+    fn drop(&mut self) {
+        if let Some(drop) = self.drop {
+            for i in 0..self.entity_count {
+                unsafe { drop(self.get_ptr(i)); }
+            }
+        }
+        if self.layout.size() != 0 && self.capacity != 0 {
+            unsafe { dealloc(self.data.as_ptr(), self.array_layout(self.capacity)); }
+        }
+    }
+}
+
+// Column storage is only accessed through the ecs which ensure exclusive access to mutated data
+unsafe impl Send for ComponentData {}
+unsafe impl Sync for ComponentData {}
+
+/*
+STATE
+ */
+
+// Copy of the entities and components of an archetype
+pub(crate) struct ArchetypeState {
+    pub(crate) data: Vec<ComponentData>,
+    pub(crate) entities: Vec<EntityID>,
+}
+
+impl ArchetypeState {
+    pub(crate) fn column(&self, id: &ComponentID) -> Option<&ComponentData> {
+        self.data.binary_search_by(|column| column.id.cmp(id)).ok().map(|index| &self.data[index])
+    }
+
+    fn duplicate(data: &[ComponentData], entities: &[EntityID], registry: &ComponentRegistry, tick: Option<ChangeTick>) -> Self {
+        Self {
+            data: data.iter().map(|column| column.duplicate(registry.get(&column.id).clone, tick)).collect(),
+            entities: entities.to_vec(),
+        }
+    }
+}
+
+/*
+EDGES
+ */
+
+// Cached transitions to the archetypes obtained by adding or removing a single component.
+// Sorted by component id to be searched without hashing.
+#[derive(Default)]
+struct ArchetypeEdges {
+    add: Vec<(ComponentID, ArchetypeID)>,
+    remove: Vec<(ComponentID, ArchetypeID)>,
+}
+
+impl ArchetypeEdges {
+    fn find(edges: &[(ComponentID, ArchetypeID)], id: &ComponentID) -> Option<ArchetypeID> {
+        edges.binary_search_by(|(component, _)| component.cmp(id)).ok().map(|index| edges[index].1)
+    }
+
+    fn insert(edges: &mut Vec<(ComponentID, ArchetypeID)>, id: ComponentID, target: ArchetypeID) {
+        match edges.binary_search_by(|(component, _)| component.cmp(&id)) {
+            Ok(index) => { edges[index].1 = target }
+            Err(index) => { edges.insert(index, (id, target)) }
         }
     }
 }
@@ -57,7 +241,9 @@ STRUCTURE
 pub struct Archetype {
     data: Vec<ComponentData>,
     entities: Vec<EntityID>,
+    // Sorted component set
     components: Vec<ComponentID>,
+    edges: ArchetypeEdges,
 }
 
 impl Archetype {
@@ -65,63 +251,101 @@ impl Archetype {
         let mut data = vec![];
 
         for comp in components {
-            data.push(ComponentData::new(*comp, registry.get_layout(comp).size()))
+            let infos = registry.get(comp);
+            data.push(ComponentData::new(*comp, infos.layout, infos.drop))
         }
 
         Archetype {
             data,
             entities: vec![],
             components: components.into(),
+            edges: ArchetypeEdges::default(),
         }
     }
 
+    // Register entity, component data should be pushed right after
     pub fn push_entity(&mut self, entity: EntityID) -> usize {
         self.entities.push(entity);
-        for comp in &mut self.data {
-            comp.extend();
-        }
         self.entities.len() - 1
     }
 
-    pub fn drop_entity(&mut self, entity_index: &usize) {
+    // Drop entity and its components. Return the entity which was moved to the dropped index, if any.
+    pub fn drop_entity(&mut self, entity_index: &usize) -> Option<EntityID> {
         for comp in &mut self.data {
             comp.drop_index(entity_index);
         }
         self.entities.swap_remove(*entity_index);
+        self.entities.get(*entity_index).cloned()
     }
 
-    pub fn update_component_data(&mut self, entity_index: &usize, id: &ComponentID, data: Vec<u8>) {
+    // Move entity and the components shared with the destination archetype. Other components are dropped.
+    // Return the new entity index and the entity which was moved to the old index, if any.
+    pub fn move_entity(&mut self, entity_index: &usize, dst: &mut Archetype) -> (usize, Option<EntityID>) {
+        let new_index = dst.push_entity(self.entities[*entity_index]);
+        // Columns of both archetypes are sorted by component id : walk them together
+        let mut dst_columns = dst.data.iter_mut().peekable();
         for comp in &mut self.data {
-            if comp.id == *id {
-                comp.update_component_data(entity_index, data.as_slice());
-                break;
+            while dst_columns.next_if(|dst_comp| dst_comp.id < comp.id).is_some() {}
+            match dst_columns.next_if(|dst_comp| dst_comp.id == comp.id) {
+                None => { comp.drop_index(entity_index) }
+                Some(dst_comp) => { comp.move_index(entity_index, dst_comp) }
             }
         }
+        self.entities.swap_remove(*entity_index);
+        (new_index, self.entities.get(*entity_index).cloned())
     }
-    
+
     pub fn components(&self) -> &Vec<ComponentID> {
         &self.components
     }
 
-    pub fn entity_data(&self, entity_index: &usize) -> Vec<(ComponentID, Vec<u8>)> {
-        let mut data = Vec::with_capacity(self.components.len());
-        for comp in &self.data {
-            let start = entity_index * comp.type_size;
-            let end = (entity_index + 1) * comp.type_size;
-            
-            let sub_vec = &comp.data[start..end];
-            data.push((comp.id, sub_vec.into()))
-        }
-        data
+    pub(crate) fn save_state(&self, registry: &ComponentRegistry) -> ArchetypeState {
+        ArchetypeState::duplicate(&self.data, &self.entities, registry, None)
     }
-    
-    pub fn last_index(&self) -> usize {
-        self.data[0].entity_count - 1
+
+    // Current components are dropped. Loaded components are marked as added at the given tick.
+    pub(crate) fn load_state(&mut self, state: &ArchetypeState, registry: &ComponentRegistry, tick: ChangeTick) {
+        debug_assert!(state.data.iter().map(|column| column.id).eq(self.components.iter().cloned()), "archetype state does not match");
+        let ArchetypeState { data, entities } = ArchetypeState::duplicate(&state.data, &state.entities, registry, Some(tick));
+        self.data = data;
+        self.entities = entities;
     }
-    
+
+    pub(crate) fn clear(&mut self) {
+        for column in &mut self.data {
+            *column = ComponentData::new(column.id, column.layout, column.drop);
+        }
+        self.entities.clear();
+    }
+
     pub fn entity_at(&self, entity_index: &usize) -> &EntityID {
         &self.entities[*entity_index]
     }
+
+    pub fn entities(&self) -> &[EntityID] {
+        &self.entities
+    }
+
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    pub fn contains(&self, id: &ComponentID) -> bool {
+        self.components.binary_search(id).is_ok()
+    }
+
+    // Columns are stored in the same order as the sorted component set
+    pub fn column(&self, id: &ComponentID) -> Option<&ComponentData> {
+        self.components.binary_search(id).ok().map(|index| &self.data[index])
+    }
+
+    pub fn column_mut(&mut self, id: &ComponentID) -> Option<&mut ComponentData> {
+        self.components.binary_search(id).ok().map(|index| &mut self.data[index])
+    }
 }
 
 /*
@@ -132,14 +356,28 @@ REGISTRY
 pub struct ArchetypeRegistry {
     archetypes: Vec<Archetype>,
     registry_map: HashMap<Vec<ComponentID>, ArchetypeID>,
+    // Transitions from entities without components
+    root_edges: ArchetypeEdges,
+    // Archetype without components, holding entities that only own sparse set components
+    empty: Option<ArchetypeID>,
 }
 
 impl ArchetypeRegistry {
+    // Components sets are sorted to get a canonical archetype whatever the insertion order
     pub fn find_or_create(&mut self, components: &[ComponentID], registry: &ComponentRegistry) -> ArchetypeID {
-        match self.registry_map.get(components) {
+        if components.is_empty() {
+            return ArchetypeID::MAX;
+        }
+        let mut components = components.to_vec();
+        components.sort();
+        components.dedup();
+
+        match self.registry_map.get(&components) {
             None => {
-                self.archetypes.push(Archetype::new(components, registry));
-                (self.archetypes.len() - 1) as ArchetypeID
+                let id = self.archetypes.len() as ArchetypeID;
+                self.archetypes.push(Archetype::new(&components, registry));
+                self.registry_map.insert(components, id);
+                id
             }
             Some(found_id) => {
                 *found_id
@@ -147,11 +385,71 @@ impl ArchetypeRegistry {
         }
     }
 
+    fn edges(&self, id: &ArchetypeID) -> &ArchetypeEdges {
+        if *id == ArchetypeID::MAX { &self.root_edges } else { &self.get_archetype(id).edges }
+    }
+
+    fn edges_mut(&mut self, id: &ArchetypeID) -> &mut ArchetypeEdges {
+        if *id == ArchetypeID::MAX { &mut self.root_edges } else { &mut self.get_archetype_mut(id).edges }
+    }
+
+    pub fn find_or_create_empty(&mut self) -> ArchetypeID {
+        *self.empty.get_or_insert_with(|| {
+            self.archetypes.push(Archetype::default());
+            self.archetypes.len() as ArchetypeID - 1
+        })
+    }
+
+    pub fn is_empty_archetype(&self, id: &ArchetypeID) -> bool {
+        self.empty == Some(*id)
+    }
+
+    pub fn find_add_edge(&self, from: &ArchetypeID, component: &ComponentID) -> Option<ArchetypeID> {
+        ArchetypeEdges::find(&self.edges(from).add, component)
+    }
+
+    pub fn find_remove_edge(&self, from: &ArchetypeID, component: &ComponentID) -> Option<ArchetypeID> {
+        ArchetypeEdges::find(&self.edges(from).remove, component)
+    }
+
+    // Register that adding 'component' to 'from' leads to 'to', and that removing it from 'to' leads back to 'from'
+    pub fn insert_edge(&mut self, from: &ArchetypeID, component: &ComponentID, to: &ArchetypeID) {
+        ArchetypeEdges::insert(&mut self.edges_mut(from).add, *component, *to);
+        ArchetypeEdges::insert(&mut self.edges_mut(to).remove, *component, *from);
+    }
+
+    pub fn len(&self) -> usize {
+        self.archetypes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.archetypes.is_empty()
+    }
+
+    pub(crate) fn iter_mut(&mut self) -> std::slice::IterMut<'_, Archetype> {
+        self.archetypes.iter_mut()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Archetype> {
+        self.archetypes.iter()
+    }
+
     pub fn get_archetype(&self, id: &ArchetypeID) -> &Archetype {
         &self.archetypes[*id as usize]
     }
-    
+
     pub fn get_archetype_mut(&mut self, id: &ArchetypeID) -> &mut Archetype {
-        self.archetypes.get_mut(*id as usize).expect(format!("Requested archetype id '{id}' is not valid").as_str())
+        self.archetypes.get_mut(*id as usize).unwrap_or_else(|| panic!("Requested archetype id '{id}' is not valid"))
+    }
+
+    pub fn get_archetype_pair_mut(&mut self, a: &ArchetypeID, b: &ArchetypeID) -> (&mut Archetype, &mut Archetype) {
+        assert_ne!(a, b, "Cannot borrow the same archetype twice");
+        if a < b {
+            let (left, right) = self.archetypes.split_at_mut(*b as usize);
+            (&mut left[*a as usize], &mut right[0])
+        } else {
+            let (left, right) = self.archetypes.split_at_mut(*a as usize);
+            (&mut right[0], &mut left[*b as usize])
+        }
     }
 }
@@ -0,0 +1,432 @@
+use std::collections::HashMap;
+use std::mem::ManuallyDrop;
+use std::ptr::NonNull;
+
+use crate::component::{ChangeTicks, Component, ComponentID, ComponentRegistry};
+use crate::ecs::Ecs;
+use crate::entity::EntityID;
+use crate::error::EcsError;
+use crate::event::Event;
+use crate::query::Access;
+use crate::system::SystemParam;
+
+/*
+BOXED COMPONENT
+ */
+
+// Heap allocated component waiting to be moved into an archetype
+pub struct BoxedComponent {
+    id: ComponentID,
+    data: NonNull<u8>,
+    register: fn(&mut ComponentRegistry),
+    free: unsafe fn(NonNull<u8>, bool),
+}
+
+// Only created from components, which are Send + Sync
+unsafe impl Send for BoxedComponent {}
+unsafe impl Sync for BoxedComponent {}
+
+impl BoxedComponent {
+    pub fn new<C: Component>(component: C) -> Self {
+        Self {
+            id: ComponentID::of::<C>(),
+            data: NonNull::from(Box::leak(Box::new(ManuallyDrop::new(component)))).cast(),
+            register: ComponentRegistry::register_component::<C>,
+            free: Self::free::<C>,
+        }
+    }
+
+    pub fn id(&self) -> &ComponentID {
+        &self.id
+    }
+
+    pub fn register_fn(&self) -> fn(&mut ComponentRegistry) {
+        self.register
+    }
+
+    pub fn data(&self) -> *const u8 {
+        self.data.as_ptr()
+    }
+
+    pub fn data_mut(&mut self) -> *mut u8 {
+        self.data.as_ptr()
+    }
+
+    // Release the allocation once the component was moved into the ecs
+    pub fn forget(self) {
+        let this = ManuallyDrop::new(self);
+        unsafe { (this.free)(this.data, false) }
+    }
+
+    unsafe fn free<C>(data: NonNull<u8>, drop_value: bool) {
+        let mut component = Box::from_raw(data.cast::<ManuallyDrop<C>>().as_ptr());
+        if drop_value {
+            ManuallyDrop::drop(&mut component);
+        }
+    }
+}
+
+impl Drop for BoxedComponent {
+    fn drop(&mut self) {
+        unsafe { (self.free)(self.data, true) }
+    }
+}
+
+/*
+COMMAND QUEUE
+ */
+
+enum Command {
+    Insert(EntityID, BoxedComponent),
+    Remove(EntityID, ComponentID),
+    SetParent(EntityID, Option<EntityID>),
+    Despawn(EntityID, bool),
+}
+
+#[derive(Default)]
+struct PendingChanges {
+    inserts: Vec<BoxedComponent>,
+    removes: Vec<ComponentID>,
+    // None : unchanged, Some(None) : remove parent
+    parent: Option<Option<EntityID>>,
+    despawn: bool,
+    recursive: bool,
+}
+
+type DeferredTrigger = Box<dyn FnOnce(&mut Ecs) + Send + Sync>;
+
+// Sent as an event when a command recorded by a system cannot be applied, e.g. on a dead entity
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandError(pub EcsError);
+
+#[derive(Default)]
+pub struct CommandQueue {
+    commands: Vec<Command>,
+    // Run once entity changes are applied
+    triggers: Vec<DeferredTrigger>,
+}
+
+impl CommandQueue {
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty() && self.triggers.is_empty()
+    }
+
+    // Apply recorded commands. Changes are merged per entity so that each entity is moved at most once.
+    pub fn apply(&mut self, world: &mut Ecs) -> Vec<EcsError> {
+        world.flush_entities();
+
+        let mut entity_order = vec![];
+        let mut changes = HashMap::<EntityID, PendingChanges>::new();
+        for command in self.commands.drain(..) {
+            let entity = match &command {
+                Command::Insert(entity, _) | Command::Remove(entity, _) | Command::SetParent(entity, _) | Command::Despawn(entity, _) => { *entity }
+            };
+            let pending = changes.entry(entity).or_insert_with(|| {
+                entity_order.push(entity);
+                PendingChanges::default()
+            });
+            if pending.despawn {
+                continue;
+            }
+            match command {
+                Command::Insert(_, component) => {
+                    pending.removes.retain(|id| id != component.id());
+                    pending.inserts.retain(|other| other.id() != component.id());
+                    pending.inserts.push(component);
+                }
+                Command::Remove(_, id) => {
+                    pending.inserts.retain(|other| *other.id() != id);
+                    pending.removes.push(id);
+                }
+                Command::SetParent(_, parent) => {
+                    pending.parent = Some(parent);
+                }
+                Command::Despawn(_, recursive) => {
+                    *pending = PendingChanges { despawn: true, recursive, ..Default::default() };
+                }
+            }
+        }
+
+        let mut errors = vec![];
+        for entity in entity_order {
+            let pending = changes.remove(&entity).expect("missing pending changes");
+            let result = match pending {
+                PendingChanges { despawn: true, recursive: true, .. } => { world.despawn_recursive(entity) }
+                PendingChanges { despawn: true, .. } => { world.destroy(entity) }
+                PendingChanges { inserts, removes, parent, .. } => {
+                    world.apply_changes(entity, inserts, removes).and_then(|_| match parent {
+                        None => { Ok(()) }
+                        Some(None) => { world.remove_parent(entity) }
+                        Some(Some(parent)) => { world.set_parent(entity, parent) }
+                    })
+                }
+            };
+            if let Err(error) = result {
+                errors.push(error);
+            }
+        }
+
+        for trigger in std::mem::take(&mut self.triggers) {
+            trigger(world);
+        }
+        errors
+    }
+}
+
+/*
+COMMANDS
+ */
+
+pub struct Commands<'w, 's> {
+    queue: &'s mut CommandQueue,
+    world: &'w Ecs,
+}
+
+impl<'w, 's> Commands<'w, 's> {
+    pub fn new(queue: &'s mut CommandQueue, world: &'w Ecs) -> Self {
+        Self { queue, world }
+    }
+
+    // Create an entity, it will be alive once the commands are applied
+    pub fn spawn(&mut self) -> EntityCommands<'_, 'w, 's> {
+        let entity = self.world.reserve_entity();
+        EntityCommands { entity, commands: self }
+    }
+
+    pub fn entity(&mut self, entity: EntityID) -> EntityCommands<'_, 'w, 's> {
+        EntityCommands { entity, commands: self }
+    }
+
+    pub fn insert<C: Component>(&mut self, entity: EntityID, component: C) {
+        self.queue.commands.push(Command::Insert(entity, BoxedComponent::new(component)));
+    }
+
+    pub fn remove<C: Component>(&mut self, entity: EntityID) {
+        self.queue.commands.push(Command::Remove(entity, ComponentID::of::<C>()));
+    }
+
+    pub fn set_parent(&mut self, child: EntityID, parent: EntityID) {
+        self.queue.commands.push(Command::SetParent(child, Some(parent)));
+    }
+
+    pub fn remove_parent(&mut self, child: EntityID) {
+        self.queue.commands.push(Command::SetParent(child, None));
+    }
+
+    pub fn despawn(&mut self, entity: EntityID) {
+        self.queue.commands.push(Command::Despawn(entity, false));
+    }
+
+    pub fn despawn_recursive(&mut self, entity: EntityID) {
+        self.queue.commands.push(Command::Despawn(entity, true));
+    }
+
+    pub fn trigger<E: Event>(&mut self, event: E) {
+        self.queue.triggers.push(Box::new(move |world| world.trigger(event)));
+    }
+
+    pub fn trigger_for<E: Event>(&mut self, target: EntityID, event: E) {
+        self.queue.triggers.push(Box::new(move |world| world.trigger_for(target, event)));
+    }
+}
+
+pub struct EntityCommands<'a, 'w, 's> {
+    entity: EntityID,
+    commands: &'a mut Commands<'w, 's>,
+}
+
+impl EntityCommands<'_, '_, '_> {
+    pub fn id(&self) -> EntityID {
+        self.entity
+    }
+
+    pub fn insert<C: Component>(&mut self, component: C) -> &mut Self {
+        self.commands.insert(self.entity, component);
+        self
+    }
+
+    pub fn remove<C: Component>(&mut self) -> &mut Self {
+        self.commands.remove::<C>(self.entity);
+        self
+    }
+
+    pub fn set_parent(&mut self, parent: EntityID) -> &mut Self {
+        self.commands.set_parent(self.entity, parent);
+        self
+    }
+
+    pub fn remove_parent(&mut self) -> &mut Self {
+        self.commands.remove_parent(self.entity);
+        self
+    }
+
+    pub fn despawn(&mut self) {
+        self.commands.despawn(self.entity);
+    }
+
+    pub fn despawn_recursive(&mut self) {
+        self.commands.despawn_recursive(self.entity);
+    }
+
+    pub fn trigger<E: Event>(&mut self, event: E) -> &mut Self {
+        self.commands.trigger_for(self.entity, event);
+        self
+    }
+}
+
+unsafe impl SystemParam for Commands<'_, '_> {
+    type State = CommandQueue;
+    type Item<'w, 's> = Commands<'w, 's>;
+
+    fn init_state(_: &mut Ecs) -> Self::State {
+        CommandQueue::default()
+    }
+
+    // Entity reservation is thread safe, and commands are only applied at sync points
+    fn access(_: &mut Access) {}
+
+    unsafe fn fetch<'w, 's>(state: &'s mut Self::State, world: &'w Ecs, _: ChangeTicks) -> Self::Item<'w, 's> {
+        Commands::new(state, world)
+    }
+
+    fn apply(state: &mut Self::State, world: &mut Ecs) {
+        for error in state.apply(world) {
+            world.send(CommandError(error));
+        }
+    }
+}
+
+/*
+TESTS
+ */
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::command::{CommandError, CommandQueue, Commands};
+    use crate::ecs::Ecs;
+    use crate::entity::EntityID;
+    use crate::error::EcsError;
+    use crate::query::Query;
+    use crate::resource::Res;
+    use crate::schedule::Schedule;
+
+    struct Spawner(u32);
+
+    struct Child(EntityID);
+
+    struct Position(f32);
+
+    struct Velocity(f32);
+
+    struct Marker;
+
+    struct Target(EntityID);
+
+    fn spawn_children(query: Query<(EntityID, &Spawner)>, mut commands: Commands) {
+        for (entity, spawner) in query {
+            let child = commands.spawn().insert(Position(spawner.0 as f32)).insert(Velocity(1.0)).id();
+            commands.entity(entity).insert(Child(child)).remove::<Spawner>();
+        }
+    }
+
+    #[test]
+    fn system_commands_test() {
+        let mut ecs = Ecs::default();
+        let spawners: Vec<EntityID> = (0..3).map(|i| {
+            let entity = ecs.create();
+            ecs.add(entity, Spawner(i)).unwrap();
+            entity
+        }).collect();
+
+        let mut schedule = Schedule::new(2);
+        schedule.add_system(spawn_children);
+        schedule.run(&mut ecs);
+        schedule.run(&mut ecs);
+
+        assert_eq!(ecs.query::<&Spawner>().count(), 0);
+        assert_eq!(ecs.query::<(&Position, &Velocity)>().count(), 3);
+        for (i, spawner) in spawners.iter().enumerate() {
+            let child = ecs.get::<Child>(*spawner).unwrap().0;
+            assert!(ecs.is_alive(child));
+            assert_eq!(ecs.get::<Position>(child).unwrap().0, i as f32);
+            assert_eq!(ecs.get::<Velocity>(child).unwrap().0, 1.0);
+        }
+    }
+
+    fn mark_target(target: Res<Target>, mut commands: Commands) {
+        commands.insert(target.0, Marker);
+    }
+
+    #[test]
+    fn command_error_test() {
+        let mut ecs = Ecs::default();
+        let entity = ecs.create();
+        ecs.destroy(entity).unwrap();
+        ecs.insert_resource(Target(entity));
+
+        let mut schedule = Schedule::new(1);
+        schedule.add_system(mark_target);
+        schedule.run(&mut ecs);
+        assert_eq!(ecs.read::<CommandError>().collect::<Vec<_>>(), vec![&CommandError(EcsError::DeadEntity(entity))]);
+    }
+
+    #[test]
+    fn batched_move_test() {
+        let mut ecs = Ecs::default();
+        let entity = ecs.create();
+        let shared = Arc::new(0);
+
+        let mut queue = CommandQueue::default();
+        let mut commands = Commands::new(&mut queue, &ecs);
+        commands.entity(entity)
+            .insert(Position(1.0))
+            .insert(Velocity(2.0))
+            .insert(shared.clone())
+            .remove::<Arc<i32>>()
+            .insert(Marker)
+            .insert(Position(3.0));
+        assert!(queue.apply(&mut ecs).is_empty());
+
+        // Entity moved directly from the empty archetype to the final one
+        assert_eq!(ecs.archetypes().iter().count(), 1);
+        assert_eq!(ecs.get::<Position>(entity).unwrap().0, 3.0);
+        assert!(ecs.get::<Marker>(entity).is_some());
+        assert!(ecs.get::<Arc<i32>>(entity).is_none());
+        assert_eq!(Arc::strong_count(&shared), 1);
+    }
+
+    #[test]
+    fn despawn_command_test() {
+        let mut ecs = Ecs::default();
+        let entity = ecs.create();
+        ecs.add(entity, Position(0.0)).unwrap();
+        let shared = Arc::new(0);
+
+        let mut queue = CommandQueue::default();
+        let mut commands = Commands::new(&mut queue, &ecs);
+        let spawned = commands.spawn().insert(shared.clone()).id();
+        commands.despawn(entity);
+        commands.insert(entity, Velocity(0.0));
+        commands.insert(entity, shared.clone());
+        assert_eq!(Arc::strong_count(&shared), 3);
+
+        assert!(queue.apply(&mut ecs).is_empty());
+        assert!(!ecs.is_alive(entity));
+        assert!(ecs.is_alive(spawned));
+        assert_eq!(Arc::strong_count(&shared), 2);
+
+        // Commands targeting dead entities are reported
+        let mut commands = Commands::new(&mut queue, &ecs);
+        commands.insert(entity, shared.clone());
+        assert_eq!(queue.apply(&mut ecs).len(), 1);
+        assert_eq!(Arc::strong_count(&shared), 2);
+
+        // Unapplied commands release their components
+        let mut commands = Commands::new(&mut queue, &ecs);
+        commands.insert(spawned, shared.clone());
+        drop(queue);
+        assert_eq!(Arc::strong_count(&shared), 2);
+    }
+}
@@ -1,33 +1,152 @@
 ﻿use std::alloc::Layout;
-use std::any::{Any, TypeId};
+use std::any::{Any, type_name, TypeId};
+use std::borrow::Cow;
 use std::collections::HashMap;
-use std::mem::{align_of, size_of};
+use std::mem::{align_of, needs_drop, size_of};
 
-pub type ComponentID = TypeId;
+use crate::ecs::Ecs;
+use crate::entity::EntityID;
+use crate::error::EcsError;
+use crate::reflect::{DynamicComponent, FieldInfo, Reflect};
+
+// Rust components are identified by their type, dynamic components by their registration index
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ComponentID {
+    Static(TypeId),
+    Dynamic(u32),
+}
+
+impl ComponentID {
+    pub fn of<C: Any>() -> Self {
+        Self::Static(TypeId::of::<C>())
+    }
+}
+
+// Components are shared between systems running on different threads
+pub trait Component: Any + Send + Sync {}
+
+impl<T: Any + Send + Sync> Component for T {}
+
+/*
+CHANGE DETECTION
+ */
+
+// World tick, advanced once per schedule run
+pub type Tick = u32;
+
+// Moment a component was changed : world tick, then the schedule sync point it happened after.
+// Sync points order changes made by different stages of the same run.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ChangeTick {
+    pub tick: Tick,
+    pub sync_point: u32,
+}
+
+// Ticks used to detect changes : everything modified after 'last_run' is considered new.
+// No 'last_run' means nothing was observed yet.
+#[derive(Copy, Clone, Debug)]
+pub struct ChangeTicks {
+    pub last_run: Option<ChangeTick>,
+    pub this_run: ChangeTick,
+}
+
+impl ChangeTicks {
+    pub fn is_newer(&self, tick: ChangeTick) -> bool {
+        self.last_run.is_none_or(|last_run| tick > last_run)
+    }
+}
+
+/*
+HOOKS
+ */
+
+pub type ComponentHook = fn(&mut Ecs, EntityID, ComponentID);
+
+// on_add : the entity did not own the component before. on_insert : after each insertion, including replacements.
+// on_remove : before the component is removed or its entity destroyed, while it can still be accessed.
+#[derive(Copy, Clone, Default)]
+pub struct ComponentHooks {
+    pub(crate) on_add: Option<ComponentHook>,
+    pub(crate) on_insert: Option<ComponentHook>,
+    pub(crate) on_remove: Option<ComponentHook>,
+}
+
+impl ComponentHooks {
+    pub fn on_add(&mut self, hook: ComponentHook) -> &mut Self {
+        self.on_add = Some(hook);
+        self
+    }
+
+    pub fn on_insert(&mut self, hook: ComponentHook) -> &mut Self {
+        self.on_insert = Some(hook);
+        self
+    }
+
+    pub fn on_remove(&mut self, hook: ComponentHook) -> &mut Self {
+        self.on_remove = Some(hook);
+        self
+    }
+}
 
 /*
 STRUCTURE
  */
 
+// Table components are stored in archetype columns, fast to iterate. Sparse set components are stored per type,
+// fast to add and remove without moving the entity to another archetype.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum StorageType {
+    #[default]
+    Table,
+    SparseSet,
+}
+
+pub type DropFn = unsafe fn(*mut u8);
+// Write a clone of the source component into uninitialized destination memory
+pub type CloneFn = unsafe fn(*const u8, *mut u8);
+
 pub struct ComponentData {
+    pub name: Cow<'static, str>,
     pub layout: Layout,
+    pub drop: Option<DropFn>,
+    // Reflected fields, empty if the component was not registered with reflection
+    pub fields: Vec<FieldInfo>,
+    // Required to snapshot components that have drop glue, others are copied bitwise
+    pub clone: Option<CloneFn>,
 }
 
 impl ComponentData {
     pub fn new<C: Sized + Any>() -> ComponentData {
         Self {
-            layout: Layout::from_size_align(size_of::<C>(), align_of::<C>()).expect("layout error")
+            name: Cow::Borrowed(type_name::<C>()),
+            layout: Layout::from_size_align(size_of::<C>(), align_of::<C>()).expect("layout error"),
+            drop: if needs_drop::<C>() { Some(drop_ptr::<C>) } else { None },
+            fields: vec![],
+            clone: None,
         }
     }
 }
 
+unsafe fn drop_ptr<C>(ptr: *mut u8) {
+    ptr.cast::<C>().drop_in_place()
+}
+
+unsafe fn clone_ptr<C: Clone>(src: *const u8, dst: *mut u8) {
+    dst.cast::<C>().write((*src.cast::<C>()).clone())
+}
+
 /*
 REGISTRY
  */
 
 #[derive(Default)]
 pub struct ComponentRegistry {
-    components: HashMap<ComponentID, ComponentData>
+    components: HashMap<ComponentID, ComponentData>,
+    names: HashMap<Cow<'static, str>, ComponentID>,
+    dynamic_count: u32,
+    // Sorted to be searched without hashing when adding or removing components
+    sparse: Vec<ComponentID>,
+    hooks: Vec<(ComponentID, ComponentHooks)>,
 }
 
 impl ComponentRegistry {
@@ -37,10 +156,94 @@ impl ComponentRegistry {
     }
     
     pub fn register_component<C:Any>(&mut self) {
-        self.components.insert(ComponentID::of::<C>(), ComponentData::new::<C>());
+        let id = ComponentID::of::<C>();
+        if !self.components.contains_key(&id) {
+            self.names.insert(Cow::Borrowed(type_name::<C>()), id);
+            self.components.insert(id, ComponentData::new::<C>());
+        }
+    }
+
+    pub fn register_clone<C: Any + Clone>(&mut self) {
+        self.register_component::<C>();
+        self.components.get_mut(&ComponentID::of::<C>()).expect("component is not registered yet").clone = Some(clone_ptr::<C>);
+    }
+
+    // Must be called before the component is added to any entity
+    pub fn register_sparse<C: Any>(&mut self) {
+        self.register_component::<C>();
+        let id = ComponentID::of::<C>();
+        if let Err(index) = self.sparse.binary_search(&id) {
+            self.sparse.insert(index, id);
+        }
+    }
+
+    // Unregistered components are stored in tables
+    pub fn storage(&self, id: &ComponentID) -> StorageType {
+        if self.sparse.binary_search(id).is_ok() { StorageType::SparseSet } else { StorageType::Table }
+    }
+
+    // Register the component and expose its fields
+    pub fn register_reflect<C: Reflect>(&mut self) {
+        self.register_component::<C>();
+        self.components.get_mut(&ComponentID::of::<C>()).expect("component is not registered yet").fields = C::fields();
+    }
+
+    // Register a component that has no rust type. Its data is zero initialized when added to an entity.
+    pub fn register_dynamic(&mut self, component: DynamicComponent) -> Result<ComponentID, EcsError> {
+        if self.names.contains_key(component.name()) {
+            return Err(EcsError::DuplicateComponent(component.name().to_string()));
+        }
+        let id = ComponentID::Dynamic(self.dynamic_count);
+        self.dynamic_count += 1;
+        let (name, layout, fields) = component.into_parts();
+        self.names.insert(Cow::Owned(name.clone()), id);
+        self.components.insert(id, ComponentData { name: Cow::Owned(name), layout, drop: None, fields, clone: None });
+        Ok(id)
+    }
+
+    pub fn hooks_mut<C: Any>(&mut self) -> &mut ComponentHooks {
+        self.register_component::<C>();
+        self.hooks_entry(ComponentID::of::<C>())
+    }
+
+    // Hooks of dynamic components
+    pub fn hooks_by_id_mut(&mut self, id: &ComponentID) -> Option<&mut ComponentHooks> {
+        self.components.contains_key(id).then(|| self.hooks_entry(*id))
+    }
+
+    // None if the component has no hooks
+    pub fn hooks(&self, id: &ComponentID) -> Option<&ComponentHooks> {
+        self.hooks.binary_search_by(|(component, _)| component.cmp(id)).ok().map(|index| &self.hooks[index].1)
+    }
+
+    fn hooks_entry(&mut self, id: ComponentID) -> &mut ComponentHooks {
+        let index = match self.hooks.binary_search_by(|(component, _)| component.cmp(&id)) {
+            Ok(index) => { index }
+            Err(index) => {
+                self.hooks.insert(index, (id, ComponentHooks::default()));
+                index
+            }
+        };
+        &mut self.hooks[index].1
+    }
+
+    pub fn find(&self, name: &str) -> Option<ComponentID> {
+        self.names.get(name).cloned()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item=(&ComponentID, &ComponentData)> {
+        self.components.iter()
+    }
+
+    pub fn try_get(&self, id: &ComponentID) -> Option<&ComponentData> {
+        self.components.get(id)
     }
     
+    pub fn get(&self, id: &ComponentID) -> &ComponentData {
+        self.components.get(id).expect("component is not registered yet")
+    }
+
     pub fn get_layout(&self, id: &ComponentID) -> &Layout {
-        &self.components.get(id).expect("component is not registered yet").layout
+        &self.get(id).layout
     }
 }
@@ -1,154 +1,544 @@
-﻿use std::any::{Any, type_name};
-use std::collections::HashMap;
-use std::mem::size_of;
+﻿use std::any::type_name;
+use std::mem::ManuallyDrop;
 
-use crate::archetype::{ArchetypeID, ArchetypeRegistry};
-use crate::component::{ComponentID, ComponentRegistry};
+use crate::archetype::{ArchetypeID, ArchetypeRegistry, ComponentData};
+use crate::command::BoxedComponent;
+use crate::component::{ChangeTick, ChangeTicks, Component, ComponentHooks, ComponentID, ComponentRegistry, StorageType, Tick};
 use crate::entity::EntityID;
+use crate::error::EcsError;
+use crate::event::{Event, Events};
 use crate::id_generator::IdGenerator;
+use crate::observer::Observers;
+use crate::query::{Query, QueryData, QueryFilter};
+use crate::resource::{Resource, Resources};
+use crate::sparse_set::SparseSets;
+use crate::system::{IntoSystem, System};
+
+// Component moved into the ecs, registered only when a new archetype has to be created for it
+struct ComponentInsert {
+    id: ComponentID,
+    data: *const u8,
+    register: fn(&mut ComponentRegistry),
+}
 
 #[derive(Default)]
 pub struct Ecs {
-    entity_registry: HashMap<EntityID, (ArchetypeID, usize)>,
-    entity_id_manager: IdGenerator<EntityID>,
+    // Archetype and internal index of each entity, indexed by entity index
+    entity_registry: Vec<(ArchetypeID, usize)>,
+    entity_id_manager: IdGenerator,
     components: ComponentRegistry,
     archetypes: ArchetypeRegistry,
+    sparse_sets: SparseSets,
+    resources: Resources,
+    event_updaters: Vec<fn(&mut Ecs)>,
+    observers: Observers,
+    change_tick: Tick,
+    sync_point: u32,
 }
 
 impl Ecs {
     pub fn create(&mut self) -> EntityID {
         let new_id = self.entity_id_manager.acquire();
-        self.entity_registry.insert(new_id, (EntityID::MAX, usize::MAX));
+        self.set_location(new_id, (ArchetypeID::MAX, usize::MAX));
         new_id
     }
 
-    pub fn destroy(&mut self, entity: EntityID) {
-        match self.entity_registry.remove(&entity) {
-            None => {}
-            Some((entity_archetype, entity_index)) => {
-                if entity_archetype != ArchetypeID::MAX {
-                    self.archetypes.get_archetype_mut(&entity_archetype).drop_entity(&entity_index);
-                }
+    // Children of the destroyed entity become roots
+    pub fn destroy(&mut self, entity: EntityID) -> Result<(), EcsError> {
+        self.check_alive(entity)?;
+        // Hooks can remove or add components of the entity : look for the next one after each hook
+        let mut notified = vec![];
+        loop {
+            let next = self.components_of(entity).into_iter().find(|component| {
+                !notified.contains(component) && self.components.hooks(component).is_some_and(|hooks| hooks.on_remove.is_some())
+            });
+            let Some(component) = next else { break; };
+            notified.push(component);
+            self.trigger_remove_hook(entity, component);
+            // Destroyed by a hook
+            if !self.is_alive(entity) {
+                return Ok(());
             }
-        };
+        }
+        self.move_entity(entity, ArchetypeID::MAX);
+        self.sparse_sets.remove_entity(entity);
+        self.observers.remove_entity(entity);
         self.entity_id_manager.release(&entity);
+        Ok(())
     }
 
+    pub fn is_alive(&self, entity: EntityID) -> bool {
+        self.entity_id_manager.is_alive(&entity)
+    }
 
-    pub fn add<C: Any>(&mut self, entity: EntityID, mut component: C) {
-        let data = unsafe {
-            Vec::from_raw_parts(
-                &mut component as *mut C as *mut u8,
-                size_of::<C>(),
-                size_of::<C>())
-        };
+    // Alive entities, sorted by index
+    pub fn entities(&self) -> impl Iterator<Item=EntityID> + '_ {
+        self.entity_id_manager.iter_alive()
+    }
 
-        if !self.components.contains::<C>() { self.components.register_component::<C>(); }
+    // Reserve an entity id from a shared reference. The entity becomes alive on the next flush.
+    pub fn reserve_entity(&self) -> EntityID {
+        self.entity_id_manager.reserve()
+    }
 
-        self.add_component(entity, ComponentID::of::<C>(), data.as_slice());
+    pub fn flush_entities(&mut self) {
+        self.entity_id_manager.flush();
     }
 
-    pub fn remove<C: Any>(&mut self, entity: EntityID) {
-        self.remove_component::<C>(entity, ComponentID::of::<C>())
+    pub fn add<C: Component>(&mut self, entity: EntityID, component: C) -> Result<(), EcsError> {
+        self.check_alive(entity)?;
+
+        let component = ManuallyDrop::new(component);
+        unsafe { self.insert_raw(entity, ComponentID::of::<C>(), &*component as *const C as *const u8, ComponentRegistry::register_component::<C>); }
+        Ok(())
     }
 
-    fn add_component(&mut self, entity: EntityID, component: ComponentID, component_data: &[u8]) {
-        // Retrieve archetype and internal entity index
-        let (old_archetype_id, old_entity_index) = *self.entity_registry.get_mut(&entity).expect("The given entity is not registered yet");
+    pub fn contains<C: Component>(&self, entity: EntityID) -> bool {
+        self.has_component(entity, &ComponentID::of::<C>())
+    }
 
-        let mut data = vec![];
+    pub fn remove<C: Component>(&mut self, entity: EntityID) -> Result<(), EcsError> {
+        self.check_alive(entity)?;
+        if !self.components.contains::<C>() {
+            return Err(EcsError::MissingComponent(entity, type_name::<C>().into()));
+        }
+        self.remove_by_id(entity, ComponentID::of::<C>())
+    }
 
-        let new_components = if old_archetype_id == ArchetypeID::MAX {
-            // No components are bound to input entity
-            vec![component]
-        } else {
-            // Retrieve data from existing archetype, then drop entity from it
-            let old_archetype = self.archetypes.get_archetype_mut(&old_archetype_id);
+    pub fn get<C: Component>(&self, entity: EntityID) -> Option<&C> {
+        let (column, index) = self.component_column(entity, &ComponentID::of::<C>())?;
+        unsafe { Some(&*(column.get_ptr(index) as *const C)) }
+    }
 
-            let mut component_ids = old_archetype.components().clone();
-            data = old_archetype.entity_data(&old_entity_index).clone();
+    // Mark the component as changed
+    pub fn get_mut<C: Component>(&mut self, entity: EntityID) -> Option<&mut C> {
+        let tick = self.change_tick();
+        let (column, index) = self.component_column_mut(entity, &ComponentID::of::<C>())?;
+        column.mark_changed(&index, tick);
+        unsafe { Some(&mut *(column.get_ptr(index) as *mut C)) }
+    }
 
-            // Update swapped entity indexes
-            let swapped_entity_index = old_archetype.last_index();
-            let swapped_entity = old_archetype.entity_at(&swapped_entity_index).clone();
-            self.entity_registry.insert(swapped_entity, (old_archetype_id, swapped_entity_index));
+    // Components owned by the entity, sorted by id
+    pub fn components_of(&self, entity: EntityID) -> Vec<ComponentID> {
+        let mut components = self.location(entity).map(|(archetype_id, _)| self.archetypes.get_archetype(&archetype_id).components().clone()).unwrap_or_default();
+        if self.is_alive(entity) {
+            components.extend(self.sparse_sets.components_of(entity));
+            components.sort();
+        }
+        components
+    }
 
-            // Remove entity data
-            old_archetype.drop_entity(&old_entity_index);
-            component_ids.push(component);
-            component_ids
+    // Store the component in a sparse set instead of archetype tables. Must be called before it is added to any entity.
+    pub fn register_sparse<C: Component>(&mut self) {
+        let id = ComponentID::of::<C>();
+        assert!(!self.archetypes.iter().any(|archetype| archetype.contains(&id)), "'{}' is already stored in tables", type_name::<C>());
+        self.components.register_sparse::<C>();
+    }
 
-        };
+    pub fn component_hooks<C: Component>(&mut self) -> &mut ComponentHooks {
+        self.components.hooks_mut::<C>()
+    }
+
+    // Mark the component as changed without accessing it
+    pub fn set_changed<C: Component>(&mut self, entity: EntityID) -> bool {
+        self.get_mut::<C>(entity).is_some()
+    }
+
+    pub fn query<D: QueryData>(&mut self) -> Query<'_, D> {
+        Query::new(self)
+    }
+
+    pub fn query_filtered<D: QueryData, F: QueryFilter>(&mut self) -> Query<'_, D, F> {
+        Query::new(self)
+    }
+
+    pub fn tick(&self) -> Tick {
+        self.change_tick
+    }
 
-        // Find an archetype containing desired components
-        let new_archetype_id = self.archetypes.find_or_create(new_components.as_slice(), &self.components);
-        let new_archetype = self.archetypes.get_archetype_mut(&new_archetype_id);
+    pub fn change_tick(&self) -> ChangeTick {
+        ChangeTick { tick: self.change_tick, sync_point: self.sync_point }
+    }
 
-        // Register entity into new archetype
-        let new_entity_index = new_archetype.push_entity(entity);
+    // Called once per schedule run
+    pub fn increment_change_tick(&mut self) {
+        self.change_tick += 1;
+        self.sync_point = 0;
+    }
 
-        // Copy component data
-        for (comp_id, comp_data) in data {
-            new_archetype.update_component_data(&new_entity_index, &comp_id, comp_data)
+    pub(crate) fn set_sync_point(&mut self, sync_point: u32) {
+        self.sync_point = sync_point;
+    }
+
+    // Queries made outside of systems detect changes made since the beginning of the previous schedule run
+    pub(crate) fn change_ticks(&self) -> ChangeTicks {
+        ChangeTicks {
+            last_run: self.change_tick.checked_sub(2).map(|tick| ChangeTick { tick, sync_point: u32::MAX }),
+            this_run: self.change_tick(),
         }
-        new_archetype.update_component_data(&new_entity_index, &component, component_data.into());
+    }
 
-        // Update entity_registry infos
-        self.entity_registry.insert(entity, (new_archetype_id, new_entity_index));
+    pub fn run_system<M>(&mut self, system: impl IntoSystem<M>) {
+        system.into_system().run(self);
+    }
+
+    pub fn insert_resource<R: Resource>(&mut self, resource: R) {
+        self.resources.insert(resource);
+    }
+
+    pub fn remove_resource<R: Resource>(&mut self) -> Option<R> {
+        self.resources.remove::<R>()
     }
 
-    fn remove_component<C: Any>(&mut self, entity: EntityID, component: ComponentID) {
+    pub fn contains_resource<R: Resource>(&self) -> bool {
+        self.resources.contains::<R>()
+    }
+
+    pub fn resource<R: Resource>(&self) -> Option<&R> {
+        self.resources.get::<R>()
+    }
+
+    pub fn resource_mut<R: Resource>(&mut self) -> Option<&mut R> {
+        self.resources.get_mut::<R>()
+    }
+
+    pub(crate) fn resources(&self) -> &Resources {
+        &self.resources
+    }
+
+    pub fn add_event<E: Event>(&mut self) {
+        if !self.resources.contains::<Events<E>>() {
+            self.resources.insert(Events::<E>::default());
+            self.event_updaters.push(|ecs| ecs.resources.get_mut::<Events<E>>().expect("missing event resource").update());
+        }
+    }
+
+    pub fn send<E: Event>(&mut self, event: E) {
+        self.add_event::<E>();
+        self.resources.get_mut::<Events<E>>().expect("missing event resource").send(event);
+    }
+
+    pub fn read<E: Event>(&self) -> impl Iterator<Item=&E> {
+        self.resources.get::<Events<E>>().into_iter().flat_map(|events| events.read())
+    }
+
+    // Swap event buffers, dropping events that were sent before the previous update
+    pub fn update_events(&mut self) {
+        for updater in self.event_updaters.clone() {
+            updater(self);
+        }
+    }
+
+    pub(crate) fn observers_mut(&mut self) -> &mut Observers {
+        &mut self.observers
+    }
+
+    pub(crate) fn observers(&self) -> &Observers {
+        &self.observers
+    }
+
+    pub(crate) fn check_alive(&self, entity: EntityID) -> Result<(), EcsError> {
+        if self.is_alive(entity) { Ok(()) } else { Err(EcsError::DeadEntity(entity)) }
+    }
+
+    // Location of an alive entity that owns at least one component
+    pub(crate) fn location(&self, entity: EntityID) -> Option<(ArchetypeID, usize)> {
+        if !self.is_alive(entity) {
+            return None;
+        }
+        match self.raw_location(entity) {
+            (ArchetypeID::MAX, _) => { None }
+            location => { Some(location) }
+        }
+    }
+
+    fn raw_location(&self, entity: EntityID) -> (ArchetypeID, usize) {
+        self.entity_registry.get(entity.index() as usize).cloned().unwrap_or((ArchetypeID::MAX, usize::MAX))
+    }
+
+    fn set_location(&mut self, entity: EntityID, location: (ArchetypeID, usize)) {
+        let index = entity.index() as usize;
+        if index >= self.entity_registry.len() {
+            self.entity_registry.resize(index + 1, (ArchetypeID::MAX, usize::MAX));
+        }
+        self.entity_registry[index] = location;
+    }
+
+    pub(crate) fn archetypes(&self) -> &ArchetypeRegistry {
+        &self.archetypes
+    }
+
+    pub(crate) fn sparse_sets(&self) -> &SparseSets {
+        &self.sparse_sets
+    }
+
+    pub(crate) fn set_sparse_sets(&mut self, sparse_sets: SparseSets) {
+        self.sparse_sets = sparse_sets;
+    }
+
+    pub(crate) fn entity_locations(&self) -> &Vec<(ArchetypeID, usize)> {
+        &self.entity_registry
+    }
+
+    pub(crate) fn id_generator(&self) -> &IdGenerator {
+        &self.entity_id_manager
+    }
+
+    pub(crate) fn id_generator_mut(&mut self) -> &mut IdGenerator {
+        &mut self.entity_id_manager
+    }
+
+    // Entity locations must match the archetypes content
+    pub(crate) fn set_entity_state(&mut self, locations: Vec<(ArchetypeID, usize)>, ids: IdGenerator) {
+        self.entity_registry = locations;
+        self.entity_id_manager = ids;
+    }
+
+    pub fn components(&self) -> &ComponentRegistry {
+        &self.components
+    }
+
+    pub(crate) fn components_mut(&mut self) -> &mut ComponentRegistry {
+        &mut self.components
+    }
+
+    pub(crate) fn archetypes_and_components_mut(&mut self) -> (&mut ArchetypeRegistry, &ComponentRegistry) {
+        (&mut self.archetypes, &self.components)
+    }
+
+    /// # Safety
+    /// `data` must point to a valid component of the given id. Ownership is transferred to the ecs.
+    pub(crate) unsafe fn insert_raw(&mut self, entity: EntityID, id: ComponentID, data: *const u8, register: fn(&mut ComponentRegistry)) {
+        let added = !self.has_component(entity, &id);
+        self.insert_components(entity, &[ComponentInsert { id, data, register }], &[]);
+        self.trigger_insert_hooks(entity, id, added);
+    }
+
+    fn has_component(&self, entity: EntityID, component: &ComponentID) -> bool {
+        self.component_column(entity, component).is_some()
+    }
+
+    // Column holding the component of the entity, with the index of the entity in it
+    pub(crate) fn component_column(&self, entity: EntityID, component: &ComponentID) -> Option<(&ComponentData, usize)> {
+        match self.components.storage(component) {
+            StorageType::Table => {
+                let (archetype_id, entity_index) = self.location(entity)?;
+                self.archetypes.get_archetype(&archetype_id).column(component).map(|column| (column, entity_index))
+            }
+            StorageType::SparseSet => {
+                let set = self.sparse_sets.get(component)?;
+                set.dense_index(entity).map(|index| (set.column(), index))
+            }
+        }
+    }
+
+    pub(crate) fn component_column_mut(&mut self, entity: EntityID, component: &ComponentID) -> Option<(&mut ComponentData, usize)> {
+        match self.components.storage(component) {
+            StorageType::Table => {
+                let (archetype_id, entity_index) = self.location(entity)?;
+                self.archetypes.get_archetype_mut(&archetype_id).column_mut(component).map(|column| (column, entity_index))
+            }
+            StorageType::SparseSet => {
+                let set = self.sparse_sets.get_mut(component)?;
+                set.dense_index(entity).map(|index| (set.column_mut(), index))
+            }
+        }
+    }
+
+    // Hooks can modify or destroy the entity : it has to be checked again after each one
+    fn trigger_insert_hooks(&mut self, entity: EntityID, component: ComponentID, added: bool) {
+        let Some(hooks) = self.components.hooks(&component).copied() else { return; };
+        if let Some(on_add) = hooks.on_add.filter(|_| added) {
+            on_add(self, entity, component);
+        }
+        if let Some(on_insert) = hooks.on_insert.filter(|_| self.has_component(entity, &component)) {
+            on_insert(self, entity, component);
+        }
+    }
+
+    fn trigger_remove_hook(&mut self, entity: EntityID, component: ComponentID) {
+        if let Some(on_remove) = self.components.hooks(&component).and_then(|hooks| hooks.on_remove) {
+            on_remove(self, entity, component);
+        }
+    }
+
+    // Insert and remove multiple components, moving the entity to its new archetype only once
+    pub(crate) fn apply_changes(&mut self, entity: EntityID, inserts: Vec<BoxedComponent>, removes: Vec<ComponentID>) -> Result<(), EcsError> {
+        self.check_alive(entity)?;
+        for component in &removes {
+            if self.has_component(entity, component) {
+                self.trigger_remove_hook(entity, *component);
+            }
+        }
+        // Destroyed by a hook
+        self.check_alive(entity)?;
+
+        let added: Vec<(ComponentID, bool)> = inserts.iter().map(|component| (*component.id(), !self.has_component(entity, component.id()))).collect();
+        let insert_data: Vec<ComponentInsert> = inserts.iter().map(|component| ComponentInsert {
+            id: *component.id(),
+            data: component.data(),
+            register: component.register_fn(),
+        }).collect();
+        unsafe { self.insert_components(entity, insert_data.as_slice(), removes.as_slice()); }
+
+        // Components were moved into the ecs
+        for component in inserts {
+            component.forget();
+        }
+        for (component, added) in added {
+            self.trigger_insert_hooks(entity, component, added);
+        }
+        Ok(())
+    }
+
+    // Ownership of the data pointed by inserted components is transferred to the ecs. Removing missing components is ignored.
+    unsafe fn insert_components(&mut self, entity: EntityID, inserts: &[ComponentInsert], removes: &[ComponentID]) {
         // Retrieve archetype and internal entity index
-        let (old_archetype_id, old_entity_index) = *self.entity_registry.get_mut(&entity).expect("The given entity is not registered yet");
+        let (old_archetype_id, old_entity_index) = self.raw_location(entity);
 
-        let mut _data = vec![];
+        let tick = self.change_tick();
+        let mut moved_data = Vec::with_capacity(inserts.len());
+        for insert in inserts {
+            // Sparse set components never change the archetype
+            if self.components.storage(&insert.id) == StorageType::SparseSet {
+                self.sparse_sets.get_or_create(&insert.id, &self.components).insert(entity, insert.data, tick);
+                continue;
+            }
+            match self.column_mut(old_archetype_id, &insert.id) {
+                // Component already exists : replace it in place
+                Some(column) => { column.replace(&old_entity_index, insert.data, tick) }
+                None => { moved_data.push(insert) }
+            }
+        }
 
-        let new_components = if old_archetype_id == ArchetypeID::MAX {
-            panic!("Current entity doesn't contains any components")
-        } else {
-            // Retrieve data from existing archetype, then drop entity from it
-            let old_archetype = self.archetypes.get_archetype_mut(&old_archetype_id);
+        for id in removes.iter().filter(|id| self.components.storage(id) == StorageType::SparseSet) {
+            if !inserts.iter().any(|insert| insert.id == *id) {
+                if let Some(set) = self.sparse_sets.get_mut(id) {
+                    set.remove(entity);
+                }
+            }
+        }
 
-            let mut component_ids = old_archetype.components().clone();
-            _data = old_archetype.entity_data(&old_entity_index).clone();
-            
-            // Update swapped entity indexes
-            let swapped_entity_index = old_archetype.last_index();
-            let swapped_entity = old_archetype.entity_at(&swapped_entity_index).clone();
-            self.entity_registry.insert(swapped_entity, (old_archetype_id, swapped_entity_index));
-
-            // Remove entity data
-            old_archetype.drop_entity(&old_entity_index);
-            let mut index = usize::MAX;
-            for i in 0..component_ids.len() { //@TODO : improve component sorting
-                if component_ids[i] == component {
-                    index = i;
+        let removed: Vec<ComponentID> = removes.iter()
+            .filter(|id| old_archetype_id != ArchetypeID::MAX && self.archetypes.get_archetype(&old_archetype_id).contains(id))
+            .filter(|id| !inserts.iter().any(|insert| insert.id == **id))
+            .cloned()
+            .collect();
+
+        // Find an archetype containing desired components, using cached transitions for single component changes
+        let new_archetype_id = match (moved_data.as_slice(), removed.as_slice()) {
+            ([], []) => { old_archetype_id }
+            ([insert], []) => {
+                match self.archetypes.find_add_edge(&old_archetype_id, &insert.id) {
+                    Some(new_archetype_id) => { new_archetype_id }
+                    None => {
+                        let new_archetype_id = self.create_archetype(old_archetype_id, &moved_data, &removed);
+                        self.archetypes.insert_edge(&old_archetype_id, &insert.id, &new_archetype_id);
+                        new_archetype_id
+                    }
+                }
+            }
+            ([], [removed_component]) => {
+                match self.archetypes.find_remove_edge(&old_archetype_id, removed_component) {
+                    Some(new_archetype_id) => { new_archetype_id }
+                    None => {
+                        let new_archetype_id = self.create_archetype(old_archetype_id, &moved_data, &removed);
+                        self.archetypes.insert_edge(&new_archetype_id, removed_component, &old_archetype_id);
+                        new_archetype_id
+                    }
                 }
             }
-            assert_ne!(index, usize::MAX, "Entity '{entity}' does not contains component '{}'", type_name::<C>());
-            component_ids.swap_remove(index);
-            component_ids
+            _ => { self.create_archetype(old_archetype_id, &moved_data, &removed) }
         };
 
-        // Empty
-        if new_components.is_empty() {
-            self.entity_registry.insert(entity, (ArchetypeID::MAX, usize::MAX));
+        // Entities owning only sparse set components are kept in the empty archetype to be found by queries
+        let new_archetype_id = match new_archetype_id {
+            ArchetypeID::MAX if self.sparse_sets.contains_entity(entity) => { self.archetypes.find_or_create_empty() }
+            id if self.archetypes.is_empty_archetype(&id) && !self.sparse_sets.contains_entity(entity) => { ArchetypeID::MAX }
+            id => { id }
+        };
+        if new_archetype_id == old_archetype_id {
             return;
         }
 
-        // Find an archetype containing desired components
-        let new_archetype_id = self.archetypes.find_or_create(new_components.as_slice(), &self.components);
-        let new_archetype = self.archetypes.get_archetype_mut(&new_archetype_id);
+        self.move_entity(entity, new_archetype_id);
+
+        // Move component data
+        for insert in moved_data {
+            self.archetypes.get_archetype_mut(&new_archetype_id)
+                .column_mut(&insert.id)
+                .expect("missing component in new archetype")
+                .push(insert.data, tick);
+        }
+    }
+
+    // Slow path : find or create the archetype matching the given changes
+    fn create_archetype(&mut self, old_archetype_id: ArchetypeID, inserts: &[&ComponentInsert], removes: &[ComponentID]) -> ArchetypeID {
+        let mut components = if old_archetype_id == ArchetypeID::MAX { vec![] } else { self.archetypes.get_archetype(&old_archetype_id).components().clone() };
+        components.retain(|id| !removes.contains(id));
+        for insert in inserts {
+            (insert.register)(&mut self.components);
+            components.push(insert.id);
+        }
+        self.archetypes.find_or_create(components.as_slice(), &self.components)
+    }
+
+    fn column_mut(&mut self, archetype_id: ArchetypeID, component: &ComponentID) -> Option<&mut ComponentData> {
+        if archetype_id == ArchetypeID::MAX {
+            return None;
+        }
+        self.archetypes.get_archetype_mut(&archetype_id).column_mut(component)
+    }
+
+    pub(crate) fn remove_by_id(&mut self, entity: EntityID, component: ComponentID) -> Result<(), EcsError> {
+        let missing = || {
+            let name = self.components.try_get(&component).map(|data| data.name.clone()).unwrap_or_else(|| format!("{component:?}").into());
+            EcsError::MissingComponent(entity, name)
+        };
+        if !self.has_component(entity, &component) {
+            return Err(missing());
+        }
+
+        self.trigger_remove_hook(entity, component);
+        // Already removed by the hook
+        if !self.has_component(entity, &component) {
+            return Ok(());
+        }
+
+        unsafe { self.insert_components(entity, &[], &[component]); }
+        Ok(())
+    }
 
-        // Register entity into new archetype
-        let new_entity_index = new_archetype.push_entity(entity);
+    // Move entity into the given archetype. Components missing from the new archetype are dropped.
+    fn move_entity(&mut self, entity: EntityID, new_archetype_id: ArchetypeID) -> (ArchetypeID, usize) {
+        let (old_archetype_id, old_entity_index) = self.raw_location(entity);
 
-        // Copy component data
-        for (comp_id, comp_data) in _data {
-            new_archetype.update_component_data(&new_entity_index, &comp_id, comp_data)
+        // Empty
+        if new_archetype_id == ArchetypeID::MAX {
+            if old_archetype_id != ArchetypeID::MAX {
+                if let Some(swapped_entity) = self.archetypes.get_archetype_mut(&old_archetype_id).drop_entity(&old_entity_index) {
+                    self.set_location(swapped_entity, (old_archetype_id, old_entity_index));
+                }
+            }
+            self.set_location(entity, (ArchetypeID::MAX, usize::MAX));
+            return (ArchetypeID::MAX, usize::MAX);
         }
 
+        let new_entity_index = if old_archetype_id == ArchetypeID::MAX {
+            self.archetypes.get_archetype_mut(&new_archetype_id).push_entity(entity)
+        } else {
+            let (old_archetype, new_archetype) = self.archetypes.get_archetype_pair_mut(&old_archetype_id, &new_archetype_id);
+            let (new_entity_index, swapped_entity) = old_archetype.move_entity(&old_entity_index, new_archetype);
+
+            // Update swapped entity indexes
+            if let Some(swapped_entity) = swapped_entity {
+                self.set_location(swapped_entity, (old_archetype_id, old_entity_index));
+            }
+            new_entity_index
+        };
+
         // Update entity_registry infos
-        self.entity_registry.insert(entity, (new_archetype_id, new_entity_index));
+        self.set_location(entity, (new_archetype_id, new_entity_index));
+        (new_archetype_id, new_entity_index)
     }
 }
@@ -0,0 +1,40 @@
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter};
+
+use crate::component::ComponentID;
+use crate::entity::EntityID;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EcsError {
+    DeadEntity(EntityID),
+    MissingComponent(EntityID, Cow<'static, str>),
+    QueryMismatch(EntityID),
+    HierarchyCycle(EntityID, EntityID),
+    DuplicateComponent(String),
+    UnknownField(String),
+    FieldTypeMismatch(String),
+    NotCloneable(Cow<'static, str>),
+    InvalidPrefabEntity(usize),
+    UnknownComponent(ComponentID),
+    NotDynamic(Cow<'static, str>),
+}
+
+impl Display for EcsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EcsError::DeadEntity(entity) => { write!(f, "Entity '{entity}' is not alive") }
+            EcsError::MissingComponent(entity, component) => { write!(f, "Entity '{entity}' does not contains component '{component}'") }
+            EcsError::QueryMismatch(entity) => { write!(f, "Entity '{entity}' does not match query") }
+            EcsError::HierarchyCycle(child, parent) => { write!(f, "Entity '{child}' cannot be a child of its descendant '{parent}'") }
+            EcsError::DuplicateComponent(name) => { write!(f, "A component named '{name}' is already registered") }
+            EcsError::UnknownField(path) => { write!(f, "Field '{path}' does not exist") }
+            EcsError::FieldTypeMismatch(path) => { write!(f, "Value does not match the type of field '{path}'") }
+            EcsError::NotCloneable(component) => { write!(f, "Component '{component}' cannot be copied into a snapshot without a clone function") }
+            EcsError::InvalidPrefabEntity(index) => { write!(f, "Prefab does not contain an entity at index {index}") }
+            EcsError::UnknownComponent(component) => { write!(f, "Component '{component:?}' is not registered") }
+            EcsError::NotDynamic(component) => { write!(f, "Component '{component}' is not a dynamic component") }
+        }
+    }
+}
+
+impl std::error::Error for EcsError {}
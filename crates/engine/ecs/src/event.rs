@@ -0,0 +1,164 @@
+use std::any::Any;
+
+// Events are shared between systems running on different threads
+pub trait Event: Any + Send + Sync {}
+
+impl<T: Any + Send + Sync> Event for T {}
+
+// Double buffered event queue : events stay readable during the update they were sent and the following one.
+// Each event is identified by its sending order, which allow readers to track what they already read.
+pub struct Events<E: Event> {
+    previous: Vec<E>,
+    current: Vec<E>,
+    previous_start: usize,
+    current_start: usize,
+}
+
+impl<E: Event> Default for Events<E> {
+    fn default() -> Self {
+        Self { previous: vec![], current: vec![], previous_start: 0, current_start: 0 }
+    }
+}
+
+impl<E: Event> Events<E> {
+    pub fn send(&mut self, event: E) {
+        self.current.push(event);
+    }
+
+    // Drop events sent before the previous update
+    pub fn update(&mut self) {
+        self.previous = std::mem::take(&mut self.current);
+        self.previous_start = self.current_start;
+        self.current_start += self.previous.len();
+    }
+
+    pub fn read(&self) -> impl Iterator<Item=&E> {
+        self.previous.iter().chain(self.current.iter())
+    }
+
+    // Read events sent after the given event count
+    pub fn read_from(&self, event_count: usize) -> impl Iterator<Item=&E> {
+        let skip_previous = event_count.saturating_sub(self.previous_start).min(self.previous.len());
+        let skip_current = event_count.saturating_sub(self.current_start).min(self.current.len());
+        self.previous[skip_previous..].iter().chain(self.current[skip_current..].iter())
+    }
+
+    // Total number of events sent since creation
+    pub fn event_count(&self) -> usize {
+        self.current_start + self.current.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.previous.len() + self.current.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/*
+SYSTEM ACCESS
+ */
+
+pub struct EventReader<'w, 's, E: Event> {
+    events: &'w Events<E>,
+    last_event_count: &'s mut usize,
+}
+
+impl<'w, 's, E: Event> EventReader<'w, 's, E> {
+    pub fn new(events: &'w Events<E>, last_event_count: &'s mut usize) -> Self {
+        Self { events, last_event_count }
+    }
+
+    // Read events that were not read yet by this reader
+    pub fn read(&mut self) -> impl Iterator<Item=&E> {
+        let from = *self.last_event_count;
+        *self.last_event_count = self.events.event_count();
+        self.events.read_from(from)
+    }
+}
+
+pub struct EventWriter<'w, E: Event> {
+    events: &'w mut Events<E>,
+}
+
+impl<'w, E: Event> EventWriter<'w, E> {
+    pub fn new(events: &'w mut Events<E>) -> Self {
+        Self { events }
+    }
+
+    pub fn send(&mut self, event: E) {
+        self.events.send(event);
+    }
+}
+
+/*
+TESTS
+ */
+
+#[cfg(test)]
+mod tests {
+    use crate::ecs::Ecs;
+    use crate::event::{EventReader, Events, EventWriter};
+    use crate::resource::{Res, ResMut};
+    use crate::schedule::Schedule;
+
+    #[derive(Debug, PartialEq)]
+    struct KeyPressed(u32);
+
+    struct Received(Vec<u32>);
+
+    struct Time(f32);
+
+    #[test]
+    fn double_buffer_test() {
+        let mut events = Events::<KeyPressed>::default();
+        events.send(KeyPressed(1));
+        events.send(KeyPressed(2));
+        assert_eq!(events.len(), 2);
+
+        events.update();
+        events.send(KeyPressed(3));
+        assert_eq!(events.read().map(|event| event.0).collect::<Vec<u32>>(), vec![1, 2, 3]);
+        assert_eq!(events.read_from(2).map(|event| event.0).collect::<Vec<u32>>(), vec![3]);
+
+        events.update();
+        assert_eq!(events.read().map(|event| event.0).collect::<Vec<u32>>(), vec![3]);
+        assert_eq!(events.read_from(0).map(|event| event.0).collect::<Vec<u32>>(), vec![3]);
+        assert_eq!(events.event_count(), 3);
+
+        events.update();
+        assert!(events.is_empty());
+    }
+
+    fn send_keys(time: Res<Time>, mut writer: EventWriter<KeyPressed>) {
+        writer.send(KeyPressed(time.0 as u32));
+    }
+
+    fn receive_keys(mut reader: EventReader<KeyPressed>, mut received: ResMut<Received>) {
+        for event in reader.read() {
+            received.0.push(event.0);
+        }
+    }
+
+    #[test]
+    fn system_events_test() {
+        let mut ecs = Ecs::default();
+        ecs.insert_resource(Time(0.0));
+        ecs.insert_resource(Received(vec![]));
+
+        // Receiver runs before the sender : events are read during the following update
+        let mut schedule = Schedule::new(2);
+        schedule.add_system(receive_keys).add_system(send_keys);
+        for i in 1..4 {
+            ecs.resource_mut::<Time>().unwrap().0 = i as f32;
+            schedule.run(&mut ecs);
+        }
+        ecs.send(KeyPressed(10));
+        schedule.run(&mut ecs);
+
+        assert_eq!(ecs.resource::<Received>().unwrap().0, vec![1, 2, 3, 10]);
+        assert_eq!(ecs.read::<KeyPressed>().collect::<Vec<&KeyPressed>>(), vec![&KeyPressed(10), &KeyPressed(3)]);
+    }
+}
@@ -0,0 +1,262 @@
+use serde::{Deserialize, Serialize};
+
+use crate::component::ComponentID;
+use crate::ecs::Ecs;
+use crate::entity::EntityID;
+use crate::error::EcsError;
+use crate::transform::LocalTransform;
+
+/*
+COMPONENTS
+ */
+
+// Hierarchy components are maintained by the ecs : use Ecs::set_parent and Ecs::remove_parent to edit them.
+// Their remove hooks are used to keep both sides of a link consistent when one of them is removed.
+#[derive(Copy, Clone, Serialize, Deserialize)]
+pub struct Parent(pub(crate) EntityID);
+
+impl Parent {
+    pub fn get(&self) -> EntityID {
+        self.0
+    }
+}
+
+#[derive(Default, Clone, Serialize, Deserialize)]
+pub struct Children(pub(crate) Vec<EntityID>);
+
+impl Children {
+    pub fn iter(&self) -> std::slice::Iter<'_, EntityID> {
+        self.0.iter()
+    }
+
+    pub fn as_slice(&self) -> &[EntityID] {
+        &self.0
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl<'a> IntoIterator for &'a Children {
+    type Item = &'a EntityID;
+    type IntoIter = std::slice::Iter<'a, EntityID>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+/*
+HIERARCHY
+ */
+
+impl Ecs {
+    pub fn parent(&self, entity: EntityID) -> Option<EntityID> {
+        self.get::<Parent>(entity).map(Parent::get)
+    }
+
+    pub fn children(&self, entity: EntityID) -> &[EntityID] {
+        self.get::<Children>(entity).map_or(&[], Children::as_slice)
+    }
+
+    pub fn set_parent(&mut self, child: EntityID, parent: EntityID) -> Result<(), EcsError> {
+        self.check_alive(child)?;
+        self.check_alive(parent)?;
+        if self.parent(child) == Some(parent) {
+            return Ok(());
+        }
+
+        // Parent must not be a descendant of the child
+        let mut ancestor = Some(parent);
+        while let Some(entity) = ancestor {
+            if entity == child {
+                return Err(EcsError::HierarchyCycle(child, parent));
+            }
+            ancestor = self.parent(entity);
+        }
+
+        self.register_hierarchy();
+        self.unlink_parent(child);
+        match self.get_mut::<Children>(parent) {
+            Some(children) => { children.0.push(child) }
+            None => { self.add(parent, Children(vec![child]))? }
+        }
+        self.add(child, Parent(parent))
+    }
+
+    // The entity becomes a root
+    pub fn remove_parent(&mut self, child: EntityID) -> Result<(), EcsError> {
+        self.check_alive(child)?;
+        if self.parent(child).is_some() {
+            self.remove::<Parent>(child)?;
+        }
+        Ok(())
+    }
+
+    // Destroy the entity and all its descendants
+    pub fn despawn_recursive(&mut self, entity: EntityID) -> Result<(), EcsError> {
+        self.check_alive(entity)?;
+        let mut descendants = vec![];
+        let mut stack = vec![entity];
+        while let Some(current) = stack.pop() {
+            stack.extend_from_slice(self.children(current));
+            descendants.push(current);
+        }
+        for descendant in descendants.into_iter().rev() {
+            self.destroy(descendant)?;
+        }
+        Ok(())
+    }
+
+    // Registered on first use. Children owns heap memory, it needs a clone function to be snapshotted.
+    fn register_hierarchy(&mut self) {
+        self.register_clone::<Children>();
+        self.component_hooks::<Parent>().on_remove(parent_removed);
+        self.component_hooks::<Children>().on_remove(children_removed);
+    }
+
+    // Remove child from the children of its current parent
+    fn unlink_parent(&mut self, child: EntityID) {
+        let Some(parent) = self.parent(child) else { return; };
+        if let Some(children) = self.get_mut::<Children>(parent) {
+            let count = children.0.len();
+            children.0.retain(|other| *other != child);
+            // Children emptied by its own remove hook is already being removed
+            if children.0.is_empty() && count > 0 {
+                let _ = self.remove::<Children>(parent);
+            }
+        }
+    }
+}
+
+/*
+HOOKS
+ */
+
+// Also called when the child is destroyed
+fn parent_removed(ecs: &mut Ecs, child: EntityID, _: ComponentID) {
+    ecs.unlink_parent(child);
+    // World transform has to be recomputed without the parent
+    ecs.set_changed::<LocalTransform>(child);
+}
+
+// Children of the entity become roots
+fn children_removed(ecs: &mut Ecs, parent: EntityID, _: ComponentID) {
+    let Some(Children(children)) = ecs.get_mut::<Children>(parent).map(std::mem::take) else { return; };
+    for child in children {
+        let _ = ecs.remove::<Parent>(child);
+    }
+}
+
+/*
+TESTS
+ */
+
+#[cfg(test)]
+mod tests {
+    use crate::command::{CommandQueue, Commands};
+    use crate::ecs::Ecs;
+    use crate::error::EcsError;
+    use crate::hierarchy::{Children, Parent};
+
+    struct Name(&'static str);
+
+    #[test]
+    fn hierarchy_test() {
+        let mut ecs = Ecs::default();
+        let root = ecs.create();
+        let a = ecs.create();
+        let b = ecs.create();
+        ecs.add(b, Name("b")).unwrap();
+
+        ecs.set_parent(a, root).unwrap();
+        ecs.set_parent(b, root).unwrap();
+        assert_eq!(ecs.children(root), &[a, b]);
+        assert_eq!(ecs.parent(b), Some(root));
+        assert_eq!(ecs.get::<Name>(b).unwrap().0, "b");
+
+        // Reparenting updates both parents
+        ecs.set_parent(b, a).unwrap();
+        assert_eq!(ecs.children(root), &[a]);
+        assert_eq!(ecs.children(a), &[b]);
+        assert_eq!(ecs.set_parent(root, b), Err(EcsError::HierarchyCycle(root, b)));
+
+        ecs.remove_parent(b).unwrap();
+        assert!(ecs.parent(b).is_none());
+        assert!(ecs.get::<Children>(a).is_none());
+        assert_eq!(ecs.query::<&Parent>().count(), 1);
+    }
+
+    #[test]
+    fn remove_components_test() {
+        let mut ecs = Ecs::default();
+        let root = ecs.create();
+        let a = ecs.create();
+        let b = ecs.create();
+        ecs.set_parent(a, root).unwrap();
+        ecs.set_parent(b, root).unwrap();
+
+        // Removing one side of a link removes the other one
+        ecs.remove::<Parent>(a).unwrap();
+        assert_eq!(ecs.children(root), &[b]);
+        ecs.remove::<Children>(root).unwrap();
+        assert!(ecs.parent(b).is_none());
+        assert_eq!(ecs.query::<&Parent>().count(), 0);
+
+        ecs.set_parent(b, a).unwrap();
+        let mut queue = CommandQueue::default();
+        Commands::new(&mut queue, &ecs).entity(b).remove::<Parent>();
+        assert!(queue.apply(&mut ecs).is_empty());
+        assert!(ecs.get::<Children>(a).is_none());
+    }
+
+    #[test]
+    fn despawn_test() {
+        let mut ecs = Ecs::default();
+        let root = ecs.create();
+        let a = ecs.create();
+        let b = ecs.create();
+        let c = ecs.create();
+        ecs.set_parent(a, root).unwrap();
+        ecs.set_parent(b, a).unwrap();
+        ecs.set_parent(c, a).unwrap();
+
+        // Children of a destroyed entity are orphaned
+        ecs.destroy(a).unwrap();
+        assert!(ecs.children(root).is_empty());
+        assert!(ecs.parent(b).is_none() && ecs.parent(c).is_none());
+        assert!(ecs.is_alive(b) && ecs.is_alive(c));
+
+        ecs.set_parent(b, root).unwrap();
+        ecs.set_parent(c, b).unwrap();
+        let other = ecs.create();
+        ecs.set_parent(other, root).unwrap();
+        ecs.despawn_recursive(b).unwrap();
+        assert!(!ecs.is_alive(b) && !ecs.is_alive(c));
+        assert_eq!(ecs.children(root), &[other]);
+    }
+
+    #[test]
+    fn hierarchy_commands_test() {
+        let mut ecs = Ecs::default();
+        let root = ecs.create();
+
+        let mut queue = CommandQueue::default();
+        let mut commands = Commands::new(&mut queue, &ecs);
+        let child = commands.spawn().insert(Name("child")).set_parent(root).id();
+        let grand_child = commands.spawn().set_parent(child).id();
+        assert!(queue.apply(&mut ecs).is_empty());
+        assert_eq!(ecs.children(root), &[child]);
+        assert_eq!(ecs.parent(grand_child), Some(child));
+
+        let mut commands = Commands::new(&mut queue, &ecs);
+        commands.entity(root).despawn_recursive();
+        assert!(queue.apply(&mut ecs).is_empty());
+        assert!(!ecs.is_alive(root) && !ecs.is_alive(child) && !ecs.is_alive(grand_child));
+    }
+}
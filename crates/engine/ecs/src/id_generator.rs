@@ -1,24 +1,109 @@
-﻿use std::collections::LinkedList;
-use std::ops::{Add, Sub};
-use num::One;
+﻿use std::sync::atomic::{AtomicU32, Ordering};
 
+use crate::entity::EntityID;
+
+#[derive(Default, Clone)]
+struct IdSlot {
+    generation: u32,
+    alive: bool,
+}
+
+// Recycle freed indexes, bumping their generation so stale ids never match the new owner
 #[derive(Default)]
-pub struct IdGenerator<C: Default + Copy> {
-    free_ids: LinkedList<C>,
-    max_id: C,
+pub struct IdGenerator {
+    slots: Vec<IdSlot>,
+    free_ids: Vec<u32>,
+    // Fresh indexes reserved from a shared reference, allocated on next flush
+    reserved: AtomicU32,
+}
+
+// Pending reservations are kept
+impl Clone for IdGenerator {
+    fn clone(&self) -> Self {
+        Self {
+            slots: self.slots.clone(),
+            free_ids: self.free_ids.clone(),
+            reserved: AtomicU32::new(self.reserved.load(Ordering::Relaxed)),
+        }
+    }
 }
 
-impl<C: Default + Copy + Add<Output = C> + Sub<Output = C> + One> IdGenerator<C> {
-    pub fn acquire(&mut self) -> C
+impl IdGenerator {
+    pub fn acquire(&mut self) -> EntityID
     {
-        if !self.free_ids.is_empty() {
-            return self.free_ids.pop_back().expect("list should not be empty there");
+        self.flush();
+        let index = match self.free_ids.pop() {
+            Some(index) => { index }
+            None => {
+                self.slots.push(IdSlot::default());
+                (self.slots.len() - 1) as u32
+            }
+        };
+        let slot = &mut self.slots[index as usize];
+        slot.alive = true;
+        EntityID::new(index, slot.generation)
+    }
+
+    // Reserve an id that will become alive on next flush. Can be called concurrently.
+    pub fn reserve(&self) -> EntityID {
+        let index = self.slots.len() as u32 + self.reserved.fetch_add(1, Ordering::Relaxed);
+        EntityID::new(index, 0)
+    }
+
+    pub fn flush(&mut self) {
+        let reserved = std::mem::take(self.reserved.get_mut());
+        for _ in 0..reserved {
+            self.slots.push(IdSlot { generation: 0, alive: true });
+        }
+    }
+
+    pub fn release(&mut self, id: &EntityID) -> bool {
+        self.flush();
+        if !self.is_alive(id) {
+            return false;
+        }
+        let slot = &mut self.slots[id.index() as usize];
+        slot.alive = false;
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free_ids.push(id.index());
+        true
+    }
+
+    pub fn is_alive(&self, id: &EntityID) -> bool {
+        match self.slots.get(id.index() as usize) {
+            None => { false }
+            Some(slot) => { slot.alive && slot.generation == id.generation() }
         }
-        self.max_id = self.max_id + One::one();
-        self.max_id - One::one()
     }
-    
-    pub fn release(&mut self, id: &C) {
-        self.free_ids.push_back(*id);
+
+    pub(crate) fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    // Generation and state of an index
+    pub(crate) fn slot(&self, index: u32) -> (u32, bool) {
+        self.slots.get(index as usize).map(|slot| (slot.generation, slot.alive)).unwrap_or_default()
+    }
+
+    pub(crate) fn set_slot(&mut self, index: u32, generation: u32, alive: bool) {
+        self.flush();
+        if index as usize >= self.slots.len() {
+            self.slots.resize_with(index as usize + 1, IdSlot::default);
+        }
+        self.slots[index as usize] = IdSlot { generation, alive };
     }
-}
\ No newline at end of file
+
+    pub(crate) fn free_ids(&self) -> &[u32] {
+        &self.free_ids
+    }
+
+    pub(crate) fn set_free_ids(&mut self, free_ids: Vec<u32>) {
+        self.free_ids = free_ids;
+    }
+
+    pub fn iter_alive(&self) -> impl Iterator<Item=EntityID> + '_ {
+        self.slots.iter().enumerate()
+            .filter(|(_, slot)| slot.alive)
+            .map(|(index, slot)| EntityID::new(index as u32, slot.generation))
+    }
+}
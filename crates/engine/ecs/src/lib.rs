@@ -3,6 +3,21 @@ pub mod archetype;
 pub mod ecs;
 pub mod component;
 pub mod id_generator;
+pub mod error;
+pub mod query;
+pub mod system;
+pub mod resource;
+pub mod event;
+pub mod command;
+pub mod schedule;
+pub mod hierarchy;
+pub mod transform;
+pub mod scene;
+pub mod reflect;
+pub mod prefab;
+pub mod observer;
+pub mod snapshot;
+pub mod sparse_set;
 
 /*
 TESTS
@@ -10,7 +25,13 @@ TESTS
 
 #[cfg(test)]
 mod tests {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
     use crate::ecs::Ecs;
+    use crate::entity::EntityID;
+    use crate::error::EcsError;
+    use crate::query::{With, Without};
 
     struct CompA {
         pub _a: u32,
@@ -29,15 +50,260 @@ mod tests {
         let e1 = ecs.create();
         let e2 = ecs.create();
         
-        ecs.add(e0, CompA { _a: 5489 });
-        ecs.add(e1, CompB { _b: 50, _c: 5.0 });
-        ecs.add(e2, CompA { _a: 15 });
-        ecs.add(e2, CompB { _b: 5454, _c: 5563.0 });
+        ecs.add(e0, CompA { _a: 5489 }).unwrap();
+        ecs.add(e1, CompB { _b: 50, _c: 5.0 }).unwrap();
+        ecs.add(e2, CompA { _a: 15 }).unwrap();
+        ecs.add(e2, CompB { _b: 5454, _c: 5563.0 }).unwrap();
 
-        ecs.destroy(e1);
-        ecs.remove::<CompA>(e2);
+        ecs.destroy(e1).unwrap();
+        ecs.remove::<CompA>(e2).unwrap();
         
-        ecs.destroy(e0);
-        ecs.destroy(e2);
+        ecs.destroy(e0).unwrap();
+        ecs.destroy(e2).unwrap();
+    }
+
+    #[test]
+    fn query_test() {
+        let mut ecs = Ecs::default();
+
+        let e0 = ecs.create();
+        let e1 = ecs.create();
+        let e2 = ecs.create();
+
+        ecs.add(e0, CompA { _a: 1 }).unwrap();
+        ecs.add(e1, CompA { _a: 2 }).unwrap();
+        ecs.add(e1, CompB { _b: 20, _c: 2.0 }).unwrap();
+        ecs.add(e2, CompB { _b: 30, _c: 3.0 }).unwrap();
+
+        let mut sum = 0;
+        for comp in ecs.query::<&CompA>() {
+            sum += comp._a;
+        }
+        assert_eq!(sum, 3);
+
+        for (a, b) in ecs.query::<(&CompA, &mut CompB)>() {
+            b._b += a._a as usize;
+        }
+        assert_eq!(ecs.get::<CompB>(e1).unwrap()._b, 22);
+        assert_eq!(ecs.get::<CompB>(e2).unwrap()._b, 30);
+
+        let mut entities: Vec<(EntityID, bool)> = ecs.query::<(EntityID, Option<&CompA>)>().iter().map(|(e, a)| (e, a.is_some())).collect();
+        entities.sort();
+        assert_eq!(entities, vec![(e0, true), (e1, true), (e2, false)]);
+
+        let with: Vec<EntityID> = ecs.query_filtered::<EntityID, With<CompB>>().into_iter().collect();
+        assert_eq!(with.len(), 2);
+        let without: Vec<EntityID> = ecs.query_filtered::<EntityID, (With<CompA>, Without<CompB>)>().into_iter().collect();
+        assert_eq!(without, vec![e0]);
+
+        assert!(ecs.query::<&CompA>().get(e2).is_err());
+        ecs.query::<&mut CompA>().get(e0).unwrap()._a = 10;
+        assert_eq!(ecs.get::<CompA>(e0).unwrap()._a, 10);
+    }
+
+    #[test]
+    fn query_after_destroy_test() {
+        let mut ecs = Ecs::default();
+
+        let entities: Vec<EntityID> = (0..5).map(|_| ecs.create()).collect();
+        for (i, entity) in entities.iter().enumerate() {
+            ecs.add(*entity, CompA { _a: i as u32 }).unwrap();
+        }
+        ecs.destroy(entities[1]).unwrap();
+        ecs.remove::<CompA>(entities[0]).unwrap();
+
+        let mut values: Vec<u32> = ecs.query::<&CompA>().into_iter().map(|comp| comp._a).collect();
+        values.sort();
+        assert_eq!(values, vec![2, 3, 4]);
+        for (i, entity) in entities.iter().enumerate().skip(2) {
+            assert_eq!(ecs.get::<CompA>(*entity).unwrap()._a, i as u32);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn query_conflicting_access_test() {
+        let mut ecs = Ecs::default();
+        ecs.query::<(&CompA, &mut CompA)>();
+    }
+
+    struct DropCounter {
+        counter: Arc<AtomicUsize>,
+        _name: String,
+    }
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            self.counter.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[repr(align(64))]
+    struct Aligned {
+        value: u8,
+    }
+
+    struct Marker;
+
+    #[test]
+    fn component_drop_test() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let new_counter = |name: &str| DropCounter { counter: counter.clone(), _name: name.to_string() };
+
+        let mut ecs = Ecs::default();
+        let e0 = ecs.create();
+        let e1 = ecs.create();
+        let e2 = ecs.create();
+
+        ecs.add(e0, new_counter("e0")).unwrap();
+        ecs.add(e1, new_counter("e1")).unwrap();
+        ecs.add(e2, new_counter("e2")).unwrap();
+        assert_eq!(counter.load(Ordering::SeqCst), 0);
+
+        // Archetype migration should move the value without dropping it
+        ecs.add(e0, CompA { _a: 1 }).unwrap();
+        ecs.add(e1, Marker).unwrap();
+        assert_eq!(counter.load(Ordering::SeqCst), 0);
+
+        // Replacing a component drops the previous value
+        ecs.add(e2, new_counter("e2 bis")).unwrap();
+        assert_eq!(counter.load(Ordering::SeqCst), 1);
+
+        ecs.remove::<DropCounter>(e0).unwrap();
+        assert_eq!(counter.load(Ordering::SeqCst), 2);
+
+        ecs.destroy(e1).unwrap();
+        assert_eq!(counter.load(Ordering::SeqCst), 3);
+
+        drop(ecs);
+        assert_eq!(counter.load(Ordering::SeqCst), 4);
+    }
+
+    #[test]
+    fn heap_component_test() {
+        let mut ecs = Ecs::default();
+        let shared = Arc::new(5);
+
+        let entities: Vec<EntityID> = (0..100).map(|_| ecs.create()).collect();
+        for (i, entity) in entities.iter().enumerate() {
+            ecs.add(*entity, format!("entity {i}")).unwrap();
+            ecs.add(*entity, vec![i; i]).unwrap();
+            if i % 2 == 0 {
+                ecs.add(*entity, shared.clone()).unwrap();
+            }
+        }
+        assert_eq!(Arc::strong_count(&shared), 51);
+
+        for (i, entity) in entities.iter().enumerate() {
+            if i % 3 == 0 {
+                ecs.remove::<String>(*entity).unwrap();
+            }
+            if i % 4 == 0 {
+                ecs.destroy(*entity).unwrap();
+            }
+        }
+        assert_eq!(Arc::strong_count(&shared), 26);
+
+        for (i, entity) in entities.iter().enumerate().filter(|(i, _)| i % 4 != 0) {
+            assert_eq!(ecs.get::<Vec<usize>>(*entity).unwrap(), &vec![i; i]);
+            match ecs.get::<String>(*entity) {
+                None => { assert_eq!(i % 3, 0) }
+                Some(name) => { assert_eq!(name, &format!("entity {i}")) }
+            }
+        }
+
+        drop(ecs);
+        assert_eq!(Arc::strong_count(&shared), 1);
+    }
+
+    #[test]
+    fn component_alignment_test() {
+        let mut ecs = Ecs::default();
+        for i in 0..10 {
+            let entity = ecs.create();
+            ecs.add(entity, CompA { _a: i }).unwrap();
+            ecs.add(entity, Marker).unwrap();
+            ecs.add(entity, Aligned { value: i as u8 }).unwrap();
+        }
+
+        let mut count = 0;
+        for (aligned, _) in ecs.query::<(&Aligned, &Marker)>() {
+            assert_eq!(aligned as *const Aligned as usize % 64, 0);
+            count += aligned.value as usize;
+        }
+        assert_eq!(count, 45);
+    }
+
+    #[test]
+    fn entity_generation_test() {
+        let mut ecs = Ecs::default();
+
+        let e0 = ecs.create();
+        ecs.add(e0, CompA { _a: 1 }).unwrap();
+        assert!(ecs.is_alive(e0));
+        ecs.destroy(e0).unwrap();
+        assert!(!ecs.is_alive(e0));
+
+        // Index is recycled with a new generation
+        let e1 = ecs.create();
+        assert_eq!(e0.index(), e1.index());
+        assert_ne!(e0, e1);
+        assert!(ecs.is_alive(e1));
+        assert!(!ecs.is_alive(e0));
+
+        assert_eq!(ecs.add(e0, CompA { _a: 2 }), Err(EcsError::DeadEntity(e0)));
+        assert_eq!(ecs.remove::<CompA>(e0), Err(EcsError::DeadEntity(e0)));
+        assert_eq!(ecs.destroy(e0), Err(EcsError::DeadEntity(e0)));
+        assert!(ecs.get::<CompA>(e0).is_none());
+        assert_eq!(ecs.query::<&CompA>().get(e0).err(), Some(EcsError::DeadEntity(e0)));
+
+        // The stale id must not affect the new entity
+        ecs.add(e1, CompB { _b: 3, _c: 3.0 }).unwrap();
+        assert!(ecs.get::<CompA>(e1).is_none());
+        assert_eq!(ecs.query::<&CompB>().get(e1).unwrap()._b, 3);
+        assert_eq!(ecs.remove::<CompA>(e1), Err(EcsError::MissingComponent(e1, std::any::type_name::<CompA>().into())));
+    }
+
+    #[test]
+    fn resource_test() {
+        let mut ecs = Ecs::default();
+        assert!(!ecs.contains_resource::<CompA>());
+
+        ecs.insert_resource(CompA { _a: 5 });
+        ecs.insert_resource(String::from("resource"));
+        ecs.resource_mut::<CompA>().unwrap()._a += 1;
+
+        assert_eq!(ecs.resource::<CompA>().unwrap()._a, 6);
+        assert_eq!(ecs.remove_resource::<String>(), Some(String::from("resource")));
+        assert!(ecs.resource::<String>().is_none());
+    }
+
+    #[test]
+    fn archetype_transition_test() {
+        let mut ecs = Ecs::default();
+
+        let e0 = ecs.create();
+        ecs.add(e0, CompA { _a: 1 }).unwrap();
+        ecs.add(e0, CompB { _b: 2, _c: 2.0 }).unwrap();
+
+        // Insertion order does not matter
+        let e1 = ecs.create();
+        ecs.add(e1, CompB { _b: 3, _c: 3.0 }).unwrap();
+        ecs.add(e1, CompA { _a: 4 }).unwrap();
+        assert_eq!(ecs.archetypes().len(), 3);
+
+        // Going back and forth reuses existing archetypes
+        for i in 0..10 {
+            ecs.remove::<CompA>(e0).unwrap();
+            ecs.add(e0, CompA { _a: i }).unwrap();
+            ecs.remove::<CompB>(e1).unwrap();
+            ecs.add(e1, CompB { _b: i as usize, _c: 0.0 }).unwrap();
+        }
+        assert_eq!(ecs.archetypes().len(), 3);
+        assert_eq!(ecs.get::<CompA>(e0).unwrap()._a, 9);
+        assert_eq!(ecs.get::<CompB>(e0).unwrap()._b, 2);
+        assert_eq!(ecs.get::<CompA>(e1).unwrap()._a, 4);
+        assert_eq!(ecs.get::<CompB>(e1).unwrap()._b, 9);
+        assert_eq!(ecs.query::<(&CompA, &CompB)>().count(), 2);
     }
-}
\ No newline at end of file
+}
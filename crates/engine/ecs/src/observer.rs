@@ -0,0 +1,265 @@
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::ecs::Ecs;
+use crate::entity::EntityID;
+use crate::event::Event;
+
+pub type ObserverID = u32;
+
+// Event passed to observers, with the entity it targets
+pub struct Trigger<'a, E: Event> {
+    event: &'a E,
+    target: Option<EntityID>,
+    observer: ObserverID,
+}
+
+impl<E: Event> Trigger<'_, E> {
+    pub fn event(&self) -> &E {
+        self.event
+    }
+
+    pub fn target(&self) -> Option<EntityID> {
+        self.target
+    }
+
+    pub fn observer(&self) -> ObserverID {
+        self.observer
+    }
+}
+
+type ObserverFn<E> = dyn Fn(&mut Ecs, &Trigger<E>) + Send + Sync;
+
+struct Observer {
+    id: ObserverID,
+    // None : observe every trigger of this event
+    target: Option<EntityID>,
+    // Arc<ObserverFn<E>>
+    callback: Box<dyn Any + Send + Sync>,
+}
+
+#[derive(Default)]
+pub struct Observers {
+    events: HashMap<TypeId, Vec<Observer>>,
+    next_id: ObserverID,
+}
+
+impl Observers {
+    fn add<E: Event>(&mut self, target: Option<EntityID>, callback: Arc<ObserverFn<E>>) -> ObserverID {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.events.entry(TypeId::of::<E>()).or_default().push(Observer { id, target, callback: Box::new(callback) });
+        id
+    }
+
+    fn remove(&mut self, id: ObserverID) -> bool {
+        for observers in self.events.values_mut() {
+            if let Some(index) = observers.iter().position(|observer| observer.id == id) {
+                observers.remove(index);
+                return true;
+            }
+        }
+        false
+    }
+
+    pub(crate) fn remove_entity(&mut self, entity: EntityID) {
+        for observers in self.events.values_mut() {
+            observers.retain(|observer| observer.target != Some(entity));
+        }
+    }
+
+    // Observers of the target first, then global ones, in registration order
+    fn matching<E: Event>(&self, target: Option<EntityID>) -> Vec<(ObserverID, Arc<ObserverFn<E>>)> {
+        let Some(observers) = self.events.get(&TypeId::of::<E>()) else { return vec![]; };
+        let targeted = observers.iter().filter(|observer| target.is_some() && observer.target == target);
+        let global = observers.iter().filter(|observer| observer.target.is_none());
+        targeted.chain(global)
+            .map(|observer| (observer.id, observer.callback.downcast_ref::<Arc<ObserverFn<E>>>().expect("observer event type mismatch").clone()))
+            .collect()
+    }
+}
+
+impl Ecs {
+    // Called for every trigger of this event
+    pub fn observe<E: Event>(&mut self, observer: impl Fn(&mut Ecs, &Trigger<E>) + Send + Sync + 'static) -> ObserverID {
+        self.observers_mut().add::<E>(None, Arc::new(observer))
+    }
+
+    // Called for triggers targeting the entity. The observer is removed when the entity is destroyed.
+    pub fn observe_entity<E: Event>(&mut self, entity: EntityID, observer: impl Fn(&mut Ecs, &Trigger<E>) + Send + Sync + 'static) -> ObserverID {
+        self.observers_mut().add::<E>(Some(entity), Arc::new(observer))
+    }
+
+    pub fn unobserve(&mut self, observer: ObserverID) -> bool {
+        self.observers_mut().remove(observer)
+    }
+
+    // Run global observers immediately
+    pub fn trigger<E: Event>(&mut self, event: E) {
+        self.run_observers(event, None);
+    }
+
+    // Run observers of the target entity, then global observers
+    pub fn trigger_for<E: Event>(&mut self, target: EntityID, event: E) {
+        self.run_observers(event, Some(target));
+    }
+
+    // Observers registered while running are only called on the next trigger
+    fn run_observers<E: Event>(&mut self, event: E, target: Option<EntityID>) {
+        for (observer, callback) in self.observers().matching::<E>(target) {
+            callback(self, &Trigger { event: &event, target, observer });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use crate::command::{CommandQueue, Commands};
+    use crate::component::ComponentID;
+    use crate::ecs::Ecs;
+    use crate::entity::EntityID;
+    use crate::observer::Trigger;
+
+    // Simulates gpu resources created for each mesh component
+    #[derive(Default)]
+    struct GpuResources {
+        allocated: Vec<EntityID>,
+        released: Vec<EntityID>,
+        uploads: u32,
+    }
+
+    struct Mesh(u32);
+
+    struct Damage(u32);
+
+    struct Health(u32);
+
+    fn allocate(ecs: &mut Ecs, entity: EntityID, _: ComponentID) {
+        ecs.resource_mut::<GpuResources>().unwrap().allocated.push(entity);
+    }
+
+    fn upload(ecs: &mut Ecs, _: EntityID, _: ComponentID) {
+        ecs.resource_mut::<GpuResources>().unwrap().uploads += 1;
+    }
+
+    fn release(ecs: &mut Ecs, entity: EntityID, _: ComponentID) {
+        // Component is still accessible
+        assert!(ecs.get::<Mesh>(entity).is_some_and(|mesh| mesh.0 < 5));
+        ecs.resource_mut::<GpuResources>().unwrap().released.push(entity);
+    }
+
+    #[test]
+    fn hooks_test() {
+        let mut ecs = Ecs::default();
+        ecs.insert_resource(GpuResources::default());
+        ecs.component_hooks::<Mesh>().on_add(allocate).on_insert(upload).on_remove(release);
+
+        let e0 = ecs.create();
+        let e1 = ecs.create();
+        ecs.add(e0, Mesh(0)).unwrap();
+        ecs.add(e0, Mesh(1)).unwrap();
+        ecs.add(e1, Mesh(2)).unwrap();
+        ecs.add(e1, 5u32).unwrap();
+
+        let resources = ecs.resource::<GpuResources>().unwrap();
+        assert_eq!(resources.allocated, vec![e0, e1]);
+        assert_eq!(resources.uploads, 3);
+
+        ecs.remove::<Mesh>(e0).unwrap();
+        ecs.destroy(e1).unwrap();
+        assert_eq!(ecs.resource::<GpuResources>().unwrap().released, vec![e0, e1]);
+
+        // Deferred changes trigger hooks too
+        let mut queue = CommandQueue::default();
+        let mut commands = Commands::new(&mut queue, &ecs);
+        let e2 = commands.spawn().insert(Mesh(3)).id();
+        commands.entity(e0).insert(Mesh(4));
+        assert!(queue.apply(&mut ecs).is_empty());
+        let mut commands = Commands::new(&mut queue, &ecs);
+        commands.despawn(e2);
+        commands.remove::<Mesh>(e0);
+        assert!(queue.apply(&mut ecs).is_empty());
+
+        let resources = ecs.resource::<GpuResources>().unwrap();
+        assert_eq!(resources.allocated, vec![e0, e1, e2, e0]);
+        assert_eq!(resources.released, vec![e0, e1, e2, e0]);
+        assert_eq!(resources.uploads, 5);
+    }
+
+    struct Cached;
+
+    struct Label;
+
+    fn drop_cache(ecs: &mut Ecs, entity: EntityID, _: ComponentID) {
+        ecs.remove::<Cached>(entity).unwrap();
+        ecs.add(entity, Label).unwrap();
+    }
+
+    fn count_removed(ecs: &mut Ecs, _: EntityID, component: ComponentID) {
+        ecs.resource_mut::<Vec<ComponentID>>().unwrap().push(component);
+    }
+
+    #[test]
+    fn destroy_hooks_test() {
+        let mut ecs = Ecs::default();
+        ecs.insert_resource(Vec::<ComponentID>::new());
+        ecs.component_hooks::<Mesh>().on_remove(drop_cache);
+        ecs.component_hooks::<Cached>().on_remove(count_removed);
+        ecs.component_hooks::<Label>().on_remove(count_removed);
+
+        // Removed components are notified once, added ones are notified before the entity is gone
+        let entity = ecs.create();
+        ecs.add(entity, Mesh(0)).unwrap();
+        ecs.add(entity, Cached).unwrap();
+        ecs.destroy(entity).unwrap();
+        assert!(!ecs.is_alive(entity));
+        assert_eq!(ecs.resource::<Vec<ComponentID>>().unwrap(), &vec![ComponentID::of::<Cached>(), ComponentID::of::<Label>()]);
+    }
+
+    #[test]
+    fn observer_test() {
+        let mut ecs = Ecs::default();
+        let e0 = ecs.create();
+        let e1 = ecs.create();
+        ecs.add(e0, Health(10)).unwrap();
+        ecs.add(e1, Health(10)).unwrap();
+
+        let total = Arc::new(AtomicU32::new(0));
+        let counter = total.clone();
+        ecs.observe(move |_, trigger: &Trigger<Damage>| {
+            counter.fetch_add(trigger.event().0, Ordering::Relaxed);
+        });
+        for entity in [e0, e1] {
+            ecs.observe_entity(entity, |ecs, trigger: &Trigger<Damage>| {
+                let target = trigger.target().unwrap();
+                ecs.get_mut::<Health>(target).unwrap().0 -= trigger.event().0;
+            });
+        }
+
+        ecs.trigger_for(e0, Damage(3));
+        ecs.trigger_for(e1, Damage(1));
+        ecs.trigger(Damage(100));
+        assert_eq!(ecs.get::<Health>(e0).unwrap().0, 7);
+        assert_eq!(ecs.get::<Health>(e1).unwrap().0, 9);
+        assert_eq!(total.load(Ordering::Relaxed), 104);
+
+        // Entity observers are removed with their entity
+        ecs.destroy(e0).unwrap();
+        let e2 = ecs.create();
+        ecs.add(e2, Health(10)).unwrap();
+        ecs.trigger_for(e2, Damage(2));
+        assert_eq!(ecs.get::<Health>(e2).unwrap().0, 10);
+
+        // Deferred trigger
+        let mut queue = CommandQueue::default();
+        let mut commands = Commands::new(&mut queue, &ecs);
+        commands.entity(e1).trigger(Damage(4));
+        assert!(queue.apply(&mut ecs).is_empty());
+        assert_eq!(ecs.get::<Health>(e1).unwrap().0, 5);
+        assert_eq!(total.load(Ordering::Relaxed), 110);
+    }
+}
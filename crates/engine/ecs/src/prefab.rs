@@ -0,0 +1,477 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use bincode::Options;
+use serde::de::DeserializeSeed;
+
+use crate::component::ComponentID;
+use crate::ecs::Ecs;
+use crate::entity::EntityID;
+use crate::error::EcsError;
+use crate::hierarchy::{Children, Parent};
+use crate::reflect::Value;
+use crate::scene::{EntityMap, load_ron, Scene, SceneError, SceneRegistry, SceneSeed, SceneSerializer};
+
+/*
+PREFAB
+ */
+
+// Entities stored in the binary scene format. The first entity is the root of each instance.
+pub struct Prefab {
+    registry: Arc<SceneRegistry>,
+    data: Vec<u8>,
+    len: usize,
+}
+
+impl Prefab {
+    // References to entities outside of the prefab are kept as is
+    pub fn new(ecs: &Ecs, registry: &Arc<SceneRegistry>, entities: &[EntityID]) -> Result<Self, SceneError> {
+        let len = entities.iter().filter(|entity| ecs.is_alive(**entity)).count();
+        if len == 0 {
+            return Err(SceneError::Serialize("prefab does not contain any entity".to_string()));
+        }
+        let data = bincode::options().serialize(&SceneSerializer::with_entities(ecs, registry, entities))
+            .map_err(|error| SceneError::Serialize(error.to_string()))?;
+        Ok(Self { registry: registry.clone(), data, len })
+    }
+
+    // The root and all its descendants
+    pub fn from_root(ecs: &Ecs, registry: &Arc<SceneRegistry>, root: EntityID) -> Result<Self, SceneError> {
+        let mut entities = vec![];
+        let mut stack = vec![root];
+        while let Some(entity) = stack.pop() {
+            entities.push(entity);
+            stack.extend(ecs.children(entity).iter().rev());
+        }
+        Self::new(ecs, registry, &entities)
+    }
+
+    // Entities are stored in file order
+    pub fn load_ron(registry: &Arc<SceneRegistry>, data: &str) -> Result<Self, SceneError> {
+        let mut ecs = Ecs::default();
+        let entities = load_ron(&mut ecs, registry, data)?;
+        Self::new(&ecs, registry, &entities)
+    }
+
+    pub fn save_ron(&self) -> Result<String, SceneError> {
+        let mut ecs = Ecs::default();
+        let entities = self.scene().spawn(&mut ecs);
+        ron::ser::to_string_pretty(&SceneSerializer::with_entities(&ecs, &self.registry, &entities), ron::ser::PrettyConfig::default())
+            .map_err(|error| SceneError::Serialize(error.to_string()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn registry(&self) -> &Arc<SceneRegistry> {
+        &self.registry
+    }
+
+    fn scene(&self) -> Scene {
+        let mut deserializer = bincode::Deserializer::from_slice(&self.data, bincode::options());
+        SceneSeed::new(&self.registry).deserialize(&mut deserializer).expect("prefab data was serialized with the same registry")
+    }
+}
+
+// Shared by a prefab asset and its instances. Replacing the prefab bumps its version so instances can be refreshed.
+pub struct PrefabSource {
+    prefab: RwLock<Arc<Prefab>>,
+    version: AtomicU32,
+}
+
+impl PrefabSource {
+    pub fn new(prefab: Prefab) -> Arc<Self> {
+        Arc::new(Self { prefab: RwLock::new(Arc::new(prefab)), version: AtomicU32::new(0) })
+    }
+
+    pub fn get(&self) -> Arc<Prefab> {
+        self.prefab.read().unwrap().clone()
+    }
+
+    pub fn set(&self, prefab: Prefab) {
+        *self.prefab.write().unwrap() = Arc::new(prefab);
+        self.version.fetch_add(1, Ordering::AcqRel);
+    }
+
+    pub fn version(&self) -> u32 {
+        self.version.load(Ordering::Acquire)
+    }
+}
+
+/*
+OVERRIDES
+ */
+
+// Value of a reflected field, replacing the prefab one on a single instance. Entity is the index in the prefab.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FieldOverride {
+    pub entity: usize,
+    pub component: ComponentID,
+    pub path: String,
+    pub value: Value,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct PrefabOverrides {
+    fields: Vec<FieldOverride>,
+}
+
+impl PrefabOverrides {
+    pub fn with_field(mut self, entity: usize, component: ComponentID, path: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.set(entity, component, path, value);
+        self
+    }
+
+    pub fn set(&mut self, entity: usize, component: ComponentID, path: impl Into<String>, value: impl Into<Value>) {
+        let field = FieldOverride { entity, component, path: path.into(), value: value.into() };
+        match self.fields.iter_mut().find(|other| other.entity == entity && other.component == component && other.path == field.path) {
+            Some(other) => { *other = field }
+            None => { self.fields.push(field) }
+        }
+    }
+
+    // The field keeps its current value until the next refresh
+    pub fn remove(&mut self, entity: usize, component: ComponentID, path: &str) -> bool {
+        let len = self.fields.len();
+        self.fields.retain(|field| !(field.entity == entity && field.component == component && field.path == path));
+        len != self.fields.len()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, FieldOverride> {
+        self.fields.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+}
+
+/*
+INSTANCES
+ */
+
+// Added to the root entity of each instance
+#[derive(Clone)]
+pub struct PrefabInstance {
+    source: Arc<PrefabSource>,
+    version: u32,
+    entities: Vec<EntityID>,
+    // Components inserted from the prefab on each entity, removed on refresh if the prefab does not contain them anymore
+    components: Vec<Vec<ComponentID>>,
+    overrides: PrefabOverrides,
+}
+
+impl PrefabInstance {
+    pub fn source(&self) -> &Arc<PrefabSource> {
+        &self.source
+    }
+
+    // Instance entities, in prefab order
+    pub fn entities(&self) -> &[EntityID] {
+        &self.entities
+    }
+
+    pub fn overrides(&self) -> &PrefabOverrides {
+        &self.overrides
+    }
+
+    pub fn is_outdated(&self) -> bool {
+        self.version != self.source.version()
+    }
+}
+
+impl Ecs {
+    // Spawn the prefab entities and return the instance root
+    pub fn instantiate(&mut self, source: &Arc<PrefabSource>, overrides: PrefabOverrides) -> Result<EntityID, EcsError> {
+        let mut instance = PrefabInstance { source: source.clone(), version: 0, entities: vec![], components: vec![], overrides };
+        self.update_instance(&mut instance)?;
+        let root = instance.entities[0];
+        self.register_clone::<PrefabInstance>();
+        self.add(root, instance)?;
+        Ok(root)
+    }
+
+    // Refresh instances of modified prefabs, returning how many were updated
+    pub fn refresh_prefabs(&mut self) -> Result<usize, EcsError> {
+        let outdated: Vec<EntityID> = self.query::<(EntityID, &PrefabInstance)>().iter()
+            .filter(|(_, instance)| instance.is_outdated())
+            .map(|(root, _)| root)
+            .collect();
+        // A failing instance does not prevent the following ones from being refreshed
+        let mut error = None;
+        for root in &outdated {
+            if let Err(refresh_error) = self.refresh_instance(*root) {
+                error.get_or_insert(refresh_error);
+            }
+        }
+        error.map_or(Ok(outdated.len()), Err)
+    }
+
+    // Apply the current prefab again. Entity ids and overrides are kept, as well as components that were not added by the prefab.
+    // The instance is stored even if the refresh fails midway, to keep track of the entities already created.
+    pub fn refresh_instance(&mut self, root: EntityID) -> Result<(), EcsError> {
+        let mut instance = self.prefab_instance(root)?.clone();
+        let result = self.update_instance(&mut instance);
+        self.add(root, instance)?;
+        result
+    }
+
+    // Change a field of an instance entity and keep it across refreshes
+    pub fn set_prefab_override(&mut self, root: EntityID, entity: usize, component: ComponentID, path: &str, value: impl Into<Value>) -> Result<(), EcsError> {
+        let value = value.into();
+        let target = *self.prefab_instance(root)?.entities.get(entity).ok_or(EcsError::InvalidPrefabEntity(entity))?;
+        let missing = EcsError::MissingComponent(target, self.components().try_get(&component).map(|data| data.name.clone()).unwrap_or_default());
+        self.reflect_mut(target, component).ok_or(missing)?.set(path, value)?;
+        self.get_mut::<PrefabInstance>(root).expect("missing prefab instance").overrides.set(entity, component, path, value);
+        Ok(())
+    }
+
+    fn prefab_instance(&self, root: EntityID) -> Result<&PrefabInstance, EcsError> {
+        self.check_alive(root)?;
+        self.get::<PrefabInstance>(root).ok_or(EcsError::MissingComponent(root, std::any::type_name::<PrefabInstance>().into()))
+    }
+
+    fn update_instance(&mut self, instance: &mut PrefabInstance) -> Result<(), EcsError> {
+        instance.version = instance.source.version();
+        let scene = instance.source.get().scene();
+
+        // Reuse existing entities so references to the instance stay valid
+        let old_entities = std::mem::take(&mut instance.entities);
+        for entity in old_entities.iter().skip(scene.entities.len()) {
+            if self.is_alive(*entity) {
+                self.destroy(*entity)?;
+            }
+        }
+        let mut entities = Vec::with_capacity(scene.entities.len());
+        for index in 0..scene.entities.len() {
+            match old_entities.get(index) {
+                Some(entity) if self.is_alive(*entity) => { entities.push(*entity) }
+                _ => { entities.push(self.create()) }
+            }
+        }
+        instance.entities = entities.clone();
+        instance.components.resize(entities.len(), vec![]);
+
+        let mut map = EntityMap::default();
+        let mut indices = HashMap::new();
+        for (index, (id, _)) in scene.entities.iter().enumerate() {
+            map.insert(*id, entities[index]);
+            indices.insert(*id, index);
+        }
+
+        // Hierarchy components are rebuilt from parent links, to keep instance roots attached to their current parent
+        let mut parents = vec![None; entities.len()];
+        for (index, (_, loaded)) in scene.entities.into_iter().enumerate() {
+            let mut inserts = vec![];
+            for mut loaded in loaded {
+                let id = *loaded.component.id();
+                if id == ComponentID::of::<Parent>() {
+                    let parent = unsafe { &*(loaded.component.data() as *const Parent) }.get();
+                    parents[index] = indices.get(&parent).cloned();
+                } else if id != ComponentID::of::<Children>() {
+                    unsafe { (loaded.map_entities)(loaded.component.data_mut(), &map) };
+                    inserts.push(loaded.component);
+                }
+            }
+            let ids: Vec<ComponentID> = inserts.iter().map(|component| *component.id()).collect();
+            let removes = instance.components[index].iter().filter(|id| !ids.contains(id)).cloned().collect();
+            self.apply_changes(entities[index], inserts, removes)?;
+            instance.components[index] = ids;
+        }
+
+        for (index, parent) in parents.into_iter().enumerate() {
+            let entity = entities[index];
+            match parent {
+                Some(parent) => { self.set_parent(entity, entities[parent])? }
+                None => {
+                    if self.parent(entity).is_some_and(|parent| entities.contains(&parent)) {
+                        self.remove_parent(entity)?;
+                    }
+                }
+            }
+        }
+
+        // Overrides targeting entities or components removed from the prefab are ignored, in case they come back.
+        // Overrides of fields that were renamed or changed type are dropped.
+        instance.overrides.fields.retain(|field| {
+            match entities.get(field.entity).and_then(|entity| self.reflect_mut(*entity, field.component)) {
+                Some(mut component) => { component.set(&field.path, field.value).is_ok() }
+                None => { true }
+            }
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use serde::{Deserialize, Serialize};
+
+    use crate::component::ComponentID;
+    use crate::ecs::Ecs;
+    use crate::entity::EntityID;
+    use crate::error::EcsError;
+    use crate::prefab::{Prefab, PrefabInstance, PrefabOverrides, PrefabSource};
+    use crate::reflect::Value;
+    use crate::scene::{EntityMap, SceneRegistry, SerializeComponent};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Health {
+        current: u32,
+        max: u32,
+    }
+
+    crate::reflect!(Health { current, max });
+
+    #[derive(Serialize, Deserialize)]
+    struct Target(EntityID);
+
+    impl SerializeComponent for Health {}
+
+    impl SerializeComponent for Target {
+        fn map_entities(&mut self, entities: &EntityMap) {
+            self.0 = entities.get(self.0);
+        }
+    }
+
+    struct Runtime;
+
+    fn registry() -> Arc<SceneRegistry> {
+        let mut registry = SceneRegistry::default();
+        registry.register_named::<Health>("Health").register_named::<Target>("Target");
+        Arc::new(registry)
+    }
+
+    // Root with a single child targeting it, or two children
+    fn build_prefab(registry: &Arc<SceneRegistry>, max: u32, children: usize) -> Prefab {
+        let mut ecs = Ecs::default();
+        let root = ecs.create();
+        ecs.add(root, Health { current: max, max }).unwrap();
+        for _ in 0..children {
+            let child = ecs.create();
+            ecs.add(child, Target(root)).unwrap();
+            ecs.set_parent(child, root).unwrap();
+        }
+        Prefab::from_root(&ecs, registry, root).unwrap()
+    }
+
+    #[test]
+    fn instantiate_test() {
+        let registry = registry();
+        let source = PrefabSource::new(build_prefab(&registry, 10, 1));
+        assert_eq!(source.get().len(), 2);
+
+        let mut ecs = Ecs::default();
+        ecs.register_reflect::<Health>();
+        let health = ComponentID::of::<Health>();
+        let r0 = ecs.instantiate(&source, PrefabOverrides::default()).unwrap();
+        let r1 = ecs.instantiate(&source, PrefabOverrides::default().with_field(0, health, "current", 3u32)).unwrap();
+        assert_eq!(ecs.entities().count(), 4);
+
+        assert_eq!(ecs.get::<Health>(r0), Some(&Health { current: 10, max: 10 }));
+        assert_eq!(ecs.get::<Health>(r1), Some(&Health { current: 3, max: 10 }));
+        for root in [r0, r1] {
+            let child = ecs.children(root)[0];
+            assert_eq!(ecs.parent(child), Some(root));
+            assert_eq!(ecs.get::<Target>(child).unwrap().0, root);
+        }
+
+        ecs.set_prefab_override(r0, 0, health, "max", 20u32).unwrap();
+        assert_eq!(ecs.get::<Health>(r0).unwrap().max, 20);
+        assert_eq!(ecs.get::<PrefabInstance>(r0).unwrap().overrides().len(), 1);
+        assert!(ecs.set_prefab_override(r0, 0, health, "max", 1.0f32).is_err());
+        assert_eq!(ecs.set_prefab_override(r0, 9, health, "max", 20u32), Err(EcsError::InvalidPrefabEntity(9)));
+    }
+
+    #[test]
+    fn refresh_test() {
+        let registry = registry();
+        let source = PrefabSource::new(build_prefab(&registry, 10, 1));
+
+        let mut ecs = Ecs::default();
+        ecs.register_reflect::<Health>();
+        let parent = ecs.create();
+        let root = ecs.instantiate(&source, PrefabOverrides::default().with_field(0, ComponentID::of::<Health>(), "current", 3u32)).unwrap();
+        ecs.set_parent(root, parent).unwrap();
+        let child = ecs.children(root)[0];
+        ecs.add(child, Runtime).unwrap();
+        assert_eq!(ecs.refresh_prefabs(), Ok(0));
+
+        // Prefab gets a new child, existing entities are updated in place
+        source.set(build_prefab(&registry, 50, 2));
+        assert_eq!(ecs.refresh_prefabs(), Ok(1));
+        assert_eq!(ecs.refresh_prefabs(), Ok(0));
+        assert_eq!(ecs.get::<Health>(root), Some(&Health { current: 3, max: 50 }));
+        assert_eq!(ecs.parent(root), Some(parent));
+        let children = ecs.children(root).to_vec();
+        assert_eq!(children.len(), 2);
+        assert_eq!(children[0], child);
+        assert!(ecs.get::<Runtime>(child).is_some());
+        assert_eq!(ecs.get::<Target>(children[1]).unwrap().0, root);
+
+        // Removed entities are despawned
+        source.set(build_prefab(&registry, 50, 0));
+        ecs.refresh_instance(root).unwrap();
+        assert!(ecs.children(root).is_empty());
+        assert!(!ecs.is_alive(child));
+        assert_eq!(ecs.get::<PrefabInstance>(root).unwrap().entities(), &[root]);
+    }
+
+    #[test]
+    fn stale_override_test() {
+        let registry = registry();
+        let source = PrefabSource::new(build_prefab(&registry, 10, 1));
+
+        // Overrides saved while 'max' was a float and before 'current' was renamed
+        let health = ComponentID::of::<Health>();
+        let overrides = PrefabOverrides::default()
+            .with_field(0, health, "current", 3u32)
+            .with_field(0, health, "max", 1.5f32)
+            .with_field(0, health, "hp", 5u32);
+        let mut ecs = Ecs::default();
+        ecs.register_reflect::<Health>();
+        let root = ecs.instantiate(&source, overrides).unwrap();
+        assert_eq!(ecs.get::<Health>(root), Some(&Health { current: 3, max: 10 }));
+        assert_eq!(ecs.get::<PrefabInstance>(root).unwrap().overrides().len(), 1);
+
+        // An override that stops matching the prefab does not break refreshes
+        let mut instance = ecs.get::<PrefabInstance>(root).unwrap().clone();
+        instance.overrides.set(0, health, "max", 2.0f32);
+        ecs.add(root, instance).unwrap();
+        source.set(build_prefab(&registry, 50, 2));
+        assert_eq!(ecs.refresh_prefabs(), Ok(1));
+        assert_eq!(ecs.refresh_prefabs(), Ok(0));
+        assert_eq!(ecs.get::<Health>(root), Some(&Health { current: 3, max: 50 }));
+        let instance = ecs.get::<PrefabInstance>(root).unwrap();
+        assert_eq!(instance.entities().len(), 3);
+        assert_eq!(instance.overrides().len(), 1);
+        assert_eq!(ecs.children(root), &instance.entities()[1..]);
+    }
+
+    #[test]
+    fn ron_test() {
+        let registry = registry();
+        let prefab = build_prefab(&registry, 7, 2);
+        let ron = prefab.save_ron().unwrap();
+        let loaded = Prefab::load_ron(&registry, &ron).unwrap();
+        assert_eq!(loaded.len(), 3);
+
+        let mut ecs = Ecs::default();
+        ecs.register_reflect::<Health>();
+        let root = ecs.instantiate(&PrefabSource::new(loaded), PrefabOverrides::default()).unwrap();
+        assert_eq!(ecs.reflect(root, ComponentID::of::<Health>()).unwrap().get("max"), Ok(Value::U32(7)));
+        assert_eq!(ecs.children(root).len(), 2);
+    }
+}
@@ -0,0 +1,565 @@
+use std::any::{Any, type_name, TypeId};
+use std::marker::PhantomData;
+use std::slice::Iter;
+
+use crate::archetype::{Archetype, ComponentData};
+use crate::component::{ChangeTick, ChangeTicks, Component, ComponentID, StorageType};
+use crate::ecs::Ecs;
+use crate::entity::EntityID;
+use crate::error::EcsError;
+use crate::resource::Resource;
+use crate::sparse_set::SparseSet;
+
+/*
+ACCESS
+ */
+
+#[derive(Default, Clone, Debug)]
+pub struct Access {
+    reads: Vec<ComponentID>,
+    writes: Vec<ComponentID>,
+    resource_reads: Vec<TypeId>,
+    resource_writes: Vec<TypeId>,
+}
+
+impl Access {
+    pub fn read<C: Any>(&mut self) {
+        Self::add_read(&mut self.reads, &self.writes, ComponentID::of::<C>(), type_name::<C>());
+    }
+
+    pub fn write<C: Any>(&mut self) {
+        Self::add_write(&mut self.writes, &self.reads, ComponentID::of::<C>(), type_name::<C>());
+    }
+
+    pub fn read_resource<R: Resource>(&mut self) {
+        Self::add_read(&mut self.resource_reads, &self.resource_writes, TypeId::of::<R>(), type_name::<R>());
+    }
+
+    pub fn write_resource<R: Resource>(&mut self) {
+        Self::add_write(&mut self.resource_writes, &self.resource_reads, TypeId::of::<R>(), type_name::<R>());
+    }
+
+    // Filters only read change ticks, which does not conflict with a write of the same query
+    pub fn read_ticks<C: Any>(&mut self) {
+        let id = ComponentID::of::<C>();
+        if !self.reads.contains(&id) && !self.writes.contains(&id) {
+            self.reads.push(id);
+        }
+    }
+
+    fn add_read<Id: PartialEq>(reads: &mut Vec<Id>, writes: &[Id], id: Id, name: &str) {
+        assert!(!writes.contains(&id), "'{name}' is already mutably borrowed");
+        if !reads.contains(&id) {
+            reads.push(id);
+        }
+    }
+
+    fn add_write<Id: PartialEq>(writes: &mut Vec<Id>, reads: &[Id], id: Id, name: &str) {
+        assert!(!writes.contains(&id) && !reads.contains(&id), "'{name}' is already borrowed");
+        writes.push(id);
+    }
+
+    pub fn reads(&self) -> &Vec<ComponentID> {
+        &self.reads
+    }
+
+    pub fn writes(&self) -> &Vec<ComponentID> {
+        &self.writes
+    }
+
+    pub fn is_compatible(&self, other: &Access) -> bool {
+        Self::is_compatible_with(&self.reads, &self.writes, &other.reads, &other.writes) &&
+            Self::is_compatible_with(&self.resource_reads, &self.resource_writes, &other.resource_reads, &other.resource_writes)
+    }
+
+    fn is_compatible_with<Id: PartialEq>(reads: &[Id], writes: &[Id], other_reads: &[Id], other_writes: &[Id]) -> bool {
+        for write in writes {
+            if other_reads.contains(write) || other_writes.contains(write) {
+                return false;
+            }
+        }
+        for write in other_writes {
+            if reads.contains(write) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/*
+COLUMNS
+ */
+
+// Column of the fetched archetype for table components, sparse set for sparse set components
+#[derive(Copy, Clone)]
+pub enum ColumnFetch<'w> {
+    Table(&'w ComponentData),
+    Sparse(&'w SparseSet),
+}
+
+impl<'w> ColumnFetch<'w> {
+    // Archetypes can only be discarded on table components, sparse set components are checked per entity
+    pub fn matches(world: &Ecs, archetype: &Archetype, id: &ComponentID) -> bool {
+        match world.components().storage(id) {
+            StorageType::Table => { archetype.contains(id) }
+            StorageType::SparseSet => { world.sparse_sets().get(id).is_some_and(|set| !set.is_empty()) }
+        }
+    }
+
+    pub fn new(world: &'w Ecs, archetype: &'w Archetype, id: &ComponentID) -> Option<Self> {
+        match world.components().storage(id) {
+            StorageType::Table => { archetype.column(id).map(Self::Table) }
+            StorageType::SparseSet => { world.sparse_sets().get(id).map(Self::Sparse) }
+        }
+    }
+
+    // Index of the entity component in the column, given its index in the archetype
+    pub fn position(&self, entity: EntityID, index: usize) -> Option<usize> {
+        match self {
+            Self::Table(_) => { Some(index) }
+            Self::Sparse(set) => { set.dense_index(entity) }
+        }
+    }
+
+    pub fn column(&self) -> &'w ComponentData {
+        match self {
+            Self::Table(column) => { column }
+            Self::Sparse(set) => { set.column() }
+        }
+    }
+}
+
+/*
+QUERY DATA
+ */
+
+/// # Safety
+/// `access` must declare every component that `fetch` reads or writes.
+pub unsafe trait QueryData {
+    type Item<'w>;
+    type Fetch<'w>;
+
+    fn access(access: &mut Access);
+    fn matches(world: &Ecs, archetype: &Archetype) -> bool;
+
+    /// # Safety
+    /// `archetype` must match this query.
+    unsafe fn init_fetch<'w>(world: &'w Ecs, archetype: &'w Archetype, ticks: ChangeTicks) -> Self::Fetch<'w>;
+
+    // Whether the entity owns the sparse set components of the query
+    fn contains(fetch: &Self::Fetch<'_>, entity: EntityID, index: usize) -> bool;
+
+    /// # Safety
+    /// `index` must be lower than the entity count of the fetched archetype and `contains` must be true.
+    unsafe fn fetch<'w>(fetch: &mut Self::Fetch<'w>, entity: EntityID, index: usize) -> Self::Item<'w>;
+}
+
+unsafe impl QueryData for EntityID {
+    type Item<'w> = EntityID;
+    type Fetch<'w> = ();
+
+    fn access(_: &mut Access) {}
+
+    fn matches(_: &Ecs, _: &Archetype) -> bool {
+        true
+    }
+
+    unsafe fn init_fetch<'w>(_: &'w Ecs, _: &'w Archetype, _: ChangeTicks) -> Self::Fetch<'w> {}
+
+    fn contains(_: &Self::Fetch<'_>, _: EntityID, _: usize) -> bool {
+        true
+    }
+
+    unsafe fn fetch<'w>(_: &mut Self::Fetch<'w>, entity: EntityID, _: usize) -> Self::Item<'w> {
+        entity
+    }
+}
+
+unsafe impl<C: Component> QueryData for &C {
+    type Item<'w> = &'w C;
+    type Fetch<'w> = ColumnFetch<'w>;
+
+    fn access(access: &mut Access) {
+        access.read::<C>();
+    }
+
+    fn matches(world: &Ecs, archetype: &Archetype) -> bool {
+        ColumnFetch::matches(world, archetype, &ComponentID::of::<C>())
+    }
+
+    unsafe fn init_fetch<'w>(world: &'w Ecs, archetype: &'w Archetype, _: ChangeTicks) -> Self::Fetch<'w> {
+        ColumnFetch::new(world, archetype, &ComponentID::of::<C>()).expect("archetype does not match query")
+    }
+
+    fn contains(fetch: &Self::Fetch<'_>, entity: EntityID, index: usize) -> bool {
+        fetch.position(entity, index).is_some()
+    }
+
+    unsafe fn fetch<'w>(fetch: &mut Self::Fetch<'w>, entity: EntityID, index: usize) -> Self::Item<'w> {
+        let position = fetch.position(entity, index).expect("entity does not match query");
+        &*(fetch.column().data_ptr() as *const C).add(position)
+    }
+}
+
+// Fetched components are marked as changed
+unsafe impl<C: Component> QueryData for &mut C {
+    type Item<'w> = &'w mut C;
+    type Fetch<'w> = (ColumnFetch<'w>, ChangeTick);
+
+    fn access(access: &mut Access) {
+        access.write::<C>();
+    }
+
+    fn matches(world: &Ecs, archetype: &Archetype) -> bool {
+        ColumnFetch::matches(world, archetype, &ComponentID::of::<C>())
+    }
+
+    unsafe fn init_fetch<'w>(world: &'w Ecs, archetype: &'w Archetype, ticks: ChangeTicks) -> Self::Fetch<'w> {
+        (ColumnFetch::new(world, archetype, &ComponentID::of::<C>()).expect("archetype does not match query"), ticks.this_run)
+    }
+
+    fn contains(fetch: &Self::Fetch<'_>, entity: EntityID, index: usize) -> bool {
+        fetch.0.position(entity, index).is_some()
+    }
+
+    unsafe fn fetch<'w>(fetch: &mut Self::Fetch<'w>, entity: EntityID, index: usize) -> Self::Item<'w> {
+        let (column, this_run) = *fetch;
+        let position = column.position(entity, index).expect("entity does not match query");
+        *column.column().changed_ticks_ptr().add(position) = this_run;
+        &mut *(column.column().data_ptr() as *mut C).add(position)
+    }
+}
+
+unsafe impl<D: QueryData> QueryData for Option<D> {
+    type Item<'w> = Option<D::Item<'w>>;
+    type Fetch<'w> = Option<D::Fetch<'w>>;
+
+    fn access(access: &mut Access) {
+        D::access(access);
+    }
+
+    fn matches(_: &Ecs, _: &Archetype) -> bool {
+        true
+    }
+
+    unsafe fn init_fetch<'w>(world: &'w Ecs, archetype: &'w Archetype, ticks: ChangeTicks) -> Self::Fetch<'w> {
+        if D::matches(world, archetype) { Some(D::init_fetch(world, archetype, ticks)) } else { None }
+    }
+
+    fn contains(_: &Self::Fetch<'_>, _: EntityID, _: usize) -> bool {
+        true
+    }
+
+    unsafe fn fetch<'w>(fetch: &mut Self::Fetch<'w>, entity: EntityID, index: usize) -> Self::Item<'w> {
+        fetch.as_mut().filter(|fetch| D::contains(fetch, entity, index)).map(|fetch| D::fetch(fetch, entity, index))
+    }
+}
+
+macro_rules! impl_query_data_tuple {
+    ($($name:ident),*) => {
+        #[allow(non_snake_case, unused_variables, clippy::unused_unit)]
+        unsafe impl<$($name: QueryData),*> QueryData for ($($name,)*) {
+            type Item<'w> = ($($name::Item<'w>,)*);
+            type Fetch<'w> = ($($name::Fetch<'w>,)*);
+
+            fn access(access: &mut Access) {
+                $($name::access(access);)*
+            }
+
+            fn matches(world: &Ecs, archetype: &Archetype) -> bool {
+                true $(&& $name::matches(world, archetype))*
+            }
+
+            unsafe fn init_fetch<'w>(world: &'w Ecs, archetype: &'w Archetype, ticks: ChangeTicks) -> Self::Fetch<'w> {
+                ($($name::init_fetch(world, archetype, ticks),)*)
+            }
+
+            fn contains(fetch: &Self::Fetch<'_>, entity: EntityID, index: usize) -> bool {
+                let ($($name,)*) = fetch;
+                true $(&& $name::contains($name, entity, index))*
+            }
+
+            unsafe fn fetch<'w>(fetch: &mut Self::Fetch<'w>, entity: EntityID, index: usize) -> Self::Item<'w> {
+                let ($($name,)*) = fetch;
+                ($($name::fetch($name, entity, index),)*)
+            }
+        }
+    };
+}
+
+impl_query_data_tuple!();
+impl_query_data_tuple!(A);
+impl_query_data_tuple!(A, B);
+impl_query_data_tuple!(A, B, C);
+impl_query_data_tuple!(A, B, C, D);
+impl_query_data_tuple!(A, B, C, D, E);
+impl_query_data_tuple!(A, B, C, D, E, F);
+impl_query_data_tuple!(A, B, C, D, E, F, G);
+impl_query_data_tuple!(A, B, C, D, E, F, G, H);
+
+/*
+FILTERS
+ */
+
+/// # Safety
+/// `access` must declare every component that `filter_fetch` reads.
+pub unsafe trait QueryFilter {
+    type Fetch<'w>;
+
+    fn access(_access: &mut Access) {}
+    fn matches(world: &Ecs, archetype: &Archetype) -> bool;
+
+    /// # Safety
+    /// `archetype` must match this filter.
+    unsafe fn init_fetch<'w>(world: &'w Ecs, archetype: &'w Archetype, ticks: ChangeTicks) -> Self::Fetch<'w>;
+
+    /// # Safety
+    /// `index` must be lower than the entity count of the fetched archetype.
+    unsafe fn filter_fetch(fetch: &mut Self::Fetch<'_>, entity: EntityID, index: usize) -> bool;
+}
+
+pub struct With<C: Any>(PhantomData<C>);
+
+pub struct Without<C: Any>(PhantomData<C>);
+
+// Components added since the last run of the system
+pub struct Added<C: Component>(PhantomData<C>);
+
+// Components added or mutably accessed since the last run of the system
+pub struct Changed<C: Component>(PhantomData<C>);
+
+// Sparse set of the component, if it is stored in one
+fn sparse_set<C: Any>(world: &Ecs) -> Option<&SparseSet> {
+    let id = ComponentID::of::<C>();
+    match world.components().storage(&id) {
+        StorageType::Table => { None }
+        StorageType::SparseSet => { world.sparse_sets().get(&id) }
+    }
+}
+
+unsafe impl<C: Any> QueryFilter for With<C> {
+    type Fetch<'w> = Option<&'w SparseSet>;
+
+    fn matches(world: &Ecs, archetype: &Archetype) -> bool {
+        ColumnFetch::matches(world, archetype, &ComponentID::of::<C>())
+    }
+
+    unsafe fn init_fetch<'w>(world: &'w Ecs, _: &'w Archetype, _: ChangeTicks) -> Self::Fetch<'w> {
+        sparse_set::<C>(world)
+    }
+
+    unsafe fn filter_fetch(fetch: &mut Self::Fetch<'_>, entity: EntityID, _: usize) -> bool {
+        fetch.is_none_or(|set| set.contains(entity))
+    }
+}
+
+unsafe impl<C: Any> QueryFilter for Without<C> {
+    type Fetch<'w> = Option<&'w SparseSet>;
+
+    fn matches(world: &Ecs, archetype: &Archetype) -> bool {
+        let id = ComponentID::of::<C>();
+        world.components().storage(&id) == StorageType::SparseSet || !archetype.contains(&id)
+    }
+
+    unsafe fn init_fetch<'w>(world: &'w Ecs, _: &'w Archetype, _: ChangeTicks) -> Self::Fetch<'w> {
+        sparse_set::<C>(world)
+    }
+
+    unsafe fn filter_fetch(fetch: &mut Self::Fetch<'_>, entity: EntityID, _: usize) -> bool {
+        fetch.is_none_or(|set| !set.contains(entity))
+    }
+}
+
+unsafe impl<C: Component> QueryFilter for Added<C> {
+    type Fetch<'w> = (ColumnFetch<'w>, ChangeTicks);
+
+    fn access(access: &mut Access) {
+        access.read_ticks::<C>();
+    }
+
+    fn matches(world: &Ecs, archetype: &Archetype) -> bool {
+        ColumnFetch::matches(world, archetype, &ComponentID::of::<C>())
+    }
+
+    unsafe fn init_fetch<'w>(world: &'w Ecs, archetype: &'w Archetype, ticks: ChangeTicks) -> Self::Fetch<'w> {
+        (ColumnFetch::new(world, archetype, &ComponentID::of::<C>()).expect("archetype does not match filter"), ticks)
+    }
+
+    unsafe fn filter_fetch(fetch: &mut Self::Fetch<'_>, entity: EntityID, index: usize) -> bool {
+        let (column, ticks) = fetch;
+        column.position(entity, index).is_some_and(|position| ticks.is_newer(column.column().added_tick(position)))
+    }
+}
+
+unsafe impl<C: Component> QueryFilter for Changed<C> {
+    type Fetch<'w> = (ColumnFetch<'w>, ChangeTicks);
+
+    fn access(access: &mut Access) {
+        access.read_ticks::<C>();
+    }
+
+    fn matches(world: &Ecs, archetype: &Archetype) -> bool {
+        ColumnFetch::matches(world, archetype, &ComponentID::of::<C>())
+    }
+
+    unsafe fn init_fetch<'w>(world: &'w Ecs, archetype: &'w Archetype, ticks: ChangeTicks) -> Self::Fetch<'w> {
+        (ColumnFetch::new(world, archetype, &ComponentID::of::<C>()).expect("archetype does not match filter"), ticks)
+    }
+
+    unsafe fn filter_fetch(fetch: &mut Self::Fetch<'_>, entity: EntityID, index: usize) -> bool {
+        let (column, ticks) = fetch;
+        column.position(entity, index).is_some_and(|position| ticks.is_newer(column.column().changed_tick(position)))
+    }
+}
+
+macro_rules! impl_query_filter_tuple {
+    ($($name:ident),*) => {
+        #[allow(non_snake_case, unused_variables, clippy::unused_unit)]
+        unsafe impl<$($name: QueryFilter),*> QueryFilter for ($($name,)*) {
+            type Fetch<'w> = ($($name::Fetch<'w>,)*);
+
+            fn access(access: &mut Access) {
+                $($name::access(access);)*
+            }
+
+            fn matches(world: &Ecs, archetype: &Archetype) -> bool {
+                true $(&& $name::matches(world, archetype))*
+            }
+
+            unsafe fn init_fetch<'w>(world: &'w Ecs, archetype: &'w Archetype, ticks: ChangeTicks) -> Self::Fetch<'w> {
+                ($($name::init_fetch(world, archetype, ticks),)*)
+            }
+
+            unsafe fn filter_fetch(fetch: &mut Self::Fetch<'_>, entity: EntityID, index: usize) -> bool {
+                let ($($name,)*) = fetch;
+                true $(&& $name::filter_fetch($name, entity, index))*
+            }
+        }
+    };
+}
+
+impl_query_filter_tuple!();
+impl_query_filter_tuple!(A);
+impl_query_filter_tuple!(A, B);
+impl_query_filter_tuple!(A, B, C);
+impl_query_filter_tuple!(A, B, C, D);
+impl_query_filter_tuple!(A, B, C, D, E);
+impl_query_filter_tuple!(A, B, C, D, E, F);
+impl_query_filter_tuple!(A, B, C, D, E, F, G);
+impl_query_filter_tuple!(A, B, C, D, E, F, G, H);
+
+/*
+QUERY
+ */
+
+pub struct Query<'w, D: QueryData, F: QueryFilter = ()> {
+    world: &'w Ecs,
+    ticks: ChangeTicks,
+    _marker: PhantomData<(D, F)>,
+}
+
+impl<'w, D: QueryData, F: QueryFilter> Query<'w, D, F> {
+    pub(crate) fn new(world: &'w mut Ecs) -> Self {
+        let mut access = Access::default();
+        D::access(&mut access);
+        F::access(&mut access);
+        let ticks = world.change_ticks();
+        Self { world, ticks, _marker: PhantomData }
+    }
+
+    /// # Safety
+    /// Caller must ensure no other access to the queried components is running at the same time.
+    pub(crate) unsafe fn new_unchecked(world: &'w Ecs, ticks: ChangeTicks) -> Self {
+        Self { world, ticks, _marker: PhantomData }
+    }
+
+    fn matches(world: &Ecs, archetype: &Archetype) -> bool {
+        D::matches(world, archetype) && F::matches(world, archetype)
+    }
+
+    pub fn iter(&mut self) -> QueryIter<'_, D, F> {
+        QueryIter::new(self.world, self.ticks)
+    }
+
+    pub fn get(&mut self, entity: EntityID) -> Result<D::Item<'_>, EcsError> {
+        if !self.world.is_alive(entity) {
+            return Err(EcsError::DeadEntity(entity));
+        }
+        let (archetype_id, entity_index) = self.world.location(entity).ok_or(EcsError::QueryMismatch(entity))?;
+        let archetype = self.world.archetypes().get_archetype(&archetype_id);
+        if !Self::matches(self.world, archetype) || !unsafe { F::filter_fetch(&mut F::init_fetch(self.world, archetype, self.ticks), entity, entity_index) } {
+            return Err(EcsError::QueryMismatch(entity));
+        }
+        let mut fetch = unsafe { D::init_fetch(self.world, archetype, self.ticks) };
+        if !D::contains(&fetch, entity, entity_index) {
+            return Err(EcsError::QueryMismatch(entity));
+        }
+        unsafe { Ok(D::fetch(&mut fetch, entity, entity_index)) }
+    }
+
+    // Count matching entities without fetching their components
+    pub fn count(&mut self) -> usize {
+        self.world.archetypes().iter().filter(|archetype| Self::matches(self.world, archetype)).map(|archetype| {
+            let fetch = unsafe { D::init_fetch(self.world, archetype, self.ticks) };
+            let mut filter = unsafe { F::init_fetch(self.world, archetype, self.ticks) };
+            archetype.entities().iter().enumerate()
+                .filter(|(index, entity)| D::contains(&fetch, **entity, *index) && unsafe { F::filter_fetch(&mut filter, **entity, *index) })
+                .count()
+        }).sum()
+    }
+}
+
+impl<'w, D: QueryData, F: QueryFilter> IntoIterator for Query<'w, D, F> {
+    type Item = D::Item<'w>;
+    type IntoIter = QueryIter<'w, D, F>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        QueryIter::new(self.world, self.ticks)
+    }
+}
+
+/*
+ITERATOR
+ */
+
+pub struct QueryIter<'w, D: QueryData, F: QueryFilter> {
+    world: &'w Ecs,
+    archetypes: Iter<'w, Archetype>,
+    ticks: ChangeTicks,
+    fetch: Option<(D::Fetch<'w>, F::Fetch<'w>)>,
+    entities: &'w [EntityID],
+    index: usize,
+}
+
+impl<'w, D: QueryData, F: QueryFilter> QueryIter<'w, D, F> {
+    fn new(world: &'w Ecs, ticks: ChangeTicks) -> Self {
+        Self { world, archetypes: world.archetypes().iter(), ticks, fetch: None, entities: &[], index: 0 }
+    }
+}
+
+impl<'w, D: QueryData, F: QueryFilter> Iterator for QueryIter<'w, D, F> {
+    type Item = D::Item<'w>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((fetch, filter)) = &mut self.fetch {
+                while self.index < self.entities.len() {
+                    let index = self.index;
+                    let entity = self.entities[index];
+                    self.index += 1;
+                    if D::contains(fetch, entity, index) && unsafe { F::filter_fetch(filter, entity, index) } {
+                        return Some(unsafe { D::fetch(fetch, entity, index) });
+                    }
+                }
+            }
+
+            // Move to the next archetype containing the requested components
+            let world = self.world;
+            let archetype = self.archetypes.find(|archetype| !archetype.is_empty() && Query::<D, F>::matches(world, archetype))?;
+            self.fetch = Some(unsafe { (D::init_fetch(world, archetype, self.ticks), F::init_fetch(world, archetype, self.ticks)) });
+            self.entities = archetype.entities();
+            self.index = 0;
+        }
+    }
+}
@@ -0,0 +1,433 @@
+use std::alloc::{alloc_zeroed, dealloc, handle_alloc_error, Layout};
+use std::borrow::Cow;
+
+use maths::vec2::Vec2;
+use maths::vec3::Vec3;
+use maths::vec4::Vec4;
+
+use crate::component::{Component, ComponentData, ComponentID, ComponentRegistry};
+use crate::ecs::Ecs;
+use crate::entity::EntityID;
+use crate::error::EcsError;
+
+/*
+FIELDS
+ */
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum FieldType {
+    Bool,
+    U8,
+    U16,
+    U32,
+    U64,
+    I8,
+    I16,
+    I32,
+    I64,
+    F32,
+    F64,
+    Entity,
+    Struct(Vec<FieldInfo>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FieldInfo {
+    pub name: Cow<'static, str>,
+    pub offset: usize,
+    pub field_type: FieldType,
+}
+
+impl FieldInfo {
+    pub fn new(name: impl Into<Cow<'static, str>>, offset: usize, field_type: FieldType) -> Self {
+        Self { name: name.into(), offset, field_type }
+    }
+
+    // The accessor is only used to infer the field type
+    pub fn of<S, F: ReflectField>(name: &'static str, offset: usize, _accessor: impl Fn(&S) -> &F) -> Self {
+        Self::new(name, offset, F::field_type())
+    }
+}
+
+// Field types that can be exposed through reflection
+/// # Safety
+/// `field_type` must describe the layout of the type : reflection reads and writes it through raw pointers.
+pub unsafe trait ReflectField {
+    fn field_type() -> FieldType;
+}
+
+/// # Safety
+/// Every field must lie inside the component, at its real offset and with its real type. Use the `reflect!` macro.
+pub unsafe trait Reflect: Component {
+    fn fields() -> Vec<FieldInfo>;
+}
+
+unsafe impl<T: Reflect> ReflectField for T {
+    fn field_type() -> FieldType {
+        FieldType::Struct(T::fields())
+    }
+}
+
+// Implement Reflect by listing the exposed fields : reflect!(Health { current, max });
+#[macro_export]
+macro_rules! reflect {
+    ($ty:ty { $($field:ident),* $(,)? }) => {
+        unsafe impl $crate::reflect::Reflect for $ty {
+            fn fields() -> Vec<$crate::reflect::FieldInfo> {
+                vec![$($crate::reflect::FieldInfo::of(stringify!($field), std::mem::offset_of!($ty, $field), |component: &$ty| &component.$field)),*]
+            }
+        }
+    };
+}
+
+reflect!(Vec2<f32> { x, y });
+reflect!(Vec3<f32> { x, y, z });
+reflect!(Vec4<f32> { x, y, z, w });
+
+/*
+VALUES
+ */
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Value {
+    Bool(bool),
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    F32(f32),
+    F64(f64),
+    Entity(EntityID),
+}
+
+macro_rules! impl_values {
+    ($($variant:ident : $ty:ty),*) => {
+        $(
+        unsafe impl ReflectField for $ty {
+            fn field_type() -> FieldType {
+                FieldType::$variant
+            }
+        }
+
+        impl From<$ty> for Value {
+            fn from(value: $ty) -> Self {
+                Value::$variant(value)
+            }
+        }
+        )*
+
+        impl Value {
+            pub fn field_type(&self) -> FieldType {
+                match self {
+                    $(Value::$variant(_) => { FieldType::$variant })*
+                }
+            }
+
+            // Struct fields cannot be read as a single value
+            unsafe fn read(ptr: *const u8, field_type: &FieldType) -> Option<Value> {
+                match field_type {
+                    $(FieldType::$variant => { Some(Value::$variant(ptr.cast::<$ty>().read())) })*
+                    FieldType::Struct(_) => { None }
+                }
+            }
+
+            unsafe fn write(&self, ptr: *mut u8) {
+                match self {
+                    $(Value::$variant(value) => { ptr.cast::<$ty>().write(*value) })*
+                }
+            }
+        }
+    };
+}
+
+impl_values!(Bool: bool, U8: u8, U16: u16, U32: u32, U64: u64, I8: i8, I16: i16, I32: i32, I64: i64, F32: f32, F64: f64, Entity: EntityID);
+
+impl FieldType {
+    pub fn layout(&self) -> Layout {
+        match self {
+            FieldType::Bool => { Layout::new::<bool>() }
+            FieldType::U8 => { Layout::new::<u8>() }
+            FieldType::U16 => { Layout::new::<u16>() }
+            FieldType::U32 => { Layout::new::<u32>() }
+            FieldType::U64 => { Layout::new::<u64>() }
+            FieldType::I8 => { Layout::new::<i8>() }
+            FieldType::I16 => { Layout::new::<i16>() }
+            FieldType::I32 => { Layout::new::<i32>() }
+            FieldType::I64 => { Layout::new::<i64>() }
+            FieldType::F32 => { Layout::new::<f32>() }
+            FieldType::F64 => { Layout::new::<f64>() }
+            FieldType::Entity => { Layout::new::<EntityID>() }
+            // Only accounts for reflected fields
+            FieldType::Struct(fields) => {
+                let (size, align) = fields.iter().fold((0, 1), |(size, align), field| {
+                    let layout = field.field_type.layout();
+                    (size.max(field.offset + layout.size()), align.max(layout.align()))
+                });
+                Layout::from_size_align(size, align).expect("invalid struct layout").pad_to_align()
+            }
+        }
+    }
+}
+
+// Offset and type of a field given its path, using '.' to access nested fields
+pub fn find_field<'a>(fields: &'a [FieldInfo], path: &str) -> Option<(usize, &'a FieldType)> {
+    let mut fields = fields;
+    let mut offset = 0;
+    let mut segments = path.split('.').peekable();
+    while let Some(segment) = segments.next() {
+        let field = fields.iter().find(|field| field.name == segment)?;
+        offset += field.offset;
+        if segments.peek().is_none() {
+            return Some((offset, &field.field_type));
+        }
+        match &field.field_type {
+            FieldType::Struct(nested) => { fields = nested }
+            _ => { return None; }
+        }
+    }
+    None
+}
+
+/*
+DYNAMIC COMPONENTS
+ */
+
+// Description of a component without rust type. Fields are laid out like a #[repr(C)] struct.
+pub struct DynamicComponent {
+    name: String,
+    layout: Layout,
+    fields: Vec<FieldInfo>,
+}
+
+impl DynamicComponent {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self { name: name.into(), layout: Layout::from_size_align(0, 1).expect("invalid layout"), fields: vec![] }
+    }
+
+    pub fn with_field(mut self, name: impl Into<String>, field_type: FieldType) -> Self {
+        let (layout, offset) = self.layout.extend(field_type.layout()).expect("dynamic component is too large");
+        self.layout = layout;
+        self.fields.push(FieldInfo::new(name.into(), offset, field_type));
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn layout(&self) -> Layout {
+        self.layout.pad_to_align()
+    }
+
+    pub(crate) fn into_parts(self) -> (String, Layout, Vec<FieldInfo>) {
+        let layout = self.layout();
+        (self.name, layout, self.fields)
+    }
+}
+
+/*
+ACCESS
+ */
+
+pub struct ReflectRef<'w> {
+    data: *const u8,
+    info: &'w ComponentData,
+}
+
+impl ReflectRef<'_> {
+    pub fn name(&self) -> &str {
+        &self.info.name
+    }
+
+    pub fn fields(&self) -> &[FieldInfo] {
+        &self.info.fields
+    }
+
+    pub fn get(&self, path: &str) -> Result<Value, EcsError> {
+        unsafe { read_field(self.data, self.info, path) }
+    }
+}
+
+pub struct ReflectMut<'w> {
+    data: *mut u8,
+    info: &'w ComponentData,
+}
+
+impl ReflectMut<'_> {
+    pub fn name(&self) -> &str {
+        &self.info.name
+    }
+
+    pub fn fields(&self) -> &[FieldInfo] {
+        &self.info.fields
+    }
+
+    pub fn get(&self, path: &str) -> Result<Value, EcsError> {
+        unsafe { read_field(self.data, self.info, path) }
+    }
+
+    pub fn set(&mut self, path: &str, value: impl Into<Value>) -> Result<(), EcsError> {
+        let value = value.into();
+        let (offset, field_type) = find_field(&self.info.fields, path).ok_or_else(|| EcsError::UnknownField(path.to_string()))?;
+        if *field_type != value.field_type() {
+            return Err(EcsError::FieldTypeMismatch(path.to_string()));
+        }
+        unsafe { value.write(self.data.add(offset)); }
+        Ok(())
+    }
+}
+
+unsafe fn read_field(data: *const u8, info: &ComponentData, path: &str) -> Result<Value, EcsError> {
+    let (offset, field_type) = find_field(&info.fields, path).ok_or_else(|| EcsError::UnknownField(path.to_string()))?;
+    Value::read(data.add(offset), field_type).ok_or_else(|| EcsError::FieldTypeMismatch(path.to_string()))
+}
+
+impl Ecs {
+    pub fn register_reflect<C: Reflect>(&mut self) {
+        self.components_mut().register_reflect::<C>();
+    }
+
+    pub fn register_dynamic(&mut self, component: DynamicComponent) -> Result<ComponentID, EcsError> {
+        self.components_mut().register_dynamic(component)
+    }
+
+    pub fn component_id(&self, name: &str) -> Option<ComponentID> {
+        self.components().find(name)
+    }
+
+    // Add a registered dynamic component, zero initialized. Existing data is reset.
+    // Rust components are rejected : zeroed memory is not a valid value for every type.
+    pub fn add_dynamic(&mut self, entity: EntityID, component: ComponentID) -> Result<(), EcsError> {
+        self.check_alive(entity)?;
+        let data = self.components().try_get(&component).ok_or(EcsError::UnknownComponent(component))?;
+        if !matches!(component, ComponentID::Dynamic(_)) {
+            return Err(EcsError::NotDynamic(data.name.clone()));
+        }
+        let layout = data.layout;
+        unsafe {
+            let data = if layout.size() == 0 { layout.align() as *mut u8 } else { alloc_zeroed(layout) };
+            if data.is_null() {
+                handle_alloc_error(layout);
+            }
+            self.insert_raw(entity, component, data, |_: &mut ComponentRegistry| {});
+            if layout.size() != 0 {
+                dealloc(data, layout);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn remove_dynamic(&mut self, entity: EntityID, component: ComponentID) -> Result<(), EcsError> {
+        self.check_alive(entity)?;
+        self.remove_by_id(entity, component)
+    }
+
+    pub fn reflect(&self, entity: EntityID, component: ComponentID) -> Option<ReflectRef<'_>> {
+        let (column, index) = self.component_column(entity, &component)?;
+        Some(ReflectRef { data: column.get_ptr(index), info: self.components().get(&component) })
+    }
+
+    // Mark the component as changed
+    pub fn reflect_mut(&mut self, entity: EntityID, component: ComponentID) -> Option<ReflectMut<'_>> {
+        let tick = self.change_tick();
+        let (column, index) = self.component_column_mut(entity, &component)?;
+        column.mark_changed(&index, tick);
+        let data = column.get_ptr(index);
+        Some(ReflectMut { data, info: self.components().get(&component) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use maths::vec3::Vec3;
+
+    use crate::component::ComponentID;
+    use crate::ecs::Ecs;
+    use crate::error::EcsError;
+    use crate::reflect::{DynamicComponent, FieldType, Value};
+
+    struct Body {
+        position: Vec3<f32>,
+        mass: f64,
+        sleeping: bool,
+        _cache: Vec<u32>,
+    }
+
+    reflect!(Body { position, mass, sleeping });
+
+    #[test]
+    fn reflect_test() {
+        let mut ecs = Ecs::default();
+        ecs.register_reflect::<Body>();
+        let entity = ecs.create();
+        ecs.add(entity, Body { position: Vec3::new(1.0, 2.0, 3.0), mass: 5.0, sleeping: false, _cache: vec![1] }).unwrap();
+
+        let id = ecs.component_id(std::any::type_name::<Body>()).unwrap();
+        let body = ecs.reflect(entity, id).unwrap();
+        assert_eq!(body.fields().iter().map(|field| field.name.as_ref()).collect::<Vec<_>>(), vec!["position", "mass", "sleeping"]);
+        assert_eq!(body.get("position.y"), Ok(Value::F32(2.0)));
+        assert_eq!(body.get("mass"), Ok(Value::F64(5.0)));
+        assert_eq!(body.get("position"), Err(EcsError::FieldTypeMismatch("position".to_string())));
+        assert_eq!(body.get("_cache"), Err(EcsError::UnknownField("_cache".to_string())));
+
+        let mut body = ecs.reflect_mut(entity, id).unwrap();
+        body.set("position.z", 7.0f32).unwrap();
+        body.set("sleeping", true).unwrap();
+        assert_eq!(body.set("mass", 1.0f32), Err(EcsError::FieldTypeMismatch("mass".to_string())));
+
+        let body = ecs.get::<Body>(entity).unwrap();
+        assert_eq!(body.position.z, 7.0);
+        assert!(body.sleeping);
+        assert_eq!(body.mass, 5.0);
+    }
+
+    #[test]
+    fn dynamic_component_test() {
+        let mut ecs = Ecs::default();
+        let health = ecs.register_dynamic(DynamicComponent::new("Health")
+            .with_field("alive", FieldType::Bool)
+            .with_field("current", FieldType::F32)
+            .with_field("target", FieldType::Entity)).unwrap();
+        assert_eq!(ecs.register_dynamic(DynamicComponent::new("Health")), Err(EcsError::DuplicateComponent("Health".to_string())));
+        assert_eq!(ecs.component_id("Health"), Some(health));
+
+        let e0 = ecs.create();
+        let e1 = ecs.create();
+        ecs.add(e0, 4u32).unwrap();
+        ecs.add_dynamic(e0, health).unwrap();
+        ecs.add_dynamic(e1, health).unwrap();
+        assert_eq!(ecs.components_of(e0).len(), 2);
+
+        // Zero initialized
+        assert_eq!(ecs.reflect(e1, health).unwrap().get("current"), Ok(Value::F32(0.0)));
+
+        let mut component = ecs.reflect_mut(e0, health).unwrap();
+        component.set("alive", true).unwrap();
+        component.set("current", 10.0f32).unwrap();
+        component.set("target", e1).unwrap();
+
+        // Moving the entity to another archetype keeps dynamic data
+        ecs.remove::<u32>(e0).unwrap();
+        let component = ecs.reflect(e0, health).unwrap();
+        assert_eq!(component.name(), "Health");
+        assert_eq!(component.get("alive"), Ok(Value::Bool(true)));
+        assert_eq!(component.get("current"), Ok(Value::F32(10.0)));
+        assert_eq!(component.get("target"), Ok(Value::Entity(e1)));
+        assert_eq!(ecs.reflect(e1, health).unwrap().get("alive"), Ok(Value::Bool(false)));
+
+        ecs.remove_dynamic(e0, health).unwrap();
+        assert!(ecs.reflect(e0, health).is_none());
+        assert!(matches!(ecs.remove_dynamic(e0, health), Err(EcsError::MissingComponent(_, _))));
+
+        // Rust components cannot be zero initialized, unknown ids are reported
+        ecs.add(e1, String::from("name")).unwrap();
+        assert_eq!(ecs.add_dynamic(e0, ComponentID::of::<String>()), Err(EcsError::NotDynamic(std::any::type_name::<String>().into())));
+        let unknown = ComponentID::Dynamic(42);
+        assert_eq!(ecs.add_dynamic(e0, unknown), Err(EcsError::UnknownComponent(unknown)));
+        assert!(matches!(ecs.remove_dynamic(e0, unknown), Err(EcsError::MissingComponent(_, _))));
+    }
+}
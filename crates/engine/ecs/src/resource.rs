@@ -0,0 +1,101 @@
+use std::any::{Any, type_name, TypeId};
+use std::cell::UnsafeCell;
+use std::collections::HashMap;
+use std::ops::{Deref, DerefMut};
+
+// Resources are shared between systems running on different threads
+pub trait Resource: Any + Send + Sync {}
+
+impl<T: Any + Send + Sync> Resource for T {}
+
+struct ResourceCell {
+    data: UnsafeCell<Box<dyn Any + Send + Sync>>,
+}
+
+// Access to resource cells is validated by the system scheduler
+unsafe impl Sync for ResourceCell {}
+
+#[derive(Default)]
+pub struct Resources {
+    resources: HashMap<TypeId, ResourceCell>,
+}
+
+impl Resources {
+    pub fn insert<R: Resource>(&mut self, resource: R) {
+        self.resources.insert(TypeId::of::<R>(), ResourceCell { data: UnsafeCell::new(Box::new(resource)) });
+    }
+
+    pub fn remove<R: Resource>(&mut self) -> Option<R> {
+        let cell = self.resources.remove(&TypeId::of::<R>())?;
+        Some(*cell.data.into_inner().downcast::<R>().expect("invalid resource type"))
+    }
+
+    pub fn contains<R: Resource>(&self) -> bool {
+        self.resources.contains_key(&TypeId::of::<R>())
+    }
+
+    pub fn get<R: Resource>(&self) -> Option<&R> {
+        let cell = self.resources.get(&TypeId::of::<R>())?;
+        unsafe { (*cell.data.get()).downcast_ref::<R>() }
+    }
+
+    pub fn get_mut<R: Resource>(&mut self) -> Option<&mut R> {
+        self.resources.get_mut(&TypeId::of::<R>())?.data.get_mut().downcast_mut::<R>()
+    }
+
+    /// # Safety
+    /// Caller must ensure nothing else is accessing this resource.
+    #[allow(clippy::mut_from_ref)]
+    pub unsafe fn get_unchecked_mut<R: Resource>(&self) -> Option<&mut R> {
+        let cell = self.resources.get(&TypeId::of::<R>())?;
+        (*cell.data.get()).downcast_mut::<R>()
+    }
+}
+
+/*
+SYSTEM ACCESS
+ */
+
+pub struct Res<'w, R: Resource> {
+    value: &'w R,
+}
+
+impl<'w, R: Resource> Res<'w, R> {
+    pub fn new(resources: &'w Resources) -> Self {
+        Self { value: resources.get::<R>().unwrap_or_else(|| panic!("Resource '{}' does not exist", type_name::<R>())) }
+    }
+}
+
+impl<R: Resource> Deref for Res<'_, R> {
+    type Target = R;
+
+    fn deref(&self) -> &Self::Target {
+        self.value
+    }
+}
+
+pub struct ResMut<'w, R: Resource> {
+    value: &'w mut R,
+}
+
+impl<'w, R: Resource> ResMut<'w, R> {
+    /// # Safety
+    /// Caller must ensure nothing else is accessing this resource.
+    pub unsafe fn new(resources: &'w Resources) -> Self {
+        Self { value: resources.get_unchecked_mut::<R>().unwrap_or_else(|| panic!("Resource '{}' does not exist", type_name::<R>())) }
+    }
+}
+
+impl<R: Resource> Deref for ResMut<'_, R> {
+    type Target = R;
+
+    fn deref(&self) -> &Self::Target {
+        self.value
+    }
+}
+
+impl<R: Resource> DerefMut for ResMut<'_, R> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.value
+    }
+}
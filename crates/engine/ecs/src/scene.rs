@@ -0,0 +1,694 @@
+use std::any::type_name;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+use bincode::Options;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde::de::{DeserializeOwned, DeserializeSeed, Error, MapAccess, SeqAccess, Visitor};
+use serde::ser::{SerializeMap, SerializeSeq, SerializeStruct};
+
+use crate::command::BoxedComponent;
+use crate::component::{Component, ComponentID};
+use crate::ecs::Ecs;
+use crate::entity::EntityID;
+use crate::hierarchy::{Children, Parent};
+use crate::transform::{GlobalTransform, LocalTransform};
+
+/*
+COMPONENTS
+ */
+
+// Components that can be stored in scenes. Loaded entities get new ids : references to other entities must be remapped.
+pub trait SerializeComponent: Component + Serialize + DeserializeOwned {
+    fn map_entities(&mut self, _entities: &EntityMap) {}
+}
+
+// New id of each loaded entity, indexed by its id in the scene
+#[derive(Default)]
+pub struct EntityMap {
+    entities: HashMap<EntityID, EntityID>,
+}
+
+impl EntityMap {
+    // Entities that are not part of the scene are kept as is
+    pub fn get(&self, entity: EntityID) -> EntityID {
+        self.entities.get(&entity).cloned().unwrap_or(entity)
+    }
+
+    pub(crate) fn insert(&mut self, from: EntityID, to: EntityID) {
+        self.entities.insert(from, to);
+    }
+}
+
+impl SerializeComponent for Parent {
+    fn map_entities(&mut self, entities: &EntityMap) {
+        self.0 = entities.get(self.0);
+    }
+}
+
+impl SerializeComponent for Children {
+    fn map_entities(&mut self, entities: &EntityMap) {
+        for child in &mut self.0 {
+            *child = entities.get(*child);
+        }
+    }
+}
+
+impl SerializeComponent for LocalTransform {}
+
+impl SerializeComponent for GlobalTransform {}
+
+/*
+REGISTRY
+ */
+
+type DeserializeFn = fn(&mut dyn erased_serde::Deserializer) -> Result<BoxedComponent, erased_serde::Error>;
+type MapEntitiesFn = unsafe fn(*mut u8, &EntityMap);
+// Raw pointers only : zero sized components are dangling and the first byte can be padding
+type SerializeFn = unsafe fn(*const u8) -> *const dyn erased_serde::Serialize;
+
+pub(crate) struct SceneComponent {
+    pub(crate) id: ComponentID,
+    pub(crate) name: &'static str,
+    pub(crate) serialize: SerializeFn,
+    pub(crate) deserialize: DeserializeFn,
+    map_entities: MapEntitiesFn,
+}
+
+impl SceneComponent {
+    fn new<C: SerializeComponent>(name: &'static str) -> Self {
+        Self {
+            id: ComponentID::of::<C>(),
+            name,
+            serialize: |data| data as *const C as *const dyn erased_serde::Serialize,
+            deserialize: |deserializer| Ok(BoxedComponent::new(erased_serde::deserialize::<C>(deserializer)?)),
+            map_entities: |data, entities| unsafe { (*(data as *mut C)).map_entities(entities) },
+        }
+    }
+}
+
+// Components stored in scenes, identified by name in files. Other components are ignored when saving.
+pub struct SceneRegistry {
+    components: Vec<SceneComponent>,
+    ids: HashMap<ComponentID, usize>,
+    names: HashMap<&'static str, usize>,
+}
+
+impl Default for SceneRegistry {
+    fn default() -> Self {
+        let mut registry = Self { components: vec![], ids: HashMap::new(), names: HashMap::new() };
+        registry
+            .register::<Parent>()
+            .register::<Children>()
+            .register::<LocalTransform>()
+            .register::<GlobalTransform>();
+        registry
+    }
+}
+
+impl SceneRegistry {
+    pub fn register<C: SerializeComponent>(&mut self) -> &mut Self {
+        self.register_named::<C>(type_name::<C>())
+    }
+
+    // Name should stay the same across versions to keep existing scenes loadable
+    pub fn register_named<C: SerializeComponent>(&mut self, name: &'static str) -> &mut Self {
+        let component = SceneComponent::new::<C>(name);
+        assert!(self.names.get(name).is_none_or(|index| self.components[*index].id == component.id), "Scene component name '{name}' is already used");
+        match self.ids.get(&component.id) {
+            Some(index) => {
+                self.names.remove(self.components[*index].name);
+                self.names.insert(name, *index);
+                self.components[*index] = component;
+            }
+            None => {
+                self.ids.insert(component.id, self.components.len());
+                self.names.insert(name, self.components.len());
+                self.components.push(component);
+            }
+        }
+        self
+    }
+
+    pub fn contains<C: Component>(&self) -> bool {
+        self.ids.contains_key(&ComponentID::of::<C>())
+    }
+
+    pub(crate) fn components(&self) -> &[SceneComponent] {
+        &self.components
+    }
+
+    pub(crate) fn index_of_name(&self, name: &str) -> Option<usize> {
+        self.names.get(name).cloned()
+    }
+}
+
+/*
+ERRORS
+ */
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SceneError {
+    Serialize(String),
+    Deserialize(String),
+}
+
+impl Display for SceneError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SceneError::Serialize(error) => { write!(f, "Failed to serialize scene : {error}") }
+            SceneError::Deserialize(error) => { write!(f, "Failed to deserialize scene : {error}") }
+        }
+    }
+}
+
+impl std::error::Error for SceneError {}
+
+/*
+FORMATS
+ */
+
+pub fn save_ron(ecs: &Ecs, registry: &SceneRegistry) -> Result<String, SceneError> {
+    ron::ser::to_string_pretty(&SceneSerializer::new(ecs, registry), ron::ser::PrettyConfig::default())
+        .map_err(|error| SceneError::Serialize(error.to_string()))
+}
+
+pub fn save_json(ecs: &Ecs, registry: &SceneRegistry) -> Result<String, SceneError> {
+    serde_json::to_string_pretty(&SceneSerializer::new(ecs, registry))
+        .map_err(|error| SceneError::Serialize(error.to_string()))
+}
+
+// Components are identified by their index in the registry instead of their name
+pub fn save_binary(ecs: &Ecs, registry: &SceneRegistry) -> Result<Vec<u8>, SceneError> {
+    bincode::options().serialize(&SceneSerializer::new(ecs, registry))
+        .map_err(|error| SceneError::Serialize(error.to_string()))
+}
+
+// Spawn scene entities into the world, returning their new ids
+pub fn load_ron(ecs: &mut Ecs, registry: &SceneRegistry, data: &str) -> Result<Vec<EntityID>, SceneError> {
+    let mut deserializer = ron::Deserializer::from_str(data).map_err(|error| SceneError::Deserialize(error.to_string()))?;
+    let scene = SceneSeed::new(registry).deserialize(&mut deserializer).map_err(|error| SceneError::Deserialize(error.to_string()))?;
+    Ok(scene.spawn(ecs))
+}
+
+pub fn load_json(ecs: &mut Ecs, registry: &SceneRegistry, data: &str) -> Result<Vec<EntityID>, SceneError> {
+    let mut deserializer = serde_json::Deserializer::from_str(data);
+    let scene = SceneSeed::new(registry).deserialize(&mut deserializer).map_err(|error| SceneError::Deserialize(error.to_string()))?;
+    Ok(scene.spawn(ecs))
+}
+
+pub fn load_binary(ecs: &mut Ecs, registry: &SceneRegistry, data: &[u8]) -> Result<Vec<EntityID>, SceneError> {
+    let mut deserializer = bincode::Deserializer::from_slice(data, bincode::options());
+    let scene = SceneSeed::new(registry).deserialize(&mut deserializer).map_err(|error| SceneError::Deserialize(error.to_string()))?;
+    Ok(scene.spawn(ecs))
+}
+
+/*
+SERIALIZATION
+ */
+
+// Serialize entities with their registered components. Human readable formats identify components by name,
+// binary ones by index in a component table stored at the beginning of the scene.
+pub struct SceneSerializer<'a> {
+    ecs: &'a Ecs,
+    registry: &'a SceneRegistry,
+    entities: Vec<EntityID>,
+}
+
+impl<'a> SceneSerializer<'a> {
+    pub fn new(ecs: &'a Ecs, registry: &'a SceneRegistry) -> Self {
+        Self { ecs, registry, entities: ecs.entities().collect() }
+    }
+
+    // Only serialize the given entities. Dead entities are skipped.
+    pub fn with_entities(ecs: &'a Ecs, registry: &'a SceneRegistry, entities: &[EntityID]) -> Self {
+        Self { ecs, registry, entities: entities.iter().filter(|entity| ecs.is_alive(**entity)).cloned().collect() }
+    }
+}
+
+impl Serialize for SceneSerializer<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let human_readable = serializer.is_human_readable();
+        let mut scene = serializer.serialize_struct("Scene", if human_readable { 1 } else { 2 })?;
+        if !human_readable {
+            let names: Vec<&str> = self.registry.components.iter().map(|component| component.name).collect();
+            scene.serialize_field("components", &names)?;
+        }
+        scene.serialize_field("entities", &EntitiesSerializer { scene: self, human_readable })?;
+        scene.end()
+    }
+}
+
+struct EntitiesSerializer<'a> {
+    scene: &'a SceneSerializer<'a>,
+    human_readable: bool,
+}
+
+impl Serialize for EntitiesSerializer<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut entities = serializer.serialize_seq(Some(self.scene.entities.len()))?;
+        for entity in &self.scene.entities {
+            entities.serialize_element(&EntitySerializer { scene: self.scene, entity: *entity, human_readable: self.human_readable })?;
+        }
+        entities.end()
+    }
+}
+
+struct EntitySerializer<'a> {
+    scene: &'a SceneSerializer<'a>,
+    entity: EntityID,
+    human_readable: bool,
+}
+
+impl Serialize for EntitySerializer<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut entity = serializer.serialize_struct("Entity", 2)?;
+        entity.serialize_field("id", &self.entity)?;
+        entity.serialize_field("components", &ComponentsSerializer { entity: self })?;
+        entity.end()
+    }
+}
+
+struct ComponentsSerializer<'a> {
+    entity: &'a EntitySerializer<'a>,
+}
+
+impl Serialize for ComponentsSerializer<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let EntitySerializer { scene, entity, human_readable } = self.entity;
+
+        // Registered components of this entity, in registry order
+        let mut components = vec![];
+        for (index, component) in scene.registry.components.iter().enumerate() {
+            if let Some((column, entity_index)) = scene.ecs.component_column(*entity, &component.id) {
+                components.push((index, component, column.get_ptr(entity_index)));
+            }
+        }
+
+        let mut map = serializer.serialize_map(Some(components.len()))?;
+        for (index, component, data) in components {
+            let value = unsafe { &*(component.serialize)(data) };
+            if *human_readable {
+                map.serialize_entry(component.name, value)?;
+            } else {
+                map.serialize_entry(&(index as u32), value)?;
+            }
+        }
+        map.end()
+    }
+}
+
+/*
+DESERIALIZATION
+ */
+
+pub(crate) struct LoadedComponent {
+    pub(crate) component: BoxedComponent,
+    pub(crate) map_entities: MapEntitiesFn,
+}
+
+// Deserialized scene, waiting to be spawned into a world
+pub struct Scene {
+    pub(crate) entities: Vec<(EntityID, Vec<LoadedComponent>)>,
+}
+
+impl Scene {
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    // Create an entity for each scene entity and remap references between them. Return the new ids in scene order.
+    pub fn spawn(self, ecs: &mut Ecs) -> Vec<EntityID> {
+        let mut map = EntityMap::default();
+        for (id, _) in &self.entities {
+            map.insert(*id, ecs.create());
+        }
+
+        // Hierarchy components are rebuilt from parent links, links to entities outside the scene are dropped
+        let mut parents = Vec::with_capacity(self.entities.len());
+        let mut spawned = Vec::with_capacity(self.entities.len());
+        for (id, loaded) in self.entities {
+            let entity = map.get(id);
+            let mut components = vec![];
+            for mut loaded in loaded {
+                let id = *loaded.component.id();
+                if id == ComponentID::of::<Parent>() {
+                    let parent = unsafe { &*(loaded.component.data() as *const Parent) }.get();
+                    parents.push((entity, map.entities.get(&parent).cloned()));
+                } else if id != ComponentID::of::<Children>() {
+                    unsafe { (loaded.map_entities)(loaded.component.data_mut(), &map) };
+                    components.push(loaded.component);
+                }
+            }
+            ecs.apply_changes(entity, components, vec![]).expect("spawned entity should be alive");
+            spawned.push(entity);
+        }
+
+        // Cycles can only come from a malformed scene, the link closing them is dropped too
+        for (child, parent) in parents {
+            if let Some(parent) = parent {
+                let _ = ecs.set_parent(child, parent);
+            }
+        }
+        spawned
+    }
+}
+
+pub struct SceneSeed<'a> {
+    registry: &'a SceneRegistry,
+}
+
+impl<'a> SceneSeed<'a> {
+    pub fn new(registry: &'a SceneRegistry) -> Self {
+        Self { registry }
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for SceneSeed<'_> {
+    type Value = Scene;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_struct("Scene", &["entities"], SceneVisitor { registry: self.registry })
+        } else {
+            deserializer.deserialize_struct("Scene", &["components", "entities"], SceneVisitor { registry: self.registry })
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(field_identifier, rename_all = "lowercase")]
+enum SceneField {
+    Entities,
+}
+
+#[derive(Deserialize)]
+#[serde(field_identifier, rename_all = "lowercase")]
+enum EntityField {
+    Id,
+    Components,
+}
+
+struct SceneVisitor<'a> {
+    registry: &'a SceneRegistry,
+}
+
+impl<'de> Visitor<'de> for SceneVisitor<'_> {
+    type Value = Scene;
+
+    fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        formatter.write_str("a scene")
+    }
+
+    // Binary format : component table followed by entities
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let names: Vec<String> = seq.next_element()?.ok_or_else(|| A::Error::invalid_length(0, &self))?;
+        let table = names.iter()
+            .map(|name| self.registry.names.get(name.as_str()).cloned().ok_or_else(|| A::Error::custom(format!("unknown component '{name}'"))))
+            .collect::<Result<Vec<usize>, A::Error>>()?;
+        let entities = seq.next_element_seed(EntitiesSeed { registry: self.registry, table: Some(&table) })?
+            .ok_or_else(|| A::Error::invalid_length(1, &self))?;
+        Ok(Scene { entities })
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut entities = None;
+        while let Some(SceneField::Entities) = map.next_key()? {
+            entities = Some(map.next_value_seed(EntitiesSeed { registry: self.registry, table: None })?);
+        }
+        Ok(Scene { entities: entities.ok_or_else(|| A::Error::missing_field("entities"))? })
+    }
+}
+
+// Without table, components are identified by name
+#[derive(Copy, Clone)]
+struct EntitiesSeed<'a> {
+    registry: &'a SceneRegistry,
+    table: Option<&'a [usize]>,
+}
+
+impl<'de> DeserializeSeed<'de> for EntitiesSeed<'_> {
+    type Value = Vec<(EntityID, Vec<LoadedComponent>)>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de> Visitor<'de> for EntitiesSeed<'_> {
+    type Value = Vec<(EntityID, Vec<LoadedComponent>)>;
+
+    fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        formatter.write_str("a list of entities")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut entities = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(entity) = seq.next_element_seed(EntitySeed { entities: self })? {
+            entities.push(entity);
+        }
+        Ok(entities)
+    }
+}
+
+struct EntitySeed<'a> {
+    entities: EntitiesSeed<'a>,
+}
+
+impl<'de> DeserializeSeed<'de> for EntitySeed<'_> {
+    type Value = (EntityID, Vec<LoadedComponent>);
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_struct("Entity", &["id", "components"], self)
+    }
+}
+
+impl<'de> Visitor<'de> for EntitySeed<'_> {
+    type Value = (EntityID, Vec<LoadedComponent>);
+
+    fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        formatter.write_str("an entity")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let id = seq.next_element()?.ok_or_else(|| A::Error::invalid_length(0, &self))?;
+        let components = seq.next_element_seed(ComponentsSeed { entities: self.entities })?.ok_or_else(|| A::Error::invalid_length(1, &self))?;
+        Ok((id, components))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let (mut id, mut components) = (None, None);
+        while let Some(key) = map.next_key()? {
+            match key {
+                EntityField::Id => { id = Some(map.next_value()?) }
+                EntityField::Components => { components = Some(map.next_value_seed(ComponentsSeed { entities: self.entities })?) }
+            }
+        }
+        Ok((id.ok_or_else(|| A::Error::missing_field("id"))?, components.ok_or_else(|| A::Error::missing_field("components"))?))
+    }
+}
+
+struct ComponentsSeed<'a> {
+    entities: EntitiesSeed<'a>,
+}
+
+impl<'de> DeserializeSeed<'de> for ComponentsSeed<'_> {
+    type Value = Vec<LoadedComponent>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for ComponentsSeed<'_> {
+    type Value = Vec<LoadedComponent>;
+
+    fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        formatter.write_str("a map of components")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let EntitiesSeed { registry, table } = self.entities;
+        let mut components = vec![];
+        loop {
+            let index = match table {
+                Some(table) => {
+                    let Some(index) = map.next_key::<u32>()? else { break; };
+                    *table.get(index as usize).ok_or_else(|| A::Error::custom(format!("invalid component index {index}")))?
+                }
+                None => {
+                    let Some(name) = map.next_key::<String>()? else { break; };
+                    *registry.names.get(name.as_str()).ok_or_else(|| A::Error::custom(format!("unknown component '{name}'")))?
+                }
+            };
+            let component = &registry.components[index];
+            components.push(LoadedComponent {
+                component: map.next_value_seed(ComponentSeed { component })?,
+                map_entities: component.map_entities,
+            });
+        }
+        Ok(components)
+    }
+}
+
+struct ComponentSeed<'a> {
+    component: &'a SceneComponent,
+}
+
+impl<'de> DeserializeSeed<'de> for ComponentSeed<'_> {
+    type Value = BoxedComponent;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        let mut deserializer = <dyn erased_serde::Deserializer>::erase(deserializer);
+        (self.component.deserialize)(&mut deserializer).map_err(D::Error::custom)
+    }
+}
+
+/*
+TESTS
+ */
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use crate::ecs::Ecs;
+    use crate::entity::EntityID;
+    use crate::scene::{EntityMap, load_binary, load_json, load_ron, save_binary, save_json, save_ron, SceneError, SceneRegistry, SceneSerializer, SerializeComponent};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Name(String);
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Health {
+        current: u32,
+        max: u32,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct Target(Option<EntityID>);
+
+    // Zero sized components are saved too
+    #[derive(Serialize, Deserialize)]
+    struct Hostile;
+
+    impl SerializeComponent for Name {}
+
+    impl SerializeComponent for Hostile {}
+
+    impl SerializeComponent for Health {}
+
+    impl SerializeComponent for Target {
+        fn map_entities(&mut self, entities: &EntityMap) {
+            self.0 = self.0.map(|entity| entities.get(entity));
+        }
+    }
+
+    struct NotSerialized;
+
+    fn registry() -> SceneRegistry {
+        let mut registry = SceneRegistry::default();
+        registry.register::<Name>().register::<Health>().register::<Hostile>().register_named::<Target>("Target");
+        registry
+    }
+
+    fn build_world() -> Ecs {
+        let mut ecs = Ecs::default();
+        let player = ecs.create();
+        ecs.add(player, Name("player".to_string())).unwrap();
+        ecs.add(player, Health { current: 5, max: 10 }).unwrap();
+        ecs.add(player, NotSerialized).unwrap();
+        let sword = ecs.create();
+        ecs.add(sword, Name("sword".to_string())).unwrap();
+        ecs.set_parent(sword, player).unwrap();
+        let enemy = ecs.create();
+        ecs.add(enemy, Target(Some(player))).unwrap();
+        ecs.add(enemy, Hostile).unwrap();
+        ecs.create();
+        ecs
+    }
+
+    #[test]
+    fn round_trip_test() {
+        let registry = registry();
+        let world = build_world();
+
+        let ron = save_ron(&world, &registry).unwrap();
+        let mut loaded = Ecs::default();
+        load_ron(&mut loaded, &registry, &ron).unwrap();
+        assert_eq!(save_ron(&loaded, &registry).unwrap(), ron);
+
+        let json = save_json(&world, &registry).unwrap();
+        let mut loaded = Ecs::default();
+        load_json(&mut loaded, &registry, &json).unwrap();
+        assert_eq!(save_json(&loaded, &registry).unwrap(), json);
+
+        let binary = save_binary(&world, &registry).unwrap();
+        assert!(binary.len() < json.len() / 4);
+        let mut loaded = Ecs::default();
+        load_binary(&mut loaded, &registry, &binary).unwrap();
+        assert_eq!(save_binary(&loaded, &registry).unwrap(), binary);
+        assert_eq!(save_ron(&loaded, &registry).unwrap(), ron);
+    }
+
+    #[test]
+    fn entity_remap_test() {
+        let registry = registry();
+        let binary = save_binary(&build_world(), &registry).unwrap();
+
+        // Load twice in a world that already contains entities
+        let mut ecs = build_world();
+        let first = load_binary(&mut ecs, &registry, &binary).unwrap();
+        let second = load_binary(&mut ecs, &registry, &binary).unwrap();
+        assert_eq!(ecs.entities().count(), 12);
+
+        for entities in [first, second] {
+            let (player, sword, enemy) = (entities[0], entities[1], entities[2]);
+            assert_eq!(ecs.get::<Name>(player).unwrap().0, "player");
+            assert_eq!(ecs.get::<Health>(player), Some(&Health { current: 5, max: 10 }));
+            assert!(ecs.get::<NotSerialized>(player).is_none());
+            assert_eq!(ecs.parent(sword), Some(player));
+            assert_eq!(ecs.children(player), &[sword]);
+            assert_eq!(ecs.get::<Target>(enemy).unwrap().0, Some(player));
+            assert!(ecs.get::<Hostile>(enemy).is_some());
+        }
+    }
+
+    #[test]
+    fn partial_hierarchy_test() {
+        let registry = registry();
+        let mut world = Ecs::default();
+        let player = world.create();
+        let sword = world.create();
+        let shield = world.create();
+        world.set_parent(sword, player).unwrap();
+        world.set_parent(shield, player).unwrap();
+
+        // The player is not saved : the sword and the shield lose their parent
+        let ron = ron::to_string(&SceneSerializer::with_entities(&world, &registry, &[sword, shield])).unwrap();
+        let mut ecs = Ecs::default();
+        let outside = ecs.create();
+        let entities = load_ron(&mut ecs, &registry, &ron).unwrap();
+        assert!(entities.iter().all(|entity| ecs.parent(*entity).is_none()));
+        assert!(ecs.children(outside).is_empty());
+
+        // Links inside the scene are kept
+        world.set_parent(shield, sword).unwrap();
+        let ron = ron::to_string(&SceneSerializer::with_entities(&world, &registry, &[sword, shield])).unwrap();
+        let entities = load_ron(&mut ecs, &registry, &ron).unwrap();
+        assert_eq!(ecs.parent(entities[0]), None);
+        assert_eq!(ecs.parent(entities[1]), Some(entities[0]));
+        assert_eq!(ecs.children(entities[0]), &[entities[1]]);
+    }
+
+    #[test]
+    fn unknown_component_test() {
+        let ron = save_ron(&build_world(), &registry()).unwrap();
+        let result = load_ron(&mut Ecs::default(), &SceneRegistry::default(), &ron);
+        assert!(matches!(result, Err(SceneError::Deserialize(error)) if error.contains("unknown component")));
+    }
+}
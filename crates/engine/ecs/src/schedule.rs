@@ -0,0 +1,285 @@
+use rayon::{ThreadPool, ThreadPoolBuilder};
+
+use crate::ecs::Ecs;
+use crate::system::{IntoSystem, System};
+
+// Allow sharing the world between systems running on the thread pool. Safety is ensured by stage building.
+#[derive(Copy, Clone)]
+struct WorldPtr(*const Ecs);
+
+unsafe impl Send for WorldPtr {}
+unsafe impl Sync for WorldPtr {}
+
+pub struct Schedule {
+    systems: Vec<Box<dyn System>>,
+    stages: Vec<Vec<usize>>,
+    dirty: bool,
+    thread_pool: ThreadPool,
+}
+
+impl Default for Schedule {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl Schedule {
+    // Zero threads let the thread pool pick the number of available cores
+    pub fn new(num_threads: usize) -> Self {
+        Self {
+            systems: vec![],
+            stages: vec![],
+            dirty: false,
+            thread_pool: ThreadPoolBuilder::new()
+                .num_threads(num_threads)
+                .thread_name(|index| format!("ecs worker {index}"))
+                .build()
+                .expect("failed to create ecs thread pool"),
+        }
+    }
+
+    pub fn add_system<M>(&mut self, system: impl IntoSystem<M>) -> &mut Self {
+        self.systems.push(Box::new(system.into_system()));
+        self.dirty = true;
+        self
+    }
+
+    pub fn stages(&mut self) -> &Vec<Vec<usize>> {
+        self.build_stages();
+        &self.stages
+    }
+
+    pub fn system_name(&self, index: usize) -> &str {
+        self.systems[index].name()
+    }
+
+    // Place each system in the first stage following every previously added system it conflicts with.
+    // Conflicting systems then always run in insertion order.
+    fn build_stages(&mut self) {
+        if !self.dirty {
+            return;
+        }
+        self.stages.clear();
+        let mut system_stages: Vec<usize> = Vec::with_capacity(self.systems.len());
+        for (index, system) in self.systems.iter().enumerate() {
+            let mut stage = 0;
+            for (other_index, other) in self.systems[..index].iter().enumerate() {
+                if !system.access().is_compatible(other.access()) {
+                    stage = stage.max(system_stages[other_index] + 1);
+                }
+            }
+            if stage == self.stages.len() {
+                self.stages.push(vec![]);
+            }
+            self.stages[stage].push(index);
+            system_stages.push(stage);
+        }
+        self.dirty = false;
+    }
+
+    // Advance the world tick once every system ran
+    pub fn run(&mut self, world: &mut Ecs) {
+        self.build_stages();
+        for system in &mut self.systems {
+            system.initialize(world);
+        }
+
+        let world_ptr = WorldPtr(world);
+        for (stage_index, stage) in self.stages.iter().enumerate() {
+            world.set_sync_point(stage_index as u32);
+
+            // Collect mutable references to the systems of this stage
+            let mut stage_systems: Vec<&mut Box<dyn System>> = self.systems.iter_mut()
+                .enumerate()
+                .filter(|(index, _)| stage.contains(index))
+                .map(|(_, system)| system)
+                .collect();
+
+            if stage_systems.len() == 1 {
+                unsafe { stage_systems[0].run_unsafe(world) }
+            } else {
+                self.thread_pool.scope(|scope| {
+                    for system in stage_systems {
+                        scope.spawn(move |_| {
+                            let world_ptr = world_ptr;
+                            // Systems of the same stage have compatible access
+                            unsafe { system.run_unsafe(&*world_ptr.0) }
+                        });
+                    }
+                });
+            }
+
+            // Sync point : apply structural changes recorded during this stage
+            world.set_sync_point(stage_index as u32 + 1);
+            for index in stage {
+                self.systems[*index].apply_deferred(world);
+            }
+        }
+
+        world.update_events();
+        world.increment_change_tick();
+    }
+}
+
+/*
+TESTS
+ */
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::{Duration, Instant};
+
+    use crate::ecs::Ecs;
+    use crate::entity::EntityID;
+    use crate::query::{Added, Changed, Query};
+    use crate::resource::ResMut;
+    use crate::schedule::Schedule;
+
+    struct Position(f32);
+
+    struct Velocity(f32);
+
+    struct Health(u32);
+
+    fn movement(query: Query<(&mut Position, &Velocity)>) {
+        for (position, velocity) in query {
+            position.0 += velocity.0;
+        }
+    }
+
+    fn regeneration(query: Query<&mut Health>) {
+        for health in query {
+            health.0 += 1;
+        }
+    }
+
+    fn read_position(query: Query<&Position>) {
+        for position in query {
+            assert!(position.0 > 0.0);
+        }
+    }
+
+    #[test]
+    fn stage_build_test() {
+        let mut schedule = Schedule::new(2);
+        schedule.add_system(movement);
+        schedule.add_system(regeneration);
+        schedule.add_system(read_position);
+        schedule.add_system(regeneration);
+
+        assert_eq!(schedule.stages(), &vec![vec![0, 1], vec![2, 3]]);
+    }
+
+    #[test]
+    fn schedule_run_test() {
+        let mut ecs = Ecs::default();
+        let e0 = ecs.create();
+        ecs.add(e0, Position(0.0)).unwrap();
+        ecs.add(e0, Velocity(2.0)).unwrap();
+        ecs.add(e0, Health(10)).unwrap();
+        let e1 = ecs.create();
+        ecs.add(e1, Health(5)).unwrap();
+
+        let mut schedule = Schedule::new(2);
+        schedule.add_system(movement).add_system(regeneration).add_system(read_position);
+        for _ in 0..3 {
+            schedule.run(&mut ecs);
+        }
+
+        assert_eq!(ecs.get::<Position>(e0).unwrap().0, 6.0);
+        assert_eq!(ecs.get::<Health>(e0).unwrap().0, 13);
+        assert_eq!(ecs.get::<Health>(e1).unwrap().0, 8);
+    }
+
+    #[test]
+    fn parallel_run_test() {
+        let running = Arc::new(AtomicUsize::new(0));
+        let overlapped = Arc::new(AtomicUsize::new(0));
+
+        // Both systems wait for each other : this only succeed if they run at the same time
+        let wait_other = |running: Arc<AtomicUsize>, overlapped: Arc<AtomicUsize>| {
+            move |_: Query<&Position>| {
+                running.fetch_add(1, Ordering::SeqCst);
+                let start = Instant::now();
+                while start.elapsed() < Duration::from_secs(5) {
+                    if running.load(Ordering::SeqCst) == 2 {
+                        overlapped.fetch_add(1, Ordering::SeqCst);
+                        break;
+                    }
+                }
+            }
+        };
+
+        let mut schedule = Schedule::new(2);
+        schedule.add_system(wait_other(running.clone(), overlapped.clone()));
+        schedule.add_system(wait_other(running.clone(), overlapped.clone()));
+        schedule.run(&mut Ecs::default());
+
+        assert_eq!(overlapped.load(Ordering::SeqCst), 2);
+    }
+
+    #[derive(Default)]
+    struct Uploaded(Vec<EntityID>);
+
+    #[derive(Default)]
+    struct Spawned(usize);
+
+    fn upload_positions(query: Query<EntityID, Changed<Position>>, mut uploaded: ResMut<Uploaded>) {
+        uploaded.0.extend(query);
+    }
+
+    fn count_spawned(query: Query<&Health, Added<Health>>, mut spawned: ResMut<Spawned>) {
+        spawned.0 += query.into_iter().count();
+    }
+
+    #[test]
+    fn change_detection_test() {
+        let mut ecs = Ecs::default();
+        ecs.insert_resource(Uploaded::default());
+        ecs.insert_resource(Spawned::default());
+        let e0 = ecs.create();
+        ecs.add(e0, Position(0.0)).unwrap();
+        ecs.add(e0, Velocity(1.0)).unwrap();
+        let e1 = ecs.create();
+        ecs.add(e1, Position(0.0)).unwrap();
+        ecs.add(e1, Health(1)).unwrap();
+
+        let mut schedule = Schedule::new(2);
+        schedule.add_system(upload_positions).add_system(count_spawned);
+
+        // Everything is new during the first run
+        schedule.run(&mut ecs);
+        assert_eq!(ecs.resource::<Uploaded>().unwrap().0, vec![e0, e1]);
+        assert_eq!(ecs.resource::<Spawned>().unwrap().0, 1);
+
+        // Nothing changed
+        ecs.resource_mut::<Uploaded>().unwrap().0.clear();
+        schedule.run(&mut ecs);
+        assert!(ecs.resource::<Uploaded>().unwrap().0.is_empty());
+        assert_eq!(ecs.resource::<Spawned>().unwrap().0, 1);
+
+        // Mutable access marks components as changed, moving to another archetype keeps ticks
+        ecs.get_mut::<Position>(e1).unwrap().0 = 1.0;
+        ecs.remove::<Health>(e1).unwrap();
+        let e2 = ecs.create();
+        ecs.add(e2, Health(2)).unwrap();
+        schedule.run(&mut ecs);
+        assert_eq!(ecs.resource::<Uploaded>().unwrap().0, vec![e1]);
+        assert_eq!(ecs.resource::<Spawned>().unwrap().0, 2);
+
+        // Systems writing components are detected by the following runs
+        ecs.resource_mut::<Uploaded>().unwrap().0.clear();
+        schedule.add_system(movement);
+        schedule.run(&mut ecs);
+        schedule.run(&mut ecs);
+        assert_eq!(ecs.resource::<Uploaded>().unwrap().0, vec![e0]);
+
+        // Direct queries detect changes made since the beginning of the previous run
+        assert_eq!(ecs.query_filtered::<EntityID, Changed<Position>>().into_iter().collect::<Vec<_>>(), vec![e0]);
+        ecs.get_mut::<Position>(e1).unwrap();
+        assert_eq!(ecs.query_filtered::<&mut Position, Changed<Position>>().count(), 2);
+        assert_eq!(ecs.tick(), 5);
+    }
+}
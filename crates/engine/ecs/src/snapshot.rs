@@ -0,0 +1,357 @@
+use std::collections::HashMap;
+
+use bincode::Options;
+use serde::{Deserialize, Serialize};
+
+use crate::archetype::{ArchetypeID, ArchetypeState};
+use crate::command::BoxedComponent;
+use crate::component::{Component, ComponentID};
+use crate::ecs::Ecs;
+use crate::entity::EntityID;
+use crate::error::EcsError;
+use crate::id_generator::IdGenerator;
+use crate::scene::{SceneError, SceneRegistry};
+use crate::sparse_set::SparseSets;
+
+/*
+SNAPSHOT
+ */
+
+// Copy of the entities and components of a world. Resources, events and observers are not included.
+pub struct Snapshot {
+    archetypes: Vec<ArchetypeState>,
+    sparse_sets: SparseSets,
+    locations: Vec<(ArchetypeID, usize)>,
+    ids: IdGenerator,
+}
+
+impl Snapshot {
+    pub fn is_alive(&self, entity: EntityID) -> bool {
+        self.ids.is_alive(&entity)
+    }
+
+    // Alive entities, sorted by index
+    pub fn entities(&self) -> impl Iterator<Item=EntityID> + '_ {
+        self.ids.iter_alive()
+    }
+
+    fn component_ptr(&self, entity: EntityID, component: &ComponentID) -> Option<*const u8> {
+        if let Some(set) = self.sparse_sets.get(component) {
+            return set.dense_index(entity).map(|index| set.column().get_ptr(index) as *const u8);
+        }
+        match self.locations.get(entity.index() as usize) {
+            Some((archetype_id, entity_index)) if *archetype_id != ArchetypeID::MAX && self.is_alive(entity) => {
+                self.archetypes[*archetype_id as usize].column(component).map(|column| column.get_ptr(*entity_index) as *const u8)
+            }
+            _ => { None }
+        }
+    }
+}
+
+impl Ecs {
+    // Components with drop glue need a clone function to be snapshotted
+    pub fn register_clone<C: Component + Clone>(&mut self) {
+        self.components_mut().register_clone::<C>();
+    }
+
+    pub fn snapshot(&self) -> Result<Snapshot, EcsError> {
+        let archetype_components = self.archetypes().iter().filter(|archetype| !archetype.is_empty()).flat_map(|archetype| archetype.components().iter());
+        let sparse_components = self.sparse_sets().iter().filter(|(_, set)| !set.is_empty()).map(|(id, _)| id);
+        for component in archetype_components.chain(sparse_components) {
+            let data = self.components().get(component);
+            if data.drop.is_some() && data.clone.is_none() {
+                return Err(EcsError::NotCloneable(data.name.clone()));
+            }
+        }
+        Ok(Snapshot {
+            archetypes: self.archetypes().iter().map(|archetype| archetype.save_state(self.components())).collect(),
+            sparse_sets: self.sparse_sets().duplicate(self.components(), None),
+            locations: self.entity_locations().clone(),
+            ids: self.id_generator().clone(),
+        })
+    }
+
+    // Restore a snapshot taken from this world. The world tick keeps running : restored components are reported
+    // as added and changed so systems relying on change detection process them again. Hooks are not triggered.
+    pub fn restore(&mut self, snapshot: &Snapshot) {
+        let tick = self.change_tick();
+        let (archetypes, components) = self.archetypes_and_components_mut();
+        assert!(snapshot.archetypes.len() <= archetypes.len(), "snapshot was taken from another world");
+        for (archetype_id, archetype) in archetypes.iter_mut().enumerate() {
+            match snapshot.archetypes.get(archetype_id) {
+                Some(state) => { archetype.load_state(state, components, tick) }
+                None => { archetype.clear() }
+            }
+        }
+        let sparse_sets = snapshot.sparse_sets.duplicate(components, Some(tick));
+        self.set_sparse_sets(sparse_sets);
+        self.set_entity_state(snapshot.locations.clone(), snapshot.ids.clone());
+    }
+}
+
+/*
+FRAME DIFF
+ */
+
+// Changes between two snapshots of the same world, limited to components of a scene registry
+#[derive(Default, Serialize, Deserialize)]
+pub struct FrameDiff {
+    // Entity indexes whose generation or state changed : (index, generation, alive)
+    slots: Vec<(u32, u32, bool)>,
+    free_ids: Vec<u32>,
+    // Component names, referenced by index below
+    components: Vec<String>,
+    // Added or modified components, serialized with bincode
+    changed: Vec<(EntityID, u32, Vec<u8>)>,
+    removed: Vec<(EntityID, u32)>,
+}
+
+impl FrameDiff {
+    pub fn new(registry: &SceneRegistry, from: &Snapshot, to: &Snapshot) -> Result<Self, SceneError> {
+        let mut diff = FrameDiff {
+            components: registry.components().iter().map(|component| component.name.to_string()).collect(),
+            free_ids: to.ids.free_ids().to_vec(),
+            ..Default::default()
+        };
+
+        for index in 0..from.ids.len().max(to.ids.len()) as u32 {
+            let (generation, alive) = to.ids.slot(index);
+            if from.ids.slot(index) != (generation, alive) {
+                diff.slots.push((index, generation, alive));
+            }
+        }
+
+        for entity in to.entities() {
+            for (index, component) in registry.components().iter().enumerate() {
+                let old = from.component_ptr(entity, &component.id).map(|data| Self::serialize_component(registry, index, data)).transpose()?;
+                match to.component_ptr(entity, &component.id) {
+                    Some(data) => {
+                        let new = Self::serialize_component(registry, index, data)?;
+                        if old.as_ref() != Some(&new) {
+                            diff.changed.push((entity, index as u32, new));
+                        }
+                    }
+                    None => {
+                        if old.is_some() {
+                            diff.removed.push((entity, index as u32));
+                        }
+                    }
+                }
+            }
+        }
+        Ok(diff)
+    }
+
+    // Bring a world in the 'from' state to the 'to' state
+    pub fn apply(&self, ecs: &mut Ecs, registry: &SceneRegistry) -> Result<(), SceneError> {
+        let table = self.components.iter()
+            .map(|name| registry.index_of_name(name).ok_or_else(|| SceneError::Deserialize(format!("unknown component '{name}'"))))
+            .collect::<Result<Vec<usize>, SceneError>>()?;
+        let component = |index: u32| table.get(index as usize).map(|index| &registry.components()[*index]).ok_or_else(|| SceneError::Deserialize(format!("invalid component index {index}")));
+
+        for (index, generation, alive) in &self.slots {
+            let (old_generation, old_alive) = ecs.id_generator().slot(*index);
+            if old_alive {
+                ecs.destroy(EntityID::new(*index, old_generation)).map_err(|error| SceneError::Deserialize(error.to_string()))?;
+            }
+            ecs.id_generator_mut().set_slot(*index, *generation, *alive);
+        }
+        ecs.id_generator_mut().set_free_ids(self.free_ids.clone());
+
+        for (entity, index) in &self.removed {
+            ecs.remove_by_id(*entity, component(*index)?.id).map_err(|error| SceneError::Deserialize(error.to_string()))?;
+        }
+
+        let mut inserts = HashMap::<EntityID, Vec<BoxedComponent>>::new();
+        let mut order = vec![];
+        for (entity, index, data) in &self.changed {
+            let mut deserializer = bincode::Deserializer::from_slice(data, bincode::options());
+            let value = (component(*index)?.deserialize)(&mut <dyn erased_serde::Deserializer>::erase(&mut deserializer))
+                .map_err(|error| SceneError::Deserialize(error.to_string()))?;
+            inserts.entry(*entity).or_insert_with(|| {
+                order.push(*entity);
+                vec![]
+            }).push(value);
+        }
+        for entity in order {
+            let components = inserts.remove(&entity).expect("missing inserted components");
+            ecs.apply_changes(entity, components, vec![]).map_err(|error| SceneError::Deserialize(error.to_string()))?;
+        }
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty() && self.changed.is_empty() && self.removed.is_empty()
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, SceneError> {
+        bincode::options().serialize(self).map_err(|error| SceneError::Serialize(error.to_string()))
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Result<Self, SceneError> {
+        bincode::options().deserialize(data).map_err(|error| SceneError::Deserialize(error.to_string()))
+    }
+
+    fn serialize_component(registry: &SceneRegistry, index: usize, data: *const u8) -> Result<Vec<u8>, SceneError> {
+        let value = unsafe { &*(registry.components()[index].serialize)(data) };
+        bincode::options().serialize(value).map_err(|error| SceneError::Serialize(error.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use crate::command::Commands;
+    use crate::ecs::Ecs;
+    use crate::entity::EntityID;
+    use crate::error::EcsError;
+    use crate::query::Query;
+    use crate::scene::{save_binary, SceneRegistry, SerializeComponent};
+    use crate::schedule::Schedule;
+    use crate::snapshot::FrameDiff;
+
+    #[derive(Clone, Serialize, Deserialize)]
+    struct Position(f32, f32);
+
+    #[derive(Clone, Serialize, Deserialize)]
+    struct Velocity(f32, f32);
+
+    #[derive(Clone, Serialize, Deserialize)]
+    struct Trail(Vec<f32>);
+
+    #[derive(Clone, Serialize, Deserialize)]
+    struct Lifetime(u32);
+
+    // Zero sized, its data pointer is dangling
+    #[derive(Clone, Serialize, Deserialize)]
+    struct Projectile;
+
+    impl SerializeComponent for Position {}
+
+    impl SerializeComponent for Velocity {}
+
+    impl SerializeComponent for Trail {}
+
+    impl SerializeComponent for Lifetime {}
+
+    impl SerializeComponent for Projectile {}
+
+    fn registry() -> SceneRegistry {
+        let mut registry = SceneRegistry::default();
+        registry.register::<Position>().register::<Velocity>().register::<Trail>().register::<Lifetime>().register::<Projectile>();
+        registry
+    }
+
+    fn movement(mut query: Query<(&mut Position, &Velocity, &mut Trail)>) {
+        for (position, velocity, trail) in query.iter() {
+            position.0 += velocity.0;
+            position.1 += velocity.1;
+            trail.0.push(position.0);
+        }
+    }
+
+    // Spawn a projectile from each emitter and despawn expired ones
+    fn lifetime(mut query: Query<(EntityID, &mut Lifetime, &Position)>, mut commands: Commands) {
+        for (entity, lifetime, position) in query.iter() {
+            if lifetime.0 == 0 {
+                commands.despawn(entity);
+            } else {
+                lifetime.0 -= 1;
+                if lifetime.0 % 3 == 0 {
+                    commands.spawn().insert(Position(position.0, position.1)).insert(Velocity(0.5, -1.0)).insert(Trail(vec![])).insert(Lifetime(4)).insert(Projectile);
+                }
+            }
+        }
+    }
+
+    fn build_world() -> (Ecs, Schedule) {
+        let mut ecs = Ecs::default();
+        ecs.register_clone::<Trail>();
+        for i in 0..4 {
+            let entity = ecs.create();
+            ecs.add(entity, Position(i as f32, 0.0)).unwrap();
+            ecs.add(entity, Velocity(1.0, i as f32 * 0.1)).unwrap();
+            ecs.add(entity, Trail(vec![])).unwrap();
+            ecs.add(entity, Lifetime(10 + i)).unwrap();
+        }
+        let mut schedule = Schedule::new(2);
+        schedule.add_system(movement);
+        schedule.add_system(lifetime);
+        (ecs, schedule)
+    }
+
+    fn step(ecs: &mut Ecs, schedule: &mut Schedule, frames: usize) {
+        for _ in 0..frames {
+            schedule.run(ecs);
+        }
+    }
+
+    #[test]
+    fn rollback_test() {
+        let registry = registry();
+        let (mut ecs, mut schedule) = build_world();
+        step(&mut ecs, &mut schedule, 5);
+        let snapshot = ecs.snapshot().unwrap();
+        let initial = save_binary(&ecs, &registry).unwrap();
+        let count = ecs.entities().count();
+
+        step(&mut ecs, &mut schedule, 12);
+        let expected = save_binary(&ecs, &registry).unwrap();
+        assert_ne!(ecs.entities().count(), count);
+
+        ecs.restore(&snapshot);
+        assert_eq!(ecs.entities().count(), count);
+        assert_eq!(save_binary(&ecs, &registry).unwrap(), initial);
+
+        // Replaying gives the same result, including spawned entity ids
+        step(&mut ecs, &mut schedule, 12);
+        assert_eq!(save_binary(&ecs, &registry).unwrap(), expected);
+
+        // The snapshot can be restored several times
+        ecs.restore(&snapshot);
+        assert_eq!(save_binary(&ecs, &registry).unwrap(), initial);
+    }
+
+    #[test]
+    fn not_cloneable_test() {
+        let mut ecs = Ecs::default();
+        let entity = ecs.create();
+        ecs.add(entity, String::from("name")).unwrap();
+        assert_eq!(ecs.snapshot().err(), Some(EcsError::NotCloneable(std::any::type_name::<String>().into())));
+        ecs.register_clone::<String>();
+        assert!(ecs.snapshot().is_ok());
+
+        // Built-in components are registered when their feature is first used
+        let child = ecs.create();
+        ecs.set_parent(child, entity).unwrap();
+        let snapshot = ecs.snapshot().unwrap();
+        ecs.remove_parent(child).unwrap();
+        ecs.restore(&snapshot);
+        assert_eq!(ecs.children(entity), &[child]);
+    }
+
+    #[test]
+    fn frame_diff_test() {
+        let registry = registry();
+        let (mut ecs, mut schedule) = build_world();
+        step(&mut ecs, &mut schedule, 3);
+        let from = ecs.snapshot().unwrap();
+        assert!(FrameDiff::new(&registry, &from, &ecs.snapshot().unwrap()).unwrap().is_empty());
+
+        step(&mut ecs, &mut schedule, 8);
+        let to = ecs.snapshot().unwrap();
+        let expected = save_binary(&ecs, &registry).unwrap();
+
+        let diff = FrameDiff::new(&registry, &from, &to).unwrap();
+        let diff = FrameDiff::from_bytes(&diff.to_bytes().unwrap()).unwrap();
+        ecs.restore(&from);
+        diff.apply(&mut ecs, &registry).unwrap();
+        assert_eq!(save_binary(&ecs, &registry).unwrap(), expected);
+
+        // Entities created afterward get the same ids
+        let spawned = ecs.create();
+        ecs.restore(&to);
+        assert_eq!(ecs.create(), spawned);
+    }
+}
@@ -0,0 +1,261 @@
+use std::collections::HashMap;
+
+use crate::archetype::ComponentData;
+use crate::component::{ChangeTick, ComponentID, ComponentRegistry};
+use crate::entity::EntityID;
+
+/*
+STRUCTURE
+ */
+
+// Components stored outside of archetypes : adding or removing them does not move the entity.
+// Data is packed in a dense column, the sparse array maps entity indexes to dense indexes.
+pub struct SparseSet {
+    sparse: Vec<u32>,
+    entities: Vec<EntityID>,
+    data: ComponentData,
+}
+
+impl SparseSet {
+    pub fn new(id: ComponentID, registry: &ComponentRegistry) -> SparseSet {
+        let infos = registry.get(&id);
+        Self { sparse: vec![], entities: vec![], data: ComponentData::new(id, infos.layout, infos.drop) }
+    }
+
+    pub fn dense_index(&self, entity: EntityID) -> Option<usize> {
+        let index = *self.sparse.get(entity.index() as usize)? as usize;
+        // Slots of destroyed entities may be reused : check the generation too
+        self.entities.get(index).filter(|owner| **owner == entity).map(|_| index)
+    }
+
+    pub fn contains(&self, entity: EntityID) -> bool {
+        self.dense_index(entity).is_some()
+    }
+
+    pub fn entities(&self) -> &[EntityID] {
+        &self.entities
+    }
+
+    pub fn column(&self) -> &ComponentData {
+        &self.data
+    }
+
+    pub fn column_mut(&mut self) -> &mut ComponentData {
+        &mut self.data
+    }
+
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    /// # Safety
+    /// `src` must point to a valid component of this set type. Ownership is transferred to the set.
+    pub unsafe fn insert(&mut self, entity: EntityID, src: *const u8, tick: ChangeTick) {
+        match self.dense_index(entity) {
+            Some(index) => { self.data.replace(&index, src, tick) }
+            None => {
+                let index = entity.index() as usize;
+                if index >= self.sparse.len() {
+                    self.sparse.resize(index + 1, u32::MAX);
+                }
+                self.sparse[index] = self.entities.len() as u32;
+                self.entities.push(entity);
+                self.data.push(src, tick);
+            }
+        }
+    }
+
+    // Drop the component of the entity. Return false if it had none.
+    pub fn remove(&mut self, entity: EntityID) -> bool {
+        let Some(index) = self.dense_index(entity) else { return false; };
+        self.data.drop_index(&index);
+        self.entities.swap_remove(index);
+        if let Some(swapped) = self.entities.get(index) {
+            self.sparse[swapped.index() as usize] = index as u32;
+        }
+        self.sparse[entity.index() as usize] = u32::MAX;
+        true
+    }
+
+    // Copy of the set, keeping change ticks unless a new one is given
+    pub(crate) fn duplicate(&self, registry: &ComponentRegistry, tick: Option<ChangeTick>) -> SparseSet {
+        Self {
+            sparse: self.sparse.clone(),
+            entities: self.entities.clone(),
+            data: self.data.duplicate(registry.get(self.data.id()).clone, tick),
+        }
+    }
+}
+
+/*
+REGISTRY
+ */
+
+#[derive(Default)]
+pub struct SparseSets {
+    sets: HashMap<ComponentID, SparseSet>,
+}
+
+impl SparseSets {
+    pub fn get(&self, id: &ComponentID) -> Option<&SparseSet> {
+        self.sets.get(id)
+    }
+
+    pub fn get_mut(&mut self, id: &ComponentID) -> Option<&mut SparseSet> {
+        self.sets.get_mut(id)
+    }
+
+    pub fn get_or_create(&mut self, id: &ComponentID, registry: &ComponentRegistry) -> &mut SparseSet {
+        self.sets.entry(*id).or_insert_with(|| SparseSet::new(*id, registry))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item=(&ComponentID, &SparseSet)> {
+        self.sets.iter()
+    }
+
+    // Sparse components owned by the entity
+    pub fn components_of(&self, entity: EntityID) -> impl Iterator<Item=ComponentID> + '_ {
+        self.sets.iter().filter(move |(_, set)| set.contains(entity)).map(|(id, _)| *id)
+    }
+
+    pub fn contains_entity(&self, entity: EntityID) -> bool {
+        self.sets.values().any(|set| set.contains(entity))
+    }
+
+    pub fn remove_entity(&mut self, entity: EntityID) {
+        for set in self.sets.values_mut() {
+            set.remove(entity);
+        }
+    }
+
+    pub(crate) fn duplicate(&self, registry: &ComponentRegistry, tick: Option<ChangeTick>) -> SparseSets {
+        Self { sets: self.sets.iter().map(|(id, set)| (*id, set.duplicate(registry, tick))).collect() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ecs::Ecs;
+    use crate::entity::EntityID;
+    use crate::query::{Added, Changed, Query, With, Without};
+    use crate::resource::ResMut;
+    use crate::schedule::Schedule;
+
+    #[derive(Debug, PartialEq)]
+    struct Position(f32);
+
+    #[derive(Debug, PartialEq)]
+    struct Selected(u32);
+
+    struct Hovered;
+
+    fn sorted(mut entities: Vec<EntityID>) -> Vec<EntityID> {
+        entities.sort();
+        entities
+    }
+
+    #[test]
+    fn toggle_test() {
+        let mut ecs = Ecs::default();
+        ecs.register_sparse::<Selected>();
+        ecs.register_sparse::<Hovered>();
+        let entities: Vec<EntityID> = (0..4).map(|i| {
+            let entity = ecs.create();
+            ecs.add(entity, Position(i as f32)).unwrap();
+            entity
+        }).collect();
+
+        // Toggling sparse components does not move entities
+        let location = ecs.location(entities[1]);
+        ecs.add(entities[1], Selected(1)).unwrap();
+        ecs.add(entities[2], Selected(2)).unwrap();
+        ecs.add(entities[2], Hovered).unwrap();
+        ecs.remove::<Selected>(entities[2]).unwrap();
+        ecs.add(entities[3], Selected(3)).unwrap();
+        assert_eq!(ecs.location(entities[1]), location);
+        assert_eq!(ecs.archetypes().len(), 1);
+        assert!(ecs.remove::<Selected>(entities[2]).is_err());
+
+        // Queries mix table and sparse components
+        let selected: Vec<(f32, u32)> = ecs.query::<(&Position, &Selected)>().into_iter().map(|(position, selected)| (position.0, selected.0)).collect();
+        assert_eq!(selected, vec![(1.0, 1), (3.0, 3)]);
+        let optional: Vec<Option<u32>> = ecs.query::<Option<&Selected>>().into_iter().map(|selected| selected.map(|selected| selected.0)).collect();
+        assert_eq!(optional, vec![None, Some(1), None, Some(3)]);
+        assert_eq!(ecs.query_filtered::<EntityID, With<Hovered>>().into_iter().collect::<Vec<_>>(), vec![entities[2]]);
+        assert_eq!(ecs.query_filtered::<EntityID, (Without<Selected>, Without<Hovered>)>().into_iter().collect::<Vec<_>>(), vec![entities[0]]);
+        for selected in ecs.query::<&mut Selected>() {
+            selected.0 *= 10;
+        }
+        assert_eq!(ecs.get::<Selected>(entities[3]), Some(&Selected(30)));
+        assert!(ecs.query::<&Selected>().get(entities[0]).is_err());
+
+        // Entities owning only sparse components are still found
+        let lone = ecs.create();
+        ecs.add(lone, Selected(4)).unwrap();
+        assert_eq!(sorted(ecs.query::<(EntityID, &Selected)>().into_iter().map(|(entity, _)| entity).collect()), vec![entities[1], entities[3], lone]);
+        assert_eq!(ecs.query::<&Position>().count(), 4);
+        ecs.remove::<Selected>(lone).unwrap();
+        assert!(ecs.location(lone).is_none());
+
+        // Destroyed entities lose their sparse components, even if their index is reused
+        ecs.destroy(entities[1]).unwrap();
+        let reused = ecs.create();
+        assert_eq!(reused.index(), entities[1].index());
+        assert!(!ecs.contains::<Selected>(reused));
+        assert_eq!(ecs.query::<&Selected>().count(), 1);
+        assert_eq!(ecs.components_of(entities[3]).len(), 2);
+    }
+
+    #[derive(Default)]
+    struct Seen(Vec<EntityID>, Vec<EntityID>);
+
+    fn detect(mut added: Query<EntityID, Added<Selected>>, mut changed: Query<EntityID, Changed<Selected>>, mut seen: ResMut<Seen>) {
+        seen.0 = added.iter().collect();
+        seen.1 = changed.iter().collect();
+    }
+
+    #[test]
+    fn change_detection_test() {
+        let mut ecs = Ecs::default();
+        ecs.register_sparse::<Selected>();
+        ecs.insert_resource(Seen::default());
+        let e0 = ecs.create();
+        let e1 = ecs.create();
+        ecs.add(e0, Selected(0)).unwrap();
+        ecs.add(e1, Position(0.0)).unwrap();
+
+        let mut schedule = Schedule::new(1);
+        schedule.add_system(detect);
+        schedule.run(&mut ecs);
+        assert_eq!(ecs.resource::<Seen>().unwrap().0, vec![e0]);
+
+        ecs.add(e1, Selected(1)).unwrap();
+        ecs.get_mut::<Selected>(e0).unwrap().0 = 2;
+        schedule.run(&mut ecs);
+        let seen = ecs.resource::<Seen>().unwrap();
+        assert_eq!(seen.0, vec![e1]);
+        assert_eq!(sorted(seen.1.clone()), vec![e0, e1]);
+
+        schedule.run(&mut ecs);
+        assert!(ecs.resource::<Seen>().unwrap().1.is_empty());
+    }
+
+    #[test]
+    fn snapshot_test() {
+        let mut ecs = Ecs::default();
+        ecs.register_sparse::<Selected>();
+        let e0 = ecs.create();
+        ecs.add(e0, Selected(0)).unwrap();
+        let snapshot = ecs.snapshot().unwrap();
+
+        ecs.remove::<Selected>(e0).unwrap();
+        let e1 = ecs.create();
+        ecs.add(e1, Selected(1)).unwrap();
+        ecs.restore(&snapshot);
+        assert_eq!(ecs.query::<(EntityID, &Selected)>().into_iter().collect::<Vec<_>>(), vec![(e0, &Selected(0))]);
+    }
+}
@@ -0,0 +1,265 @@
+use std::any::type_name;
+use std::marker::PhantomData;
+
+use crate::component::{ChangeTick, ChangeTicks};
+use crate::ecs::Ecs;
+use crate::event::{Event, EventReader, Events, EventWriter};
+use crate::query::{Access, Query, QueryData, QueryFilter};
+use crate::resource::{Res, ResMut, Resource};
+
+/*
+SYSTEM PARAMETERS
+ */
+
+/// # Safety
+/// `access` must declare every component and resource that `fetch` gives access to.
+pub unsafe trait SystemParam {
+    // Per system data, created once when the system is initialized
+    type State: Send + Sync + 'static;
+    type Item<'w, 's>;
+
+    fn init_state(world: &mut Ecs) -> Self::State;
+    fn access(access: &mut Access);
+
+    /// # Safety
+    /// Caller must ensure no running system has a conflicting access.
+    unsafe fn fetch<'w, 's>(state: &'s mut Self::State, world: &'w Ecs, ticks: ChangeTicks) -> Self::Item<'w, 's>;
+
+    // Apply deferred operations at the next sync point
+    fn apply(_state: &mut Self::State, _world: &mut Ecs) {}
+}
+
+unsafe impl<D: QueryData, F: QueryFilter> SystemParam for Query<'_, D, F> {
+    type State = ();
+    type Item<'w, 's> = Query<'w, D, F>;
+
+    fn init_state(_: &mut Ecs) -> Self::State {}
+
+    fn access(access: &mut Access) {
+        D::access(access);
+        F::access(access);
+    }
+
+    unsafe fn fetch<'w, 's>(_: &'s mut Self::State, world: &'w Ecs, ticks: ChangeTicks) -> Self::Item<'w, 's> {
+        Query::new_unchecked(world, ticks)
+    }
+}
+
+unsafe impl<R: Resource> SystemParam for Res<'_, R> {
+    type State = ();
+    type Item<'w, 's> = Res<'w, R>;
+
+    fn init_state(_: &mut Ecs) -> Self::State {}
+
+    fn access(access: &mut Access) {
+        access.read_resource::<R>();
+    }
+
+    unsafe fn fetch<'w, 's>(_: &'s mut Self::State, world: &'w Ecs, _: ChangeTicks) -> Self::Item<'w, 's> {
+        Res::new(world.resources())
+    }
+}
+
+unsafe impl<R: Resource> SystemParam for ResMut<'_, R> {
+    type State = ();
+    type Item<'w, 's> = ResMut<'w, R>;
+
+    fn init_state(_: &mut Ecs) -> Self::State {}
+
+    fn access(access: &mut Access) {
+        access.write_resource::<R>();
+    }
+
+    unsafe fn fetch<'w, 's>(_: &'s mut Self::State, world: &'w Ecs, _: ChangeTicks) -> Self::Item<'w, 's> {
+        ResMut::new(world.resources())
+    }
+}
+
+unsafe impl<E: Event> SystemParam for EventReader<'_, '_, E> {
+    // Number of events already read by this system
+    type State = usize;
+    type Item<'w, 's> = EventReader<'w, 's, E>;
+
+    fn init_state(world: &mut Ecs) -> Self::State {
+        world.add_event::<E>();
+        0
+    }
+
+    fn access(access: &mut Access) {
+        access.read_resource::<Events<E>>();
+    }
+
+    unsafe fn fetch<'w, 's>(state: &'s mut Self::State, world: &'w Ecs, _: ChangeTicks) -> Self::Item<'w, 's> {
+        EventReader::new(world.resources().get::<Events<E>>().expect("event is not registered"), state)
+    }
+}
+
+unsafe impl<E: Event> SystemParam for EventWriter<'_, E> {
+    type State = ();
+    type Item<'w, 's> = EventWriter<'w, E>;
+
+    fn init_state(world: &mut Ecs) -> Self::State {
+        world.add_event::<E>();
+    }
+
+    fn access(access: &mut Access) {
+        access.write_resource::<Events<E>>();
+    }
+
+    unsafe fn fetch<'w, 's>(_: &'s mut Self::State, world: &'w Ecs, _: ChangeTicks) -> Self::Item<'w, 's> {
+        EventWriter::new(world.resources().get_unchecked_mut::<Events<E>>().expect("event is not registered"))
+    }
+}
+
+/*
+SYSTEM
+ */
+
+pub trait System: Send + Sync {
+    fn name(&self) -> &str;
+    fn access(&self) -> &Access;
+
+    // Called once before the first run
+    fn initialize(&mut self, world: &mut Ecs);
+
+    /// # Safety
+    /// System must be initialized, and caller must ensure no running system has an access conflicting with `access()`.
+    unsafe fn run_unsafe(&mut self, world: &Ecs);
+
+    // Apply deferred operations recorded during the last run, like commands
+    fn apply_deferred(&mut self, world: &mut Ecs);
+
+    fn run(&mut self, world: &mut Ecs) {
+        self.initialize(world);
+        unsafe { self.run_unsafe(world) }
+        self.apply_deferred(world);
+    }
+}
+
+pub trait IntoSystem<Marker> {
+    type System: System + 'static;
+
+    fn into_system(self) -> Self::System;
+}
+
+impl<S: System + 'static> IntoSystem<()> for S {
+    type System = S;
+
+    fn into_system(self) -> Self::System {
+        self
+    }
+}
+
+pub trait SystemParamFunction<Marker>: Send + Sync + 'static {
+    type State: Send + Sync + 'static;
+
+    fn init_state(world: &mut Ecs) -> Self::State;
+    fn access(access: &mut Access);
+
+    /// # Safety
+    /// Caller must ensure no running system has a conflicting access.
+    unsafe fn run(&mut self, state: &mut Self::State, world: &Ecs, ticks: ChangeTicks);
+
+    fn apply(state: &mut Self::State, world: &mut Ecs);
+}
+
+pub struct FunctionSystem<Marker, F: SystemParamFunction<Marker>> {
+    function: F,
+    state: Option<F::State>,
+    name: &'static str,
+    access: Access,
+    // Previous run, used for change detection
+    last_run: Option<ChangeTick>,
+    _marker: PhantomData<fn() -> Marker>,
+}
+
+impl<Marker: 'static, F: SystemParamFunction<Marker>> System for FunctionSystem<Marker, F> {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn access(&self) -> &Access {
+        &self.access
+    }
+
+    fn initialize(&mut self, world: &mut Ecs) {
+        if self.state.is_none() {
+            self.state = Some(F::init_state(world));
+        }
+    }
+
+    unsafe fn run_unsafe(&mut self, world: &Ecs) {
+        let state = self.state.as_mut().unwrap_or_else(|| panic!("System '{}' is not initialized", self.name));
+        let ticks = ChangeTicks { last_run: self.last_run, this_run: world.change_tick() };
+        self.function.run(state, world, ticks);
+        self.last_run = Some(ticks.this_run);
+    }
+
+    fn apply_deferred(&mut self, world: &mut Ecs) {
+        if let Some(state) = &mut self.state {
+            F::apply(state, world);
+        }
+    }
+}
+
+impl<Marker: 'static, F: SystemParamFunction<Marker>> IntoSystem<(Marker, )> for F {
+    type System = FunctionSystem<Marker, F>;
+
+    fn into_system(self) -> Self::System {
+        let mut access = Access::default();
+        F::access(&mut access);
+        FunctionSystem {
+            function: self,
+            state: None,
+            name: type_name::<F>(),
+            access,
+            last_run: None,
+            _marker: PhantomData,
+        }
+    }
+}
+
+macro_rules! impl_system_function {
+    ($($param:ident),*) => {
+        #[allow(non_snake_case, unused_variables, clippy::unused_unit)]
+        impl<Func, $($param: SystemParam),*> SystemParamFunction<fn($($param,)*)> for Func
+            where Func: Send + Sync + 'static,
+                  for<'a> &'a mut Func: FnMut($($param),*) + FnMut($($param::Item<'_, '_>),*) {
+            type State = ($($param::State,)*);
+
+            fn init_state(world: &mut Ecs) -> Self::State {
+                ($($param::init_state(world),)*)
+            }
+
+            fn access(access: &mut Access) {
+                $($param::access(access);)*
+            }
+
+            fn apply(state: &mut Self::State, world: &mut Ecs) {
+                let ($($param,)*) = state;
+                $($param::apply($param, world);)*
+            }
+
+            unsafe fn run(&mut self, state: &mut Self::State, world: &Ecs, ticks: ChangeTicks) {
+                // Help the compiler to resolve parameters lifetimes
+                #[allow(clippy::too_many_arguments)]
+                fn call_inner<$($param),*>(mut function: impl FnMut($($param),*), $($param: $param),*) {
+                    function($($param),*)
+                }
+                let ($($param,)*) = state;
+                $(let $param = $param::fetch($param, world, ticks);)*
+                call_inner(self, $($param),*)
+            }
+        }
+    };
+}
+
+impl_system_function!();
+impl_system_function!(A);
+impl_system_function!(A, B);
+impl_system_function!(A, B, C);
+impl_system_function!(A, B, C, D);
+impl_system_function!(A, B, C, D, E);
+impl_system_function!(A, B, C, D, E, F);
+impl_system_function!(A, B, C, D, E, F, G);
+impl_system_function!(A, B, C, D, E, F, G, H);
@@ -0,0 +1,126 @@
+use maths::mat4::Mat4F32;
+use serde::{Deserialize, Serialize};
+
+use crate::entity::EntityID;
+use crate::hierarchy::{Children, Parent};
+use crate::query::{Added, Changed, Query, With, Without};
+
+/*
+COMPONENTS
+ */
+
+// Transform relative to the parent entity, or to the world for roots
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LocalTransform(pub Mat4F32);
+
+// Computed by propagate_transforms
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GlobalTransform(pub Mat4F32);
+
+impl Default for LocalTransform {
+    fn default() -> Self {
+        Self(Mat4F32::identity())
+    }
+}
+
+impl Default for GlobalTransform {
+    fn default() -> Self {
+        Self(Mat4F32::identity())
+    }
+}
+
+/*
+PROPAGATION
+ */
+
+// Compute world matrices of entities owning both a LocalTransform and a GlobalTransform, starting from roots.
+// Only subtrees where a local transform or a parent changed are written, so Changed<GlobalTransform> stays meaningful.
+// Entities whose parent has no transform are not updated.
+#[allow(clippy::too_many_arguments)]
+pub fn propagate_transforms(
+    roots: Query<EntityID, (With<GlobalTransform>, Without<Parent>)>,
+    mut locals: Query<&LocalTransform>,
+    mut globals: Query<&mut GlobalTransform>,
+    mut children: Query<&Children>,
+    mut changed_locals: Query<(), Changed<LocalTransform>>,
+    mut changed_parents: Query<(), Changed<Parent>>,
+    mut added_globals: Query<(), Added<GlobalTransform>>,
+) {
+    let mut stack: Vec<(EntityID, Mat4F32, bool)> = roots.into_iter().map(|root| (root, Mat4F32::identity(), false)).collect();
+    while let Some((entity, parent_matrix, parent_dirty)) = stack.pop() {
+        let Ok(local) = locals.get(entity) else { continue; };
+        let matrix = parent_matrix * local.0;
+
+        let dirty = parent_dirty ||
+            changed_locals.get(entity).is_ok() ||
+            changed_parents.get(entity).is_ok() ||
+            added_globals.get(entity).is_ok();
+        if dirty {
+            match globals.get(entity) {
+                Ok(global) => { global.0 = matrix }
+                Err(_) => { continue; }
+            }
+        }
+
+        if let Ok(children) = children.get(entity) {
+            stack.extend(children.iter().map(|child| (*child, matrix, dirty)));
+        }
+    }
+}
+
+/*
+TESTS
+ */
+
+#[cfg(test)]
+mod tests {
+    use maths::mat4::Mat4F32;
+
+    use crate::ecs::Ecs;
+    use crate::entity::EntityID;
+    use crate::query::Changed;
+    use crate::schedule::Schedule;
+    use crate::transform::{GlobalTransform, LocalTransform, propagate_transforms};
+
+    fn translation(x: f32, y: f32, z: f32) -> Mat4F32 {
+        Mat4F32::from_cols([[1.0, 0.0, 0.0, 0.0], [0.0, 1.0, 0.0, 0.0], [0.0, 0.0, 1.0, 0.0], [x, y, z, 1.0]])
+    }
+
+    fn spawn(ecs: &mut Ecs, x: f32) -> EntityID {
+        let entity = ecs.create();
+        ecs.add(entity, LocalTransform(translation(x, 0.0, 0.0))).unwrap();
+        ecs.add(entity, GlobalTransform::default()).unwrap();
+        entity
+    }
+
+    #[test]
+    fn propagation_test() {
+        let mut ecs = Ecs::default();
+        let root = spawn(&mut ecs, 1.0);
+        let child = spawn(&mut ecs, 2.0);
+        let grand_child = spawn(&mut ecs, 3.0);
+        ecs.set_parent(child, root).unwrap();
+        ecs.set_parent(grand_child, child).unwrap();
+
+        let mut schedule = Schedule::new(1);
+        schedule.add_system(propagate_transforms);
+        schedule.run(&mut ecs);
+        assert_eq!(ecs.get::<GlobalTransform>(grand_child).unwrap().0, translation(6.0, 0.0, 0.0));
+        assert_eq!(ecs.get::<GlobalTransform>(child).unwrap().0, translation(3.0, 0.0, 0.0));
+
+        // Only the modified subtree is written
+        schedule.run(&mut ecs);
+        ecs.get_mut::<LocalTransform>(child).unwrap().0 = translation(5.0, 0.0, 0.0);
+        schedule.run(&mut ecs);
+        let mut changed: Vec<EntityID> = ecs.query_filtered::<EntityID, Changed<GlobalTransform>>().into_iter().collect();
+        changed.sort();
+        assert_eq!(changed, vec![child, grand_child]);
+        assert_eq!(ecs.get::<GlobalTransform>(grand_child).unwrap().0, translation(9.0, 0.0, 0.0));
+
+        // Orphans are relative to the world
+        ecs.remove_parent(child).unwrap();
+        schedule.run(&mut ecs);
+        assert_eq!(ecs.get::<GlobalTransform>(child).unwrap().0, translation(5.0, 0.0, 0.0));
+        assert_eq!(ecs.get::<GlobalTransform>(grand_child).unwrap().0, translation(8.0, 0.0, 0.0));
+    }
+}
@@ -20,8 +20,8 @@ use gfx::shader_instance::{BindPoint, ShaderInstance};
 use gfx::surface::GfxSurface;
 use gfx::types::{ClearValues, PixelFormat, Scissors};
 use imgui_bindings::{igCreateContext, igEndFrame, igGetDrawData, igGetIO, igGetMainViewport, igGetStyle, igNewFrame, igRender, igShowDemoWindow, igStyleColorsDark, ImDrawIdx, ImDrawVert, ImFontAtlas_GetTexDataAsRGBA32, ImGuiBackendFlags__ImGuiBackendFlags_HasMouseCursors, ImGuiBackendFlags__ImGuiBackendFlags_HasSetMousePos, ImGuiBackendFlags__ImGuiBackendFlags_PlatformHasViewports, ImGuiConfigFlags__ImGuiConfigFlags_DockingEnable, ImGuiConfigFlags__ImGuiConfigFlags_NavEnableGamepad, ImGuiConfigFlags__ImGuiConfigFlags_NavEnableKeyboard, ImGuiConfigFlags__ImGuiConfigFlags_ViewportsEnable, ImGuiContext, ImTextureID, ImVec2, ImVec4};
+use maths::color::LinearRgba;
 use maths::vec2::Vec2f32;
-use maths::vec4::Vec4F32;
 use plateform::input_system::{InputMapping, MouseButton};
 use shader_compiler::backends::backend_shaderc::{BackendShaderC, ShaderCIncluder};
 use shader_compiler::CompilerBackend;
@@ -115,7 +115,7 @@ impl ImGUiContext {
             pass_id: PassID::new("imgui_render_pass"),
             color_attachments: vec![RenderPassAttachment {
                 name: "color".to_string(),
-                clear_value: ClearValues::Color(Vec4F32::new(0.0, 0.0, 0.0, 1.0)),
+                clear_value: ClearValues::Color(LinearRgba::BLACK),
                 image_format: PixelFormat::R8G8B8A8_UNORM,
             }],
             depth_attachment: Some(RenderPassAttachment {
@@ -1,9 +1,12 @@
-﻿extern crate proc_macro;
+﻿// Derives are written with explicit matches on the item kind
+#![allow(clippy::single_match, clippy::useless_format)]
+
+extern crate proc_macro;
 
 use proc_macro::TokenStream;
 use std::str::FromStr;
 
-use quote::quote;
+use quote::{format_ident, quote};
 use syn::Data::{Enum, Struct};
 use syn::DeriveInput;
 use syn::parse_macro_input;
@@ -168,6 +171,80 @@ pub fn derive_operator_div(input: TokenStream) -> TokenStream {
     expanded.into()
 }
 
+#[proc_macro_derive(OpsNeg)]
+pub fn derive_operator_neg(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+    let type_name = ast.ident;
+
+    let mut expanded = quote! {};
+
+    if let Struct(data_struct) = ast.data {
+        let field_vector: Vec<&Option<syn::Ident>> = data_struct.fields.iter().map(|variant| &variant.ident).collect();
+
+        expanded.extend(quote! {
+            impl<T: Default + ops::Neg<Output=T>> ops::Neg for #type_name<T> {
+                type Output = #type_name<T>;
+                fn neg(self) -> Self::Output {
+                    Self::Output
+                    {
+                        #(#field_vector: -self.#field_vector,)*
+                    }
+                }
+            }
+        });
+    }
+
+    expanded.into()
+}
+
+// Accessors for every combination of 2 to 4 fields, like xy() or zyx(). Vec2, Vec3 and Vec4 must be in scope.
+#[proc_macro_derive(Swizzle)]
+pub fn derive_swizzle(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+    let type_name = ast.ident;
+
+    let mut expanded = quote! {};
+
+    if let Struct(data_struct) = ast.data {
+        let fields: Vec<syn::Ident> = data_struct.fields.iter().filter_map(|variant| variant.ident.clone()).collect();
+
+        let mut combinations: Vec<Vec<&syn::Ident>> = vec![vec![]];
+        let mut methods = vec![];
+        for size in 1..=4usize {
+            combinations = combinations.iter().flat_map(|combination| fields.iter().map(move |field| {
+                let mut combination = combination.clone();
+                combination.push(field);
+                combination
+            })).collect();
+            if size == 1 {
+                continue;
+            }
+
+            let target = format_ident!("Vec{}", size);
+            for combination in &combinations {
+                // The same fields in the same order : just a copy
+                if combination.len() == fields.len() && combination.iter().zip(&fields).all(|(a, b)| *a == b) {
+                    continue;
+                }
+                let name = format_ident!("{}", combination.iter().map(|field| field.to_string()).collect::<String>());
+                methods.push(quote! {
+                    pub fn #name(&self) -> #target<T> {
+                        #target::new(#(self.#combination,)*)
+                    }
+                });
+            }
+        }
+
+        expanded.extend(quote! {
+            impl<T: Default + Copy> #type_name<T> {
+                #(#methods)*
+            }
+        });
+    }
+
+    expanded.into()
+}
+
 #[proc_macro_derive(DefaultConstruct)]
 pub fn derive_default_construct(input: TokenStream) -> TokenStream {
     let ast = parse_macro_input!(input as DeriveInput);
@@ -179,7 +256,8 @@ pub fn derive_default_construct(input: TokenStream) -> TokenStream {
             let field_vector: Vec<&Option<syn::Ident>> = data_struct.fields.iter().map(|variant| &variant.ident).collect();
 
             expanded.extend(quote! {
-                impl<T: Default> #type_name<T> {                      
+                impl<T: Default> #type_name<T> {
+                    #[allow(clippy::too_many_arguments)]
                     pub fn new(#(#field_vector: T,)*) -> Self {
                         Self
                         {
@@ -0,0 +1,72 @@
+use criterion::{BenchmarkId, black_box, Criterion, criterion_group, criterion_main};
+
+use maths::mat4::Mat4F32;
+use maths::quat::QuatF32;
+use maths::simd::{is_accelerated, Mat4A, QuatA};
+use maths::vec3::{Vec3, Vec3F32};
+use maths::vec4::{Vec4, Vec4F32};
+
+const POINT_COUNT: usize = 100_000;
+
+fn points() -> Vec<Vec4F32> {
+    (0..POINT_COUNT).map(|i| {
+        let i = i as f32;
+        Vec4::new(i.sin() * 10.0, i.cos() * 10.0, i * 0.001, 1.0)
+    }).collect()
+}
+
+fn matrix() -> Mat4F32 {
+    Mat4F32::perspective(1.2, 16.0 / 9.0, 0.1, 100.0) * Mat4F32::look_at(Vec3::new(5.0, 3.0, 5.0), Vec3::splat(0.0), Vec3::new(0.0, 1.0, 0.0))
+}
+
+// Operators of the generic types use the simd types when accelerated : suffix the names so that results of both
+// backends are not mixed up
+fn name(variant: &str) -> String {
+    format!("{variant}_{}", if is_accelerated() { "simd" } else { "scalar" })
+}
+
+fn transform_points(c: &mut Criterion) {
+    let mut group = c.benchmark_group("transform_points");
+    let (m, points) = (matrix(), points());
+    let mut out = vec![Vec4::splat(0.0); POINT_COUNT];
+
+    group.bench_function(BenchmarkId::from_parameter(name("operator")), |b| {
+        b.iter(|| {
+            for (point, out) in points.iter().zip(out.iter_mut()) {
+                *out = black_box(m) * *point;
+            }
+        })
+    });
+    group.bench_function(BenchmarkId::from_parameter(name("aligned")), |b| {
+        let m = Mat4A::from(m);
+        b.iter(|| black_box(m).transform_points(&points, &mut out))
+    });
+    group.finish();
+}
+
+fn rotate_points(c: &mut Criterion) {
+    let mut group = c.benchmark_group("rotate_points");
+    let q = QuatF32::from_euler(0.3, 1.1, -0.4);
+    let points: Vec<Vec3F32> = points().into_iter().map(Vec3::from).collect();
+    let mut out = vec![Vec3::splat(0.0); POINT_COUNT];
+
+    group.bench_function(BenchmarkId::from_parameter(name("operator")), |b| {
+        b.iter(|| {
+            for (point, out) in points.iter().zip(out.iter_mut()) {
+                *out = black_box(q) * *point;
+            }
+        })
+    });
+    group.bench_function(BenchmarkId::from_parameter(name("aligned")), |b| {
+        let q = QuatA::from(q);
+        b.iter(|| {
+            for (point, out) in points.iter().zip(out.iter_mut()) {
+                *out = black_box(q) * *point;
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, transform_points, rotate_points);
+criterion_main!(benches);
@@ -0,0 +1,479 @@
+use crate::mat3::{Mat3, Mat3F32, Mat3F64};
+use crate::scalar::Float;
+use crate::vec3::Vec3;
+use crate::vec4::{Vec4, Vec4F32};
+
+/*
+TRANSFER FUNCTIONS
+ */
+
+// Curve between linear light and the encoded signal stored in images or sent to the display
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TransferFunction {
+    Linear,
+    // Also used by Display P3. Mirrored for negative values, as in extended sRGB.
+    Srgb,
+    Bt709,
+    // Pure 2.2 gamma (563 / 256)
+    AdobeRgb,
+    // Pure 2.6 gamma
+    DciP3,
+    // SMPTE ST 2084 (HDR10). Linear values are absolute : 1.0 is 10000 nits.
+    Pq,
+    // Hybrid log-gamma (BT.2100). Linear values are relative scene light in [0, 1].
+    Hlg,
+}
+
+impl TransferFunction {
+    // Linear light to signal
+    pub fn encode<T: Float>(&self, linear: T) -> T {
+        match self {
+            TransferFunction::Linear => { linear }
+            TransferFunction::Srgb => {
+                mirrored(linear, |x| {
+                    if x <= T::from_f64(0.0031308) {
+                        x * T::from_f64(12.92)
+                    } else {
+                        T::from_f64(1.055) * x.powf(T::one() / T::from_f64(2.4)) - T::from_f64(0.055)
+                    }
+                })
+            }
+            TransferFunction::Bt709 => {
+                mirrored(linear, |x| {
+                    if x < T::from_f64(0.018) {
+                        x * T::from_f64(4.5)
+                    } else {
+                        T::from_f64(1.099) * x.powf(T::from_f64(0.45)) - T::from_f64(0.099)
+                    }
+                })
+            }
+            TransferFunction::AdobeRgb => { mirrored(linear, |x| x.powf(T::from_f64(256.0 / 563.0))) }
+            TransferFunction::DciP3 => { mirrored(linear, |x| x.powf(T::one() / T::from_f64(2.6))) }
+            TransferFunction::Pq => {
+                let y = positive(linear).powf(T::from_f64(PQ_M1));
+                ((T::from_f64(PQ_C1) + T::from_f64(PQ_C2) * y) / (T::one() + T::from_f64(PQ_C3) * y)).powf(T::from_f64(PQ_M2))
+            }
+            TransferFunction::Hlg => {
+                let x = positive(linear);
+                if x <= T::one() / T::from(12) {
+                    (T::from(3) * x).sqrt()
+                } else {
+                    T::from_f64(HLG_A) * (T::from(12) * x - T::from_f64(HLG_B)).ln() + T::from_f64(HLG_C)
+                }
+            }
+        }
+    }
+
+    // Signal to linear light
+    pub fn decode<T: Float>(&self, encoded: T) -> T {
+        match self {
+            TransferFunction::Linear => { encoded }
+            TransferFunction::Srgb => {
+                mirrored(encoded, |x| {
+                    if x <= T::from_f64(0.04045) {
+                        x / T::from_f64(12.92)
+                    } else {
+                        ((x + T::from_f64(0.055)) / T::from_f64(1.055)).powf(T::from_f64(2.4))
+                    }
+                })
+            }
+            TransferFunction::Bt709 => {
+                mirrored(encoded, |x| {
+                    if x < T::from_f64(0.081) {
+                        x / T::from_f64(4.5)
+                    } else {
+                        ((x + T::from_f64(0.099)) / T::from_f64(1.099)).powf(T::one() / T::from_f64(0.45))
+                    }
+                })
+            }
+            TransferFunction::AdobeRgb => { mirrored(encoded, |x| x.powf(T::from_f64(563.0 / 256.0))) }
+            TransferFunction::DciP3 => { mirrored(encoded, |x| x.powf(T::from_f64(2.6))) }
+            TransferFunction::Pq => {
+                let e = positive(encoded).powf(T::one() / T::from_f64(PQ_M2));
+                (positive(e - T::from_f64(PQ_C1)) / (T::from_f64(PQ_C2) - T::from_f64(PQ_C3) * e)).powf(T::one() / T::from_f64(PQ_M1))
+            }
+            TransferFunction::Hlg => {
+                let x = positive(encoded);
+                if x <= T::from_f64(0.5) {
+                    x * x / T::from(3)
+                } else {
+                    (((x - T::from_f64(HLG_C)) / T::from_f64(HLG_A)).exp() + T::from_f64(HLG_B)) / T::from(12)
+                }
+            }
+        }
+    }
+}
+
+const PQ_M1: f64 = 2610.0 / 16384.0;
+const PQ_M2: f64 = 2523.0 / 4096.0 * 128.0;
+const PQ_C1: f64 = 3424.0 / 4096.0;
+const PQ_C2: f64 = 2413.0 / 4096.0 * 32.0;
+const PQ_C3: f64 = 2392.0 / 4096.0 * 32.0;
+
+const HLG_A: f64 = 0.17883277;
+const HLG_B: f64 = 0.28466892;
+const HLG_C: f64 = 0.55991073;
+
+fn mirrored<T: Float>(value: T, curve: impl Fn(T) -> T) -> T {
+    if value < T::zero() { -curve(-value) } else { curve(value) }
+}
+
+fn positive<T: Float>(value: T) -> T {
+    if value < T::zero() { T::zero() } else { value }
+}
+
+/*
+PRIMARIES
+ */
+
+// Chromaticities of the red, green and blue primaries and of the white point
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Primaries {
+    // Shared by sRGB
+    Bt709,
+    DisplayP3,
+    // P3 primaries with the DCI white point
+    DciP3,
+    Bt2020,
+    AdobeRgb,
+}
+
+const D65: [f64; 2] = [0.3127, 0.3290];
+
+impl Primaries {
+    // Red, green, blue and white xy chromaticities
+    fn chromaticities(&self) -> [[f64; 2]; 4] {
+        match self {
+            Primaries::Bt709 => { [[0.64, 0.33], [0.30, 0.60], [0.15, 0.06], D65] }
+            Primaries::DisplayP3 => { [[0.680, 0.320], [0.265, 0.690], [0.150, 0.060], D65] }
+            Primaries::DciP3 => { [[0.680, 0.320], [0.265, 0.690], [0.150, 0.060], [0.314, 0.351]] }
+            Primaries::Bt2020 => { [[0.708, 0.292], [0.170, 0.797], [0.131, 0.046], D65] }
+            Primaries::AdobeRgb => { [[0.64, 0.33], [0.21, 0.71], [0.15, 0.06], D65] }
+        }
+    }
+
+    // Linear RGB to CIE XYZ
+    pub fn to_xyz(&self) -> Mat3F64 {
+        let xyz = |[x, y]: [f64; 2]| Vec3::new(x / y, 1.0, (1.0 - x - y) / y);
+        let [red, green, blue, white] = self.chromaticities().map(xyz);
+        let primaries = Mat3::from_cols([red.to_array(), green.to_array(), blue.to_array()]);
+        // Scale the primaries so that they add up to the white point
+        let scale = primaries.inverse().expect("primaries are independent") * white;
+        primaries * Mat3::from_diagonal(scale)
+    }
+
+    // Linear RGB in these primaries to linear RGB in the target ones. The white point is not adapted.
+    pub fn conversion(&self, target: Primaries) -> Mat3F32 {
+        let matrix = target.to_xyz().inverse().expect("primaries are independent") * self.to_xyz();
+        Mat3::from_cols(matrix.to_cols().map(|col| col.map(|value| value as f32)))
+    }
+}
+
+/*
+COLORS
+ */
+
+// Linear light with BT.709 (sRGB) primaries : the space lighting and blending happen in
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LinearRgba {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+// sRGB encoded, as in 8 bit textures and color pickers
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Srgba {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+// Hue in degrees, saturation and value in [0, 1], over sRGB encoded values
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Hsv {
+    pub h: f32,
+    pub s: f32,
+    pub v: f32,
+    pub a: f32,
+}
+
+// Perceptual space : even steps look even, good for gradients. See https://bottosson.github.io/posts/oklab
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Oklab {
+    pub l: f32,
+    pub a: f32,
+    pub b: f32,
+    pub alpha: f32,
+}
+
+impl LinearRgba {
+    pub const BLACK: Self = Self::new(0.0, 0.0, 0.0, 1.0);
+    pub const WHITE: Self = Self::new(1.0, 1.0, 1.0, 1.0);
+    pub const TRANSPARENT: Self = Self::new(0.0, 0.0, 0.0, 0.0);
+
+    pub const fn new(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Self { r, g, b, a }
+    }
+
+    pub const fn rgb(r: f32, g: f32, b: f32) -> Self {
+        Self::new(r, g, b, 1.0)
+    }
+
+    pub fn to_array(&self) -> [f32; 4] {
+        [self.r, self.g, self.b, self.a]
+    }
+
+    // Relative luminance (Y)
+    pub fn luminance(&self) -> f32 {
+        0.2126 * self.r + 0.7152 * self.g + 0.0722 * self.b
+    }
+
+    pub fn lerp(&self, other: Self, t: f32) -> Self {
+        let mix = |a: f32, b: f32| a + (b - a) * t;
+        Self::new(mix(self.r, other.r), mix(self.g, other.g), mix(self.b, other.b), mix(self.a, other.a))
+    }
+
+    pub fn premultiply(&self) -> Self {
+        Self::new(self.r * self.a, self.g * self.a, self.b * self.a, self.a)
+    }
+
+    // Fully transparent colors have lost their color : they become transparent black
+    pub fn unpremultiply(&self) -> Self {
+        if self.a == 0.0 {
+            return Self::TRANSPARENT;
+        }
+        Self::new(self.r / self.a, self.g / self.a, self.b / self.a, self.a)
+    }
+
+    // Porter-Duff over, both colors being premultiplied
+    pub fn over(&self, background: Self) -> Self {
+        let remaining = 1.0 - self.a;
+        Self::new(
+            self.r + background.r * remaining,
+            self.g + background.g * remaining,
+            self.b + background.b * remaining,
+            self.a + background.a * remaining)
+    }
+
+    // Same color with other primaries, e.g. for a wide gamut or HDR swapchain. Alpha is kept.
+    pub fn to_primaries(&self, target: Primaries) -> Self {
+        let rgb = Primaries::Bt709.conversion(target) * Vec3::new(self.r, self.g, self.b);
+        Self::new(rgb.x, rgb.y, rgb.z, self.a)
+    }
+}
+
+impl Srgba {
+    pub const fn new(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Self { r, g, b, a }
+    }
+
+    pub const fn rgb(r: f32, g: f32, b: f32) -> Self {
+        Self::new(r, g, b, 1.0)
+    }
+
+    pub fn from_rgba8(rgba: [u8; 4]) -> Self {
+        let [r, g, b, a] = rgba.map(|value| value as f32 / 255.0);
+        Self::new(r, g, b, a)
+    }
+
+    // Components are clamped to [0, 1]
+    pub fn to_rgba8(&self) -> [u8; 4] {
+        [self.r, self.g, self.b, self.a].map(|value| (value.clamp(0.0, 1.0) * 255.0).round() as u8)
+    }
+}
+
+impl From<Srgba> for LinearRgba {
+    fn from(color: Srgba) -> Self {
+        let decode = |value| TransferFunction::Srgb.decode(value);
+        Self::new(decode(color.r), decode(color.g), decode(color.b), color.a)
+    }
+}
+
+impl From<LinearRgba> for Srgba {
+    fn from(color: LinearRgba) -> Self {
+        let encode = |value| TransferFunction::Srgb.encode(value);
+        Self::new(encode(color.r), encode(color.g), encode(color.b), color.a)
+    }
+}
+
+impl From<Hsv> for Srgba {
+    fn from(color: Hsv) -> Self {
+        let hue = color.h.rem_euclid(360.0) / 60.0;
+        let chroma = color.v * color.s;
+        let second = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+        let (r, g, b) = match hue as u32 {
+            0 => { (chroma, second, 0.0) }
+            1 => { (second, chroma, 0.0) }
+            2 => { (0.0, chroma, second) }
+            3 => { (0.0, second, chroma) }
+            4 => { (second, 0.0, chroma) }
+            _ => { (chroma, 0.0, second) }
+        };
+        let offset = color.v - chroma;
+        Self::new(r + offset, g + offset, b + offset, color.a)
+    }
+}
+
+impl From<Srgba> for Hsv {
+    fn from(color: Srgba) -> Self {
+        let max = color.r.max(color.g).max(color.b);
+        let chroma = max - color.r.min(color.g).min(color.b);
+        let hue = if chroma == 0.0 {
+            0.0
+        } else if max == color.r {
+            ((color.g - color.b) / chroma).rem_euclid(6.0)
+        } else if max == color.g {
+            (color.b - color.r) / chroma + 2.0
+        } else {
+            (color.r - color.g) / chroma + 4.0
+        };
+        let saturation = if max == 0.0 { 0.0 } else { chroma / max };
+        Self { h: hue * 60.0, s: saturation, v: max, a: color.a }
+    }
+}
+
+impl From<Hsv> for LinearRgba {
+    fn from(color: Hsv) -> Self {
+        Srgba::from(color).into()
+    }
+}
+
+impl From<LinearRgba> for Hsv {
+    fn from(color: LinearRgba) -> Self {
+        Srgba::from(color).into()
+    }
+}
+
+impl From<LinearRgba> for Oklab {
+    fn from(color: LinearRgba) -> Self {
+        // Reference coefficients are given with more precision than f32
+        let (r, g, b) = (color.r as f64, color.g as f64, color.b as f64);
+        let l = (0.4122214708 * r + 0.5363325363 * g + 0.0514459929 * b).cbrt();
+        let m = (0.2119034982 * r + 0.6806995451 * g + 0.1073969566 * b).cbrt();
+        let s = (0.0883024619 * r + 0.2817188376 * g + 0.6299787005 * b).cbrt();
+        Self {
+            l: (0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s) as f32,
+            a: (1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s) as f32,
+            b: (0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s) as f32,
+            alpha: color.a,
+        }
+    }
+}
+
+impl From<Oklab> for LinearRgba {
+    fn from(color: Oklab) -> Self {
+        let (lightness, a, b) = (color.l as f64, color.a as f64, color.b as f64);
+        let l = lightness + 0.3963377774 * a + 0.2158037573 * b;
+        let m = lightness - 0.1055613458 * a - 0.0638541728 * b;
+        let s = lightness - 0.0894841775 * a - 1.2914855480 * b;
+        let (l, m, s) = (l * l * l, m * m * m, s * s * s);
+        Self::new(
+            (4.0767416621 * l - 3.3077115913 * m + 0.2309699292 * s) as f32,
+            (-1.2684380046 * l + 2.6097574011 * m - 0.3413193965 * s) as f32,
+            (-0.0041960863 * l - 0.7034186147 * m + 1.7076147010 * s) as f32,
+            color.alpha)
+    }
+}
+
+impl From<LinearRgba> for Vec4F32 {
+    fn from(color: LinearRgba) -> Self {
+        Vec4::new(color.r, color.g, color.b, color.a)
+    }
+}
+
+impl From<Vec4F32> for LinearRgba {
+    fn from(color: Vec4F32) -> Self {
+        Self::new(color.x, color.y, color.z, color.w)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::color::{Hsv, LinearRgba, Oklab, Primaries, Srgba, TransferFunction};
+    use crate::vec3::Vec3;
+
+    fn assert_near(a: f32, b: f32) {
+        assert!((a - b).abs() < 1e-4, "{a} != {b}");
+    }
+
+    #[test]
+    fn transfer_test() {
+        let functions = [
+            TransferFunction::Linear, TransferFunction::Srgb, TransferFunction::Bt709, TransferFunction::AdobeRgb,
+            TransferFunction::DciP3, TransferFunction::Pq, TransferFunction::Hlg,
+        ];
+        for function in functions {
+            for value in [0.0f64, 0.001, 0.01, 0.05, 0.18, 0.5, 1.0] {
+                let encoded = function.encode(value);
+                assert!((function.decode(encoded) - value).abs() < 1e-6, "{function:?} {value}");
+            }
+            assert!((function.encode(1.0f64) - 1.0).abs() < 1e-6, "{function:?}");
+        }
+        assert_near(TransferFunction::Srgb.encode(0.5), 0.735357);
+        assert_near(TransferFunction::Srgb.encode(-0.5), -0.735357);
+        // 100 nits
+        assert_near(TransferFunction::Pq.encode(0.01), 0.508078);
+        assert_near(TransferFunction::Hlg.encode(1.0 / 12.0), 0.5);
+    }
+
+    #[test]
+    fn primaries_test() {
+        let xyz = Primaries::Bt709.to_xyz();
+        assert!((xyz.x2 - 0.2126).abs() < 1e-4 && (xyz.y2 - 0.7152).abs() < 1e-4 && (xyz.z2 - 0.0722).abs() < 1e-4);
+
+        let to_bt2020 = Primaries::Bt709.conversion(Primaries::Bt2020);
+        let red = to_bt2020 * Vec3::new(1.0, 0.0, 0.0);
+        assert_near(red.x, 0.6274);
+        assert_near(red.y, 0.0691);
+        assert_near(red.z, 0.0164);
+        // Same white point : white stays white
+        let white = LinearRgba::WHITE.to_primaries(Primaries::DisplayP3);
+        assert_near(white.r, 1.0);
+        assert_near(white.g, 1.0);
+        assert_near(white.b, 1.0);
+    }
+
+    #[test]
+    fn color_test() {
+        let color = Srgba::from_rgba8([255, 128, 0, 255]);
+        let linear = LinearRgba::from(color);
+        assert_near(linear.g, 0.215861);
+        assert_eq!(Srgba::from(linear).to_rgba8(), [255, 128, 0, 255]);
+
+        let hsv = Hsv::from(color);
+        assert_near(hsv.h, 30.117647);
+        assert_near(hsv.s, 1.0);
+        assert_eq!(Srgba::from(hsv).to_rgba8(), [255, 128, 0, 255]);
+        assert_eq!(Srgba::from(Hsv { h: -120.0, s: 1.0, v: 1.0, a: 1.0 }), Srgba::rgb(0.0, 0.0, 1.0));
+
+        let white = Oklab::from(LinearRgba::WHITE);
+        assert_near(white.l, 1.0);
+        assert_near(white.a, 0.0);
+        assert_near(white.b, 0.0);
+        let back = LinearRgba::from(Oklab::from(linear));
+        assert_near(back.r, linear.r);
+        assert_near(back.g, linear.g);
+        assert_near(back.b, linear.b);
+    }
+
+    #[test]
+    fn alpha_test() {
+        let color = LinearRgba::new(0.8, 0.4, 0.2, 0.5);
+        let premultiplied = color.premultiply();
+        assert_eq!(premultiplied, LinearRgba::new(0.4, 0.2, 0.1, 0.5));
+        assert_eq!(premultiplied.unpremultiply(), color);
+        assert_eq!(LinearRgba::new(1.0, 1.0, 1.0, 0.0).unpremultiply(), LinearRgba::TRANSPARENT);
+
+        assert_eq!(premultiplied.over(LinearRgba::BLACK), LinearRgba::new(0.4, 0.2, 0.1, 1.0));
+        assert_eq!(LinearRgba::TRANSPARENT.over(LinearRgba::WHITE), LinearRgba::WHITE);
+    }
+}
@@ -0,0 +1,210 @@
+use crate::scalar::Float;
+
+// Map a progress in [0, 1] to an eased progress. Start and end values are kept, but Back and Elastic
+// overshoot in between. See https://easings.net for the shapes.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Easing {
+    #[default]
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    QuartIn,
+    QuartOut,
+    QuartInOut,
+    QuintIn,
+    QuintOut,
+    QuintInOut,
+    SineIn,
+    SineOut,
+    SineInOut,
+    ExpoIn,
+    ExpoOut,
+    ExpoInOut,
+    CircIn,
+    CircOut,
+    CircInOut,
+    BackIn,
+    BackOut,
+    BackInOut,
+    ElasticIn,
+    ElasticOut,
+    ElasticInOut,
+    BounceIn,
+    BounceOut,
+    BounceInOut,
+}
+
+#[derive(Copy, Clone)]
+enum Shape {
+    Quad,
+    Cubic,
+    Quart,
+    Quint,
+    Sine,
+    Expo,
+    Circ,
+    Back,
+    Elastic,
+    Bounce,
+}
+
+#[derive(Copy, Clone)]
+enum Mode {
+    In,
+    Out,
+    InOut,
+}
+
+impl Easing {
+    pub const ALL: [Easing; 31] = [
+        Easing::Linear,
+        Easing::QuadIn, Easing::QuadOut, Easing::QuadInOut,
+        Easing::CubicIn, Easing::CubicOut, Easing::CubicInOut,
+        Easing::QuartIn, Easing::QuartOut, Easing::QuartInOut,
+        Easing::QuintIn, Easing::QuintOut, Easing::QuintInOut,
+        Easing::SineIn, Easing::SineOut, Easing::SineInOut,
+        Easing::ExpoIn, Easing::ExpoOut, Easing::ExpoInOut,
+        Easing::CircIn, Easing::CircOut, Easing::CircInOut,
+        Easing::BackIn, Easing::BackOut, Easing::BackInOut,
+        Easing::ElasticIn, Easing::ElasticOut, Easing::ElasticInOut,
+        Easing::BounceIn, Easing::BounceOut, Easing::BounceInOut,
+    ];
+
+    // The progress is clamped to [0, 1]
+    pub fn apply<T: Float>(&self, t: T) -> T {
+        let t = if t < T::zero() { T::zero() } else if t > T::one() { T::one() } else { t };
+        let Some((shape, mode)) = self.split() else { return t; };
+        let half = T::from_f64(0.5);
+        let two = T::from(2);
+        match mode {
+            Mode::In => { shape.ease_in(t) }
+            Mode::Out => { T::one() - shape.ease_in(T::one() - t) }
+            Mode::InOut => {
+                if t < half {
+                    shape.ease_in(two * t) * half
+                } else {
+                    T::one() - shape.ease_in(two - two * t) * half
+                }
+            }
+        }
+    }
+
+    fn split(&self) -> Option<(Shape, Mode)> {
+        let split = match self {
+            Easing::Linear => { return None; }
+            Easing::QuadIn => { (Shape::Quad, Mode::In) }
+            Easing::QuadOut => { (Shape::Quad, Mode::Out) }
+            Easing::QuadInOut => { (Shape::Quad, Mode::InOut) }
+            Easing::CubicIn => { (Shape::Cubic, Mode::In) }
+            Easing::CubicOut => { (Shape::Cubic, Mode::Out) }
+            Easing::CubicInOut => { (Shape::Cubic, Mode::InOut) }
+            Easing::QuartIn => { (Shape::Quart, Mode::In) }
+            Easing::QuartOut => { (Shape::Quart, Mode::Out) }
+            Easing::QuartInOut => { (Shape::Quart, Mode::InOut) }
+            Easing::QuintIn => { (Shape::Quint, Mode::In) }
+            Easing::QuintOut => { (Shape::Quint, Mode::Out) }
+            Easing::QuintInOut => { (Shape::Quint, Mode::InOut) }
+            Easing::SineIn => { (Shape::Sine, Mode::In) }
+            Easing::SineOut => { (Shape::Sine, Mode::Out) }
+            Easing::SineInOut => { (Shape::Sine, Mode::InOut) }
+            Easing::ExpoIn => { (Shape::Expo, Mode::In) }
+            Easing::ExpoOut => { (Shape::Expo, Mode::Out) }
+            Easing::ExpoInOut => { (Shape::Expo, Mode::InOut) }
+            Easing::CircIn => { (Shape::Circ, Mode::In) }
+            Easing::CircOut => { (Shape::Circ, Mode::Out) }
+            Easing::CircInOut => { (Shape::Circ, Mode::InOut) }
+            Easing::BackIn => { (Shape::Back, Mode::In) }
+            Easing::BackOut => { (Shape::Back, Mode::Out) }
+            Easing::BackInOut => { (Shape::Back, Mode::InOut) }
+            Easing::ElasticIn => { (Shape::Elastic, Mode::In) }
+            Easing::ElasticOut => { (Shape::Elastic, Mode::Out) }
+            Easing::ElasticInOut => { (Shape::Elastic, Mode::InOut) }
+            Easing::BounceIn => { (Shape::Bounce, Mode::In) }
+            Easing::BounceOut => { (Shape::Bounce, Mode::Out) }
+            Easing::BounceInOut => { (Shape::Bounce, Mode::InOut) }
+        };
+        Some(split)
+    }
+}
+
+impl Shape {
+    // Ease in variant : the other modes are derived from it
+    fn ease_in<T: Float>(&self, t: T) -> T {
+        let (zero, one) = (T::zero(), T::one());
+        match self {
+            Shape::Quad => { t * t }
+            Shape::Cubic => { t * t * t }
+            Shape::Quart => { t * t * t * t }
+            Shape::Quint => { t * t * t * t * t }
+            Shape::Sine => { one - (t * T::PI / T::from(2)).cos() }
+            Shape::Expo => {
+                if t == zero { zero } else { T::from(2).powf(T::from(10) * t - T::from(10)) }
+            }
+            Shape::Circ => { one - (one - t * t).sqrt() }
+            Shape::Back => {
+                let c1 = T::from_f64(1.70158);
+                (c1 + one) * t * t * t - c1 * t * t
+            }
+            Shape::Elastic => {
+                if t == zero || t == one {
+                    return t;
+                }
+                let c4 = T::from(2) * T::PI / T::from(3);
+                -T::from(2).powf(T::from(10) * t - T::from(10)) * ((t * T::from(10) - T::from_f64(10.75)) * c4).sin()
+            }
+            Shape::Bounce => { one - bounce_out(one - t) }
+        }
+    }
+}
+
+fn bounce_out<T: Float>(t: T) -> T {
+    let (n1, d1) = (T::from_f64(7.5625), T::from_f64(2.75));
+    if t < T::one() / d1 {
+        n1 * t * t
+    } else if t < T::from(2) / d1 {
+        let t = t - T::from_f64(1.5) / d1;
+        n1 * t * t + T::from_f64(0.75)
+    } else if t < T::from_f64(2.5) / d1 {
+        let t = t - T::from_f64(2.25) / d1;
+        n1 * t * t + T::from_f64(0.9375)
+    } else {
+        let t = t - T::from_f64(2.625) / d1;
+        n1 * t * t + T::from_f64(0.984375)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::easing::Easing;
+
+    #[test]
+    fn easing_test() {
+        for easing in Easing::ALL {
+            assert!(easing.apply(0.0f64).abs() < 1e-9, "{easing:?}");
+            assert!((easing.apply(1.0f64) - 1.0).abs() < 1e-9, "{easing:?}");
+            // Symmetric modes go through the middle
+            if format!("{easing:?}").ends_with("InOut") {
+                assert!((easing.apply(0.5f64) - 0.5).abs() < 1e-9, "{easing:?}");
+            }
+        }
+        assert_eq!(Easing::Linear.apply(0.25f32), 0.25);
+        assert_eq!(Easing::QuadIn.apply(0.5f64), 0.25);
+        assert_eq!(Easing::QuadOut.apply(0.5f64), 0.75);
+        assert_eq!(Easing::CubicInOut.apply(0.25f64), 0.0625);
+        assert_eq!(Easing::CubicIn.apply(2.0f64), 1.0);
+        assert!(Easing::BackIn.apply(0.2f64) < 0.0);
+        assert!(Easing::ElasticOut.apply(0.2f64) > 1.0);
+        assert!((Easing::BounceOut.apply(1.0f64 / 2.75) - 1.0).abs() < 1e-9);
+
+        // In and out variants mirror each other
+        for t in [0.1f64, 0.3, 0.7] {
+            assert!((Easing::SineIn.apply(t) + Easing::SineOut.apply(1.0 - t) - 1.0).abs() < 1e-9);
+            assert!((Easing::ExpoIn.apply(t) + Easing::ExpoOut.apply(1.0 - t) - 1.0).abs() < 1e-9);
+        }
+    }
+}
@@ -0,0 +1,435 @@
+use crate::mat4::Mat4;
+use crate::scalar::Float;
+use crate::vec3::Vec3;
+use crate::vec4::Vec4;
+
+/*
+AXIS ALIGNED BOX
+ */
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Aabb<T: Default> {
+    pub min: Vec3<T>,
+    pub max: Vec3<T>,
+}
+
+pub type AabbF32 = Aabb<f32>;
+pub type AabbF64 = Aabb<f64>;
+
+impl<T: Float> Aabb<T> {
+    pub fn new(min: Vec3<T>, max: Vec3<T>) -> Self {
+        Self { min, max }
+    }
+
+    pub fn from_center_extents(center: Vec3<T>, extents: Vec3<T>) -> Self {
+        Self::new(center - extents, center + extents)
+    }
+
+    // None if there is no point
+    pub fn from_points(points: impl IntoIterator<Item=Vec3<T>>) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        Some(points.fold(Self::new(first, first), |aabb, point| Self::new(aabb.min.min(point), aabb.max.max(point))))
+    }
+
+    pub fn center(&self) -> Vec3<T> {
+        (self.min + self.max) / T::from(2)
+    }
+
+    // Half of the size
+    pub fn extents(&self) -> Vec3<T> {
+        (self.max - self.min) / T::from(2)
+    }
+
+    pub fn size(&self) -> Vec3<T> {
+        self.max - self.min
+    }
+
+    pub fn merge(&self, other: &Self) -> Self {
+        Self::new(self.min.min(other.min), self.max.max(other.max))
+    }
+
+    pub fn contains_point(&self, point: Vec3<T>) -> bool {
+        point.clamp(self.min, self.max) == point
+    }
+
+    pub fn contains(&self, other: &Self) -> bool {
+        self.contains_point(other.min) && self.contains_point(other.max)
+    }
+
+    // Touching boxes intersect
+    pub fn intersects(&self, other: &Self) -> bool {
+        self.min.x <= other.max.x && self.max.x >= other.min.x
+            && self.min.y <= other.max.y && self.max.y >= other.min.y
+            && self.min.z <= other.max.z && self.max.z >= other.min.z
+    }
+
+    pub fn closest_point(&self, point: Vec3<T>) -> Vec3<T> {
+        point.clamp(self.min, self.max)
+    }
+
+    // Box enclosing the transformed box. The matrix must be affine.
+    pub fn transform(&self, m: &Mat4<T>) -> Self {
+        let axis = |index: usize| Vec3::from(m.col(index)).abs();
+        let extents = self.extents();
+        let center = Vec3::from(*m * Vec4::new(self.center().x, self.center().y, self.center().z, T::one()));
+        Self::from_center_extents(center, axis(0) * extents.x + axis(1) * extents.y + axis(2) * extents.z)
+    }
+}
+
+/*
+SPHERE
+ */
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Sphere<T: Default> {
+    pub center: Vec3<T>,
+    pub radius: T,
+}
+
+pub type SphereF32 = Sphere<f32>;
+pub type SphereF64 = Sphere<f64>;
+
+impl<T: Float> Sphere<T> {
+    pub fn new(center: Vec3<T>, radius: T) -> Self {
+        Self { center, radius }
+    }
+
+    // Not the smallest enclosing sphere, but cheap
+    pub fn from_aabb(aabb: &Aabb<T>) -> Self {
+        Self::new(aabb.center(), aabb.extents().length())
+    }
+
+    pub fn contains_point(&self, point: Vec3<T>) -> bool {
+        self.center.distance_squared(point) <= self.radius * self.radius
+    }
+
+    pub fn intersects(&self, other: &Self) -> bool {
+        let radius = self.radius + other.radius;
+        self.center.distance_squared(other.center) <= radius * radius
+    }
+
+    pub fn intersects_aabb(&self, aabb: &Aabb<T>) -> bool {
+        self.contains_point(aabb.closest_point(self.center))
+    }
+}
+
+/*
+PLANE
+ */
+
+// Points p with normal.dot(p) + distance = 0. The normal side is the positive side.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Plane<T: Default> {
+    pub normal: Vec3<T>,
+    pub distance: T,
+}
+
+pub type PlaneF32 = Plane<f32>;
+pub type PlaneF64 = Plane<f64>;
+
+impl<T: Float> Plane<T> {
+    pub fn new(normal: Vec3<T>, distance: T) -> Self {
+        Self { normal, distance }
+    }
+
+    pub fn from_point_normal(point: Vec3<T>, normal: Vec3<T>) -> Self {
+        let normal = normal.normalize();
+        Self::new(normal, -normal.dot(point))
+    }
+
+    // Counter-clockwise points see the positive side
+    pub fn from_points(a: Vec3<T>, b: Vec3<T>, c: Vec3<T>) -> Self {
+        Self::from_point_normal(a, (b - a).cross(c - a))
+    }
+
+    // Scale the equation so that the normal has a unit length. Degenerate planes are kept as is.
+    pub fn normalize(&self) -> Self {
+        let length = self.normal.length();
+        if length <= T::EPSILON {
+            return *self;
+        }
+        Self::new(self.normal / length, self.distance / length)
+    }
+
+    // Positive on the normal side. Only a distance if the plane is normalized.
+    pub fn signed_distance(&self, point: Vec3<T>) -> T {
+        self.normal.dot(point) + self.distance
+    }
+}
+
+/*
+RAY
+ */
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Ray<T: Default> {
+    pub origin: Vec3<T>,
+    pub direction: Vec3<T>,
+}
+
+pub type RayF32 = Ray<f32>;
+pub type RayF64 = Ray<f64>;
+
+// Intersections return the distance along the ray, in direction lengths. Hits behind the origin are ignored.
+impl<T: Float> Ray<T> {
+    pub fn new(origin: Vec3<T>, direction: Vec3<T>) -> Self {
+        Self { origin, direction }
+    }
+
+    pub fn at(&self, distance: T) -> Vec3<T> {
+        self.origin + self.direction * distance
+    }
+
+    pub fn intersect_plane(&self, plane: &Plane<T>) -> Option<T> {
+        let denominator = plane.normal.dot(self.direction);
+        if denominator.abs() <= T::EPSILON {
+            return None;
+        }
+        Some(-plane.signed_distance(self.origin) / denominator).filter(|t| *t >= T::zero())
+    }
+
+    // Slab test. Zero if the origin is inside the box.
+    pub fn intersect_aabb(&self, aabb: &Aabb<T>) -> Option<T> {
+        let (mut near, mut far) = (T::zero(), T::INFINITY);
+        for axis in 0..3 {
+            let inverse = T::one() / self.direction[axis];
+            let mut t0 = (aabb.min[axis] - self.origin[axis]) * inverse;
+            let mut t1 = (aabb.max[axis] - self.origin[axis]) * inverse;
+            if t0 > t1 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+            // Written so that NaN, from a ray parallel to a slab face, does not shrink the range
+            near = if t0 > near { t0 } else { near };
+            far = if t1 < far { t1 } else { far };
+            if near > far {
+                return None;
+            }
+        }
+        Some(near)
+    }
+
+    // Zero if the origin is inside the sphere
+    pub fn intersect_sphere(&self, sphere: &Sphere<T>) -> Option<T> {
+        let offset = self.origin - sphere.center;
+        let a = self.direction.length_squared();
+        let b = offset.dot(self.direction);
+        let c = offset.length_squared() - sphere.radius * sphere.radius;
+        let discriminant = b * b - a * c;
+        if discriminant < T::zero() {
+            return None;
+        }
+        let root = discriminant.sqrt();
+        let (near, far) = ((-b - root) / a, (-b + root) / a);
+        match (near >= T::zero(), far >= T::zero()) {
+            (true, _) => { Some(near) }
+            (false, true) => { Some(T::zero()) }
+            _ => { None }
+        }
+    }
+
+    // Möller-Trumbore, both faces are hit
+    pub fn intersect_triangle(&self, triangle: &Triangle<T>) -> Option<T> {
+        let (edge1, edge2) = (triangle.b - triangle.a, triangle.c - triangle.a);
+        let p = self.direction.cross(edge2);
+        let determinant = edge1.dot(p);
+        if determinant.abs() <= T::EPSILON {
+            return None;
+        }
+        let inverse = T::one() / determinant;
+        let offset = self.origin - triangle.a;
+        let u = offset.dot(p) * inverse;
+        if u < T::zero() || u > T::one() {
+            return None;
+        }
+        let q = offset.cross(edge1);
+        let v = self.direction.dot(q) * inverse;
+        if v < T::zero() || u + v > T::one() {
+            return None;
+        }
+        Some(edge2.dot(q) * inverse).filter(|t| *t >= T::zero())
+    }
+}
+
+/*
+TRIANGLE
+ */
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Triangle<T: Default> {
+    pub a: Vec3<T>,
+    pub b: Vec3<T>,
+    pub c: Vec3<T>,
+}
+
+pub type TriangleF32 = Triangle<f32>;
+pub type TriangleF64 = Triangle<f64>;
+
+impl<T: Float> Triangle<T> {
+    pub fn new(a: Vec3<T>, b: Vec3<T>, c: Vec3<T>) -> Self {
+        Self { a, b, c }
+    }
+
+    // Facing the side where the points are counter-clockwise
+    pub fn normal(&self) -> Vec3<T> {
+        (self.b - self.a).cross(self.c - self.a).normalize()
+    }
+
+    pub fn area(&self) -> T {
+        (self.b - self.a).cross(self.c - self.a).length() / T::from(2)
+    }
+
+    pub fn plane(&self) -> Plane<T> {
+        Plane::from_points(self.a, self.b, self.c)
+    }
+
+    pub fn aabb(&self) -> Aabb<T> {
+        Aabb::new(self.a.min(self.b).min(self.c), self.a.max(self.b).max(self.c))
+    }
+}
+
+/*
+FRUSTUM
+ */
+
+// Plane normals point inside
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Frustum<T: Default> {
+    pub planes: [Plane<T>; 6],
+}
+
+pub type FrustumF32 = Frustum<f32>;
+pub type FrustumF64 = Frustum<f64>;
+
+impl<T: Float> Frustum<T> {
+    pub const LEFT: usize = 0;
+    pub const RIGHT: usize = 1;
+    pub const BOTTOM: usize = 2;
+    pub const TOP: usize = 3;
+    pub const NEAR: usize = 4;
+    pub const FAR: usize = 5;
+
+    // Planes of a projection or view-projection matrix, for a 0..1 depth range. Planes are in the space the
+    // matrix is applied to : world space for a view-projection. With reversed depth, near and far are swapped.
+    // An infinite far plane is degenerate and accepts everything.
+    pub fn from_mat4(m: &Mat4<T>) -> Self {
+        let (x, y, z, w) = (m.row(0), m.row(1), m.row(2), m.row(3));
+        let plane = |row: Vec4<T>| Plane::new(Vec3::from(row), row.w).normalize();
+        Self { planes: [plane(w + x), plane(w - x), plane(w + y), plane(w - y), plane(z), plane(w - z)] }
+    }
+
+    pub fn contains_point(&self, point: Vec3<T>) -> bool {
+        self.planes.iter().all(|plane| plane.signed_distance(point) >= T::zero())
+    }
+
+    // Conservative : may accept spheres near the frustum corners
+    pub fn intersects_sphere(&self, sphere: &Sphere<T>) -> bool {
+        self.planes.iter().all(|plane| plane.signed_distance(sphere.center) >= -sphere.radius)
+    }
+
+    // Conservative : may accept boxes near the frustum corners
+    pub fn intersects_aabb(&self, aabb: &Aabb<T>) -> bool {
+        let (center, extents) = (aabb.center(), aabb.extents());
+        self.planes.iter().all(|plane| plane.signed_distance(center) >= -plane.normal.abs().dot(extents))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::FRAC_PI_2;
+
+    use crate::geometry::{AabbF64, FrustumF64, PlaneF64, RayF64, SphereF64, TriangleF64};
+    use crate::mat4::Mat4F64;
+    use crate::quat::QuatF64;
+    use crate::transform::TransformF64;
+    use crate::vec3::{Vec3, Vec3F64};
+
+    fn assert_vec_near(a: Vec3F64, b: Vec3F64) {
+        assert!(a.distance(b) < 1e-9, "{a:?} != {b:?}");
+    }
+
+    #[test]
+    fn volume_test() {
+        let aabb = AabbF64::from_points([Vec3::new(1.0, 0.0, -1.0), Vec3::new(-1.0, 2.0, 0.0), Vec3::new(0.0, 1.0, 3.0)]).unwrap();
+        assert_eq!(aabb, AabbF64::new(Vec3::new(-1.0, 0.0, -1.0), Vec3::new(1.0, 2.0, 3.0)));
+        assert_eq!(aabb.center(), Vec3::new(0.0, 1.0, 1.0));
+        assert!(aabb.contains_point(Vec3::new(1.0, 0.5, 0.0)));
+        assert!(!aabb.contains_point(Vec3::new(1.5, 0.5, 0.0)));
+        assert!(aabb.intersects(&AabbF64::new(Vec3::splat(1.0), Vec3::splat(5.0))));
+        assert!(!aabb.intersects(&AabbF64::new(Vec3::splat(3.5), Vec3::splat(5.0))));
+        assert!(aabb.merge(&AabbF64::new(Vec3::splat(3.5), Vec3::splat(5.0))).contains(&aabb));
+
+        // Rotating a quarter turn around x swaps the y and z sizes
+        let transform = TransformF64::new(Vec3::new(10.0, 0.0, 0.0), QuatF64::from_rotation_x(FRAC_PI_2), Vec3::splat(1.0));
+        let transformed = aabb.transform(&transform.to_mat4());
+        assert_vec_near(transformed.center(), transform.transform_point(aabb.center()));
+        assert_vec_near(transformed.size(), Vec3::new(2.0, 4.0, 2.0));
+
+        let sphere = SphereF64::new(Vec3::new(3.0, 0.0, 0.0), 1.0);
+        assert!(sphere.intersects(&SphereF64::new(Vec3::new(0.0, 0.0, 0.0), 2.0)));
+        assert!(!sphere.intersects(&SphereF64::new(Vec3::new(0.0, 0.0, 0.0), 1.9)));
+        assert!(!sphere.intersects_aabb(&aabb));
+        assert!(SphereF64::new(Vec3::new(2.0, 2.5, 0.0), 1.2).intersects_aabb(&aabb));
+        assert!(!SphereF64::new(Vec3::new(2.0, 2.9, 0.0), 1.2).intersects_aabb(&aabb));
+    }
+
+    #[test]
+    fn ray_test() {
+        let ray = RayF64::new(Vec3::new(0.0, 0.0, -5.0), Vec3::new(0.0, 0.0, 2.0));
+        let aabb = AabbF64::new(Vec3::splat(-1.0), Vec3::splat(1.0));
+        assert_eq!(ray.intersect_aabb(&aabb), Some(2.0));
+        assert_eq!(ray.at(2.0), Vec3::new(0.0, 0.0, -1.0));
+        assert_eq!(RayF64::new(Vec3::splat(0.0), Vec3::new(1.0, 0.0, 0.0)).intersect_aabb(&aabb), Some(0.0));
+        assert_eq!(RayF64::new(Vec3::new(0.0, 2.0, -5.0), Vec3::new(0.0, 0.0, 1.0)).intersect_aabb(&aabb), None);
+        assert_eq!(RayF64::new(Vec3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, 1.0)).intersect_aabb(&aabb), None);
+
+        assert_eq!(ray.intersect_sphere(&SphereF64::new(Vec3::new(0.0, 0.0, 1.0), 2.0)), Some(2.0));
+        assert_eq!(ray.intersect_sphere(&SphereF64::new(Vec3::new(0.0, 3.0, 1.0), 2.0)), None);
+        assert_eq!(ray.intersect_plane(&PlaneF64::from_point_normal(Vec3::new(0.0, 0.0, 3.0), Vec3::new(0.0, 0.0, -1.0))), Some(4.0));
+        assert_eq!(ray.intersect_plane(&PlaneF64::from_point_normal(Vec3::new(0.0, 0.0, 3.0), Vec3::new(1.0, 0.0, 0.0))), None);
+
+        let triangle = TriangleF64::new(Vec3::new(-1.0, -1.0, 1.0), Vec3::new(1.0, -1.0, 1.0), Vec3::new(0.0, 1.0, 1.0));
+        assert_eq!(triangle.normal(), Vec3::new(0.0, 0.0, 1.0));
+        assert_eq!(triangle.area(), 2.0);
+        assert_eq!(ray.intersect_triangle(&triangle), Some(3.0));
+        assert_eq!(RayF64::new(Vec3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0)).intersect_triangle(&triangle), Some(4.0));
+        assert_eq!(RayF64::new(Vec3::new(0.9, 0.9, -5.0), Vec3::new(0.0, 0.0, 1.0)).intersect_triangle(&triangle), None);
+        assert_eq!(RayF64::new(Vec3::new(0.0, 0.0, 2.0), Vec3::new(0.0, 0.0, 1.0)).intersect_triangle(&triangle), None);
+    }
+
+    #[test]
+    fn frustum_test() {
+        let view = Mat4F64::look_at(Vec3::new(0.0, 0.0, 10.0), Vec3::splat(0.0), Vec3::new(0.0, 1.0, 0.0));
+        let projections = [
+            Mat4F64::perspective(FRAC_PI_2, 1.0, 1.0, 100.0),
+            Mat4F64::perspective_reversed(FRAC_PI_2, 1.0, 1.0, 100.0),
+        ];
+        for projection in projections {
+            let frustum = FrustumF64::from_mat4(&(projection * view));
+            assert!(frustum.contains_point(Vec3::new(0.0, 0.0, 0.0)));
+            // 90 degrees : the side planes go through the corners
+            assert!(frustum.contains_point(Vec3::new(9.9, 9.9, 0.0)));
+            assert!(!frustum.contains_point(Vec3::new(10.1, 0.0, 0.0)));
+            assert!(!frustum.contains_point(Vec3::new(0.0, 0.0, 9.5)));
+            assert!(!frustum.contains_point(Vec3::new(0.0, 0.0, -95.0)));
+            assert!((frustum.planes[FrustumF64::LEFT].signed_distance(Vec3::new(-10.0, 0.0, 0.0))).abs() < 1e-9);
+
+            assert!(frustum.intersects_aabb(&AabbF64::new(Vec3::new(10.5, -1.0, -1.0), Vec3::new(12.0, 1.0, 1.0))));
+            assert!(!frustum.intersects_aabb(&AabbF64::new(Vec3::new(11.5, -1.0, -1.0), Vec3::new(12.0, 1.0, 1.0))));
+            assert!(!frustum.intersects_aabb(&AabbF64::new(Vec3::new(-1.0, -1.0, 10.0), Vec3::new(1.0, 1.0, 12.0))));
+            assert!(frustum.intersects_sphere(&SphereF64::new(Vec3::new(0.0, 0.0, 9.5), 1.0)));
+            assert!(!frustum.intersects_sphere(&SphereF64::new(Vec3::new(0.0, 0.0, 11.0), 1.0)));
+        }
+
+        // An infinite far plane keeps everything in front of the camera
+        let frustum = FrustumF64::from_mat4(&(Mat4F64::perspective_infinite(FRAC_PI_2, 1.0, 1.0) * view));
+        assert!(frustum.contains_point(Vec3::new(0.0, 0.0, -1e6)));
+        assert!(!frustum.contains_point(Vec3::new(0.0, 0.0, 9.5)));
+    }
+}
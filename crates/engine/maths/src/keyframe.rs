@@ -0,0 +1,160 @@
+use crate::easing::Easing;
+use crate::scalar::Float;
+use crate::spline::{CurvePoint, hermite};
+
+// How the value goes from a key to the next one
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Interpolation {
+    // Keep the value until the next key
+    Step,
+    #[default]
+    Linear,
+    // Smooth curve through the neighbour keys, like a Catmull-Rom spline
+    Cubic,
+    Ease(Easing),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Keyframe<T, P> {
+    pub time: T,
+    pub value: P,
+    // Used between this key and the next one
+    pub interpolation: Interpolation,
+}
+
+impl<T, P> Keyframe<T, P> {
+    pub fn new(time: T, value: P, interpolation: Interpolation) -> Self {
+        Self { time, value, interpolation }
+    }
+}
+
+// Animation track : keys sorted by time. The value is held before the first key and after the last one.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KeyframeCurve<T, P> {
+    keys: Vec<Keyframe<T, P>>,
+}
+
+impl<T, P> Default for KeyframeCurve<T, P> {
+    fn default() -> Self {
+        Self { keys: vec![] }
+    }
+}
+
+impl<T: Float, P: CurvePoint<T>> KeyframeCurve<T, P> {
+    pub fn new(keys: impl IntoIterator<Item=Keyframe<T, P>>) -> Self {
+        let mut curve = Self::default();
+        for key in keys {
+            curve.insert(key);
+        }
+        curve
+    }
+
+    // A key at the same time as an existing one replaces it
+    pub fn insert(&mut self, key: Keyframe<T, P>) {
+        let index = self.keys.partition_point(|other| other.time < key.time);
+        match self.keys.get_mut(index) {
+            Some(other) if other.time == key.time => { *other = key }
+            _ => { self.keys.insert(index, key) }
+        }
+    }
+
+    pub fn remove(&mut self, index: usize) -> Keyframe<T, P> {
+        self.keys.remove(index)
+    }
+
+    pub fn keys(&self) -> &[Keyframe<T, P>] {
+        &self.keys
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    // Time of the first and last keys
+    pub fn range(&self) -> Option<(T, T)> {
+        Some((self.keys.first()?.time, self.keys.last()?.time))
+    }
+
+    // None if there is no key
+    pub fn sample(&self, time: T) -> Option<P> {
+        let first = self.keys.first()?;
+        let index = self.keys.partition_point(|key| key.time <= time);
+        if index == 0 {
+            return Some(first.value);
+        }
+        if index == self.keys.len() {
+            return Some(self.keys[index - 1].value);
+        }
+
+        let (start, end) = (&self.keys[index - 1], &self.keys[index]);
+        let duration = end.time - start.time;
+        let t = (time - start.time) / duration;
+        let value = match start.interpolation {
+            Interpolation::Step => { start.value }
+            Interpolation::Linear => { start.value + (end.value - start.value) * t }
+            Interpolation::Ease(easing) => { start.value + (end.value - start.value) * easing.apply(t) }
+            Interpolation::Cubic => {
+                hermite(start.value, self.tangent(index - 1) * duration, end.value, self.tangent(index) * duration, t)
+            }
+        };
+        Some(value)
+    }
+
+    // Rate of change at a key, from its neighbours. Keys have distinct times.
+    fn tangent(&self, index: usize) -> P {
+        let previous = &self.keys[index.saturating_sub(1)];
+        let next = &self.keys[(index + 1).min(self.keys.len() - 1)];
+        (next.value - previous.value) * (T::one() / (next.time - previous.time))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::easing::Easing;
+    use crate::keyframe::{Interpolation, Keyframe, KeyframeCurve};
+    use crate::vec3::Vec3;
+
+    #[test]
+    fn keyframe_test() {
+        let mut curve = KeyframeCurve::new([
+            Keyframe::new(2.0, 20.0, Interpolation::Step),
+            Keyframe::new(0.0, 0.0, Interpolation::Linear),
+            Keyframe::new(1.0, 10.0, Interpolation::Ease(Easing::QuadIn)),
+        ]);
+        assert_eq!(curve.keys().iter().map(|key| key.time).collect::<Vec<f64>>(), vec![0.0, 1.0, 2.0]);
+        assert_eq!(curve.range(), Some((0.0, 2.0)));
+        assert_eq!(curve.sample(-1.0), Some(0.0));
+        assert_eq!(curve.sample(0.25), Some(2.5));
+        assert_eq!(curve.sample(1.5), Some(12.5));
+        assert_eq!(curve.sample(2.0), Some(20.0));
+        assert_eq!(curve.sample(5.0), Some(20.0));
+
+        curve.insert(Keyframe::new(2.0, 30.0, Interpolation::Step));
+        curve.insert(Keyframe::new(3.0, 0.0, Interpolation::Linear));
+        assert_eq!(curve.keys().len(), 4);
+        assert_eq!(curve.sample(2.9), Some(30.0));
+        assert_eq!(curve.remove(3).value, 0.0);
+        assert!(KeyframeCurve::<f64, f64>::default().sample(0.0).is_none());
+    }
+
+    #[test]
+    fn cubic_test() {
+        // Evenly spaced keys on a line : the cubic curve stays on it
+        let curve = KeyframeCurve::new((0..4).map(|i| Keyframe::new(i as f64 * 0.5, Vec3::new(i as f64, 2.0 * i as f64, 0.0), Interpolation::Cubic)));
+        let value = curve.sample(0.75).unwrap();
+        assert!((value.x - 1.5).abs() < 1e-9 && (value.y - 3.0).abs() < 1e-9);
+
+        // Goes through the keys, smoothly
+        let curve = KeyframeCurve::new([
+            Keyframe::new(0.0, 0.0f64, Interpolation::Cubic),
+            Keyframe::new(1.0, 1.0, Interpolation::Cubic),
+            Keyframe::new(3.0, 0.0, Interpolation::Cubic),
+        ]);
+        assert_eq!(curve.sample(1.0), Some(1.0));
+        let (before, after) = (curve.sample(1.0 - 1e-6).unwrap(), curve.sample(1.0 + 1e-6).unwrap());
+        assert!(((1.0 - before) - (after - 1.0)).abs() < 1e-9);
+    }
+}
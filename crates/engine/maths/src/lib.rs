@@ -4,10 +4,107 @@ use crate::vec2::*;
 use crate::vec3::*;
 use crate::vec4::*;
 
+// Operations shared by Vec2, Vec3 and Vec4. Declared before the modules using it.
+macro_rules! impl_vector {
+    ($name:ident, $size:literal, $($field:ident),+) => {
+        impl<T: Default + Copy + PartialOrd> $name<T> {
+            pub fn splat(value: T) -> Self {
+                Self { $($field: value),+ }
+            }
+
+            pub fn to_array(&self) -> [T; $size] {
+                [$(self.$field),+]
+            }
+
+            pub fn min(&self, other: Self) -> Self {
+                Self { $($field: if other.$field < self.$field { other.$field } else { self.$field }),+ }
+            }
+
+            pub fn max(&self, other: Self) -> Self {
+                Self { $($field: if other.$field > self.$field { other.$field } else { self.$field }),+ }
+            }
+
+            pub fn clamp(&self, min: Self, max: Self) -> Self {
+                self.max(min).min(max)
+            }
+        }
+
+        impl<T: $crate::scalar::Scalar> $name<T> {
+            pub fn dot(&self, other: Self) -> T {
+                T::zero() $(+ self.$field * other.$field)+
+            }
+
+            pub fn length_squared(&self) -> T {
+                self.dot(*self)
+            }
+
+            pub fn distance_squared(&self, other: Self) -> T {
+                (*self - other).length_squared()
+            }
+
+            pub fn abs(&self) -> Self {
+                Self { $($field: if self.$field < T::zero() { -self.$field } else { self.$field }),+ }
+            }
+
+            pub fn lerp(&self, other: Self, t: T) -> Self {
+                *self + (other - *self) * t
+            }
+        }
+
+        impl<T: $crate::scalar::Float> $name<T> {
+            pub fn length(&self) -> T {
+                self.length_squared().sqrt()
+            }
+
+            pub fn distance(&self, other: Self) -> T {
+                (*self - other).length()
+            }
+
+            // Components are NaN for a zero vector
+            pub fn normalize(&self) -> Self {
+                *self / self.length()
+            }
+
+            pub fn normalize_or_zero(&self) -> Self {
+                let length = self.length();
+                if length > T::EPSILON { *self / length } else { Self::splat(T::zero()) }
+            }
+        }
+
+        impl<T: Default> ops::Index<usize> for $name<T> {
+            type Output = T;
+
+            fn index(&self, index: usize) -> &T {
+                [$(&self.$field),+][index]
+            }
+        }
+
+        impl<T: Default> ops::IndexMut<usize> for $name<T> {
+            fn index_mut(&mut self, index: usize) -> &mut T {
+                let [$($field),+] = [$(&mut self.$field),+];
+                [$($field),+].into_iter().nth(index).expect("vector index out of bounds")
+            }
+        }
+    };
+}
+
 pub mod rect2d;
+pub mod color;
+pub mod easing;
+pub mod geometry;
+pub mod keyframe;
+pub mod scalar;
+pub mod simd;
+pub mod spline;
 pub mod mat2;
 pub mod mat3;
 pub mod mat4;
+pub mod noise;
+pub mod packer;
+pub mod projection;
+pub mod quat;
+pub mod random;
+pub mod transform;
 pub mod vec2;
 pub mod vec3;
 pub mod vec4;
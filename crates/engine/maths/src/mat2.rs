@@ -1,9 +1,13 @@
-﻿
 use std::ops;
 use macros::*;
 
+use crate::scalar::Scalar;
+use crate::vec2::Vec2;
 
-#[derive(Debug, Copy, Clone, OpsAdd, OpsSub, DefaultConstruct)]
+// Column major : x1..x2 is the first column
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, OpsAdd, OpsSub, DefaultConstruct)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Mat2<T: Default> {
     pub x1: T,
     pub x2: T,
@@ -12,4 +16,129 @@ pub struct Mat2<T: Default> {
 }
 
 pub type Mat2F32 = Mat2<f32>;
-pub type Mat2F64 = Mat2<f64>;
\ No newline at end of file
+pub type Mat2F64 = Mat2<f64>;
+
+impl<T: Default + Copy + From<u8>> Mat2<T> {
+    pub fn identity() -> Self {
+        let (zero, one) = (T::from(0), T::from(1));
+        Self::new(
+            one, zero,
+            zero, one)
+    }
+}
+
+impl<T: Default + Copy> Mat2<T> {
+    pub fn from_cols(cols: [[T; 2]; 2]) -> Self {
+        let [x, y] = cols;
+        Self::new(
+            x[0], x[1],
+            y[0], y[1])
+    }
+
+    pub fn to_cols(&self) -> [[T; 2]; 2] {
+        [
+            [self.x1, self.x2],
+            [self.y1, self.y2],
+        ]
+    }
+
+    // Elements in memory order, as expected by shaders
+    pub fn to_array(&self) -> [T; 4] {
+        [self.x1, self.x2, self.y1, self.y2]
+    }
+
+    pub fn from_diagonal(diagonal: Vec2<T>) -> Self where T: From<u8> {
+        let zero = T::from(0);
+        Self::new(
+            diagonal.x, zero,
+            zero, diagonal.y)
+    }
+
+    pub fn col(&self, index: usize) -> Vec2<T> {
+        let [x, y] = self.to_cols()[index];
+        Vec2::new(x, y)
+    }
+
+    pub fn row(&self, index: usize) -> Vec2<T> {
+        let cols = self.to_cols();
+        Vec2::new(cols[0][index], cols[1][index])
+    }
+
+    pub fn transpose(&self) -> Self {
+        Self::new(
+            self.x1, self.y1,
+            self.x2, self.y2)
+    }
+}
+
+impl<T: Scalar> Mat2<T> {
+    pub fn determinant(&self) -> T {
+        self.x1 * self.y2 - self.y1 * self.x2
+    }
+
+    // None if the matrix is singular
+    pub fn inverse(&self) -> Option<Self> {
+        let determinant = self.determinant();
+        if determinant == T::zero() {
+            return None;
+        }
+        let inv_det = T::one() / determinant;
+        Some(Self::new(
+            self.y2 * inv_det, -self.x2 * inv_det,
+            -self.y1 * inv_det, self.x1 * inv_det))
+    }
+}
+
+impl<T: Default + Copy + ops::Add<Output=T> + ops::Mul<Output=T>> ops::Mul<Mat2<T>> for Mat2<T> {
+    type Output = Mat2<T>;
+
+    fn mul(self, rhs: Mat2<T>) -> Self::Output {
+        Self::new(
+            self.x1 * rhs.x1 + self.y1 * rhs.x2, self.x2 * rhs.x1 + self.y2 * rhs.x2,
+            self.x1 * rhs.y1 + self.y1 * rhs.y2, self.x2 * rhs.y1 + self.y2 * rhs.y2)
+    }
+}
+
+impl<T: Default + Copy + ops::Add<Output=T> + ops::Mul<Output=T>> ops::Mul<Vec2<T>> for Mat2<T> {
+    type Output = Vec2<T>;
+
+    fn mul(self, rhs: Vec2<T>) -> Self::Output {
+        Vec2::new(
+            self.x1 * rhs.x + self.y1 * rhs.y,
+            self.x2 * rhs.x + self.y2 * rhs.y)
+    }
+}
+
+impl<T: Default + Copy + ops::Mul<Output=T>> ops::Mul<T> for Mat2<T> {
+    type Output = Mat2<T>;
+
+    fn mul(self, rhs: T) -> Self::Output {
+        Self::new(
+            self.x1 * rhs, self.x2 * rhs,
+            self.y1 * rhs, self.y2 * rhs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mat2::Mat2F64;
+    use crate::vec2::Vec2;
+
+    #[test]
+    fn mat2_test() {
+        let m = Mat2F64::from_cols([[1.0, 2.0], [3.0, 4.0]]);
+        assert_eq!(m.row(0), Vec2::new(1.0, 3.0));
+        assert_eq!(m.col(1), Vec2::new(3.0, 4.0));
+        assert_eq!(m.transpose(), Mat2F64::from_cols([[1.0, 3.0], [2.0, 4.0]]));
+        assert_eq!(m.to_array(), [1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(m * Mat2F64::identity(), m);
+        assert_eq!(m * Vec2::new(1.0, 1.0), Vec2::new(4.0, 6.0));
+        assert_eq!(m * Mat2F64::from_cols([[0.0, 1.0], [1.0, 0.0]]), Mat2F64::from_cols([[3.0, 4.0], [1.0, 2.0]]));
+        assert_eq!(m * 2.0, m + m);
+
+        assert_eq!(m.determinant(), -2.0);
+        assert_eq!(m * m.inverse().unwrap(), Mat2F64::identity());
+        assert_eq!(Mat2F64::from_diagonal(Vec2::new(2.0, 4.0)).inverse(), Some(Mat2F64::from_diagonal(Vec2::new(0.5, 0.25))));
+        assert!(Mat2F64::from_cols([[1.0, 2.0], [2.0, 4.0]]).inverse().is_none());
+    }
+}
@@ -2,7 +2,14 @@
 use std::ops;
 use macros::*;
 
-#[derive(Debug, Copy, Clone, OpsAdd, OpsSub, DefaultConstruct)]
+use crate::mat4::Mat4;
+use crate::scalar::Scalar;
+use crate::vec3::Vec3;
+
+// Column major : x1..x3 is the first column
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, OpsAdd, OpsSub, DefaultConstruct)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Mat3<T: Default> {
     pub x1: T,
     pub x2: T,
@@ -16,4 +23,171 @@ pub struct Mat3<T: Default> {
 }
 
 pub type Mat3F32 = Mat3<f32>;
-pub type Mat3F64 = Mat3<f64>;
\ No newline at end of file
+pub type Mat3F64 = Mat3<f64>;
+
+impl<T: Default + Copy + From<u8>> Mat3<T> {
+    pub fn identity() -> Self {
+        Self::from_diagonal(Vec3::new(T::from(1), T::from(1), T::from(1)))
+    }
+
+    pub fn from_diagonal(diagonal: Vec3<T>) -> Self {
+        let zero = T::from(0);
+        Self::new(
+            diagonal.x, zero, zero,
+            zero, diagonal.y, zero,
+            zero, zero, diagonal.z)
+    }
+}
+
+impl<T: Default + Copy> Mat3<T> {
+    pub fn from_cols(cols: [[T; 3]; 3]) -> Self {
+        let [x, y, z] = cols;
+        Self::new(
+            x[0], x[1], x[2],
+            y[0], y[1], y[2],
+            z[0], z[1], z[2])
+    }
+
+    pub fn to_cols(&self) -> [[T; 3]; 3] {
+        [
+            [self.x1, self.x2, self.x3],
+            [self.y1, self.y2, self.y3],
+            [self.z1, self.z2, self.z3],
+        ]
+    }
+
+    // Elements in memory order. Shaders expect mat3 columns padded to 16 bytes : convert to Mat4 before uploading.
+    pub fn to_array(&self) -> [T; 9] {
+        [self.x1, self.x2, self.x3, self.y1, self.y2, self.y3, self.z1, self.z2, self.z3]
+    }
+
+    pub fn col(&self, index: usize) -> Vec3<T> {
+        let [x, y, z] = self.to_cols()[index];
+        Vec3::new(x, y, z)
+    }
+
+    pub fn row(&self, index: usize) -> Vec3<T> {
+        let cols = self.to_cols();
+        Vec3::new(cols[0][index], cols[1][index], cols[2][index])
+    }
+
+    pub fn transpose(&self) -> Self {
+        Self::new(
+            self.x1, self.y1, self.z1,
+            self.x2, self.y2, self.z2,
+            self.x3, self.y3, self.z3)
+    }
+}
+
+fn cross<T: Scalar>(a: [T; 3], b: [T; 3]) -> [T; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn dot<T: Scalar>(a: [T; 3], b: [T; 3]) -> T {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+impl<T: Scalar> Mat3<T> {
+    pub fn determinant(&self) -> T {
+        let [x, y, z] = self.to_cols();
+        dot(x, cross(y, z))
+    }
+
+    // None if the matrix is singular
+    pub fn inverse(&self) -> Option<Self> {
+        let [x, y, z] = self.to_cols();
+        // Rows of the inverse are orthogonal to two columns of the matrix
+        let rows = [cross(y, z), cross(z, x), cross(x, y)];
+        let determinant = dot(x, rows[0]);
+        if determinant == T::zero() {
+            return None;
+        }
+        let inv_det = T::one() / determinant;
+        Some(Self::from_cols(rows).transpose() * inv_det)
+    }
+}
+
+impl<T: Default + Copy + ops::Add<Output=T> + ops::Mul<Output=T>> ops::Mul<Mat3<T>> for Mat3<T> {
+    type Output = Mat3<T>;
+
+    fn mul(self, rhs: Mat3<T>) -> Self::Output {
+        let [x, y, z] = rhs.to_cols();
+        let [x, y, z] = [x, y, z].map(|col| self * Vec3::new(col[0], col[1], col[2]));
+        Self::new(
+            x.x, x.y, x.z,
+            y.x, y.y, y.z,
+            z.x, z.y, z.z)
+    }
+}
+
+impl<T: Default + Copy + ops::Add<Output=T> + ops::Mul<Output=T>> ops::Mul<Vec3<T>> for Mat3<T> {
+    type Output = Vec3<T>;
+
+    fn mul(self, rhs: Vec3<T>) -> Self::Output {
+        Vec3::new(
+            self.x1 * rhs.x + self.y1 * rhs.y + self.z1 * rhs.z,
+            self.x2 * rhs.x + self.y2 * rhs.y + self.z2 * rhs.z,
+            self.x3 * rhs.x + self.y3 * rhs.y + self.z3 * rhs.z)
+    }
+}
+
+impl<T: Default + Copy + ops::Mul<Output=T>> ops::Mul<T> for Mat3<T> {
+    type Output = Mat3<T>;
+
+    fn mul(self, rhs: T) -> Self::Output {
+        let [x, y, z] = self.to_cols().map(|col| col.map(|value| value * rhs));
+        Self::from_cols([x, y, z])
+    }
+}
+
+// Upper left 3x3 part, dropping translation
+impl<T: Default + Copy> From<Mat4<T>> for Mat3<T> {
+    fn from(m: Mat4<T>) -> Self {
+        Self::new(
+            m.x1, m.x2, m.x3,
+            m.y1, m.y2, m.y3,
+            m.z1, m.z2, m.z3)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mat3::Mat3F64;
+    use crate::mat4::Mat4F64;
+    use crate::vec3::Vec3;
+
+    fn assert_near(a: Mat3F64, b: Mat3F64) {
+        assert!(a.to_array().iter().zip(b.to_array()).all(|(a, b)| (a - b).abs() < 1e-9), "{a:?} != {b:?}");
+    }
+
+    #[test]
+    fn mat3_test() {
+        let m = Mat3F64::from_cols([[2.0, 0.0, 1.0], [1.0, 3.0, 0.0], [0.0, 1.0, 4.0]]);
+        assert_eq!(m.col(1), Vec3::new(1.0, 3.0, 0.0));
+        assert_eq!(m.row(2), Vec3::new(1.0, 0.0, 4.0));
+        assert_eq!(m.transpose().transpose(), m);
+        assert_eq!(m.transpose().col(2), m.row(2));
+        assert_eq!(m * Mat3F64::identity(), m);
+        assert_eq!(Mat3F64::identity() * m, m);
+        assert_eq!(m * Vec3::new(1.0, 0.0, 0.0), m.col(0));
+        assert_eq!(m * Vec3::new(1.0, 2.0, 3.0), Vec3::new(4.0, 9.0, 13.0));
+
+        // (AB)^T = B^T A^T
+        let n = Mat3F64::from_cols([[1.0, -2.0, 0.5], [0.0, 1.0, 2.0], [3.0, 0.0, -1.0]]);
+        assert_eq!((m * n).transpose(), n.transpose() * m.transpose());
+        assert_eq!((m * n) * Vec3::new(1.0, 2.0, 3.0), m * (n * Vec3::new(1.0, 2.0, 3.0)));
+
+        assert_eq!(m.determinant(), 25.0);
+        assert_eq!(m.determinant(), m.transpose().determinant());
+        assert_near(m * m.inverse().unwrap(), Mat3F64::identity());
+        assert_near(m.inverse().unwrap() * m, Mat3F64::identity());
+        assert_near((m * n).inverse().unwrap(), n.inverse().unwrap() * m.inverse().unwrap());
+        assert!(Mat3F64::from_cols([[1.0, 2.0, 3.0], [2.0, 4.0, 6.0], [0.0, 1.0, 0.0]]).inverse().is_none());
+
+        assert_eq!(Mat3F64::from(Mat4F64::identity()), Mat3F64::identity());
+    }
+}
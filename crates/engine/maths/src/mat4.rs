@@ -2,7 +2,15 @@
 use std::ops;
 use macros::*;
 
-#[derive(Debug, Copy, Clone, OpsAdd, OpsSub, DefaultConstruct)]
+use crate::mat3::Mat3;
+use crate::scalar::Scalar;
+use crate::simd;
+use crate::vec4::Vec4;
+
+// Column major : x1..x4 is the first column
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, OpsAdd, OpsSub, DefaultConstruct)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Mat4<T: Default> {
     pub x1: T,
     pub x2: T,
@@ -23,4 +31,265 @@ pub struct Mat4<T: Default> {
 }
 
 pub type Mat4F32 = Mat4<f32>;
-pub type Mat4F64 = Mat4<f64>;
\ No newline at end of file
+pub type Mat4F64 = Mat4<f64>;
+
+impl<T: Default + Copy + From<u8>> Mat4<T> {
+    pub fn identity() -> Self {
+        let (zero, one) = (T::from(0), T::from(1));
+        Self::new(
+            one, zero, zero, zero,
+            zero, one, zero, zero,
+            zero, zero, one, zero,
+            zero, zero, zero, one)
+    }
+}
+
+impl<T: Default + Copy> Mat4<T> {
+    pub fn from_cols(cols: [[T; 4]; 4]) -> Self {
+        let [x, y, z, w] = cols;
+        Self::new(
+            x[0], x[1], x[2], x[3],
+            y[0], y[1], y[2], y[3],
+            z[0], z[1], z[2], z[3],
+            w[0], w[1], w[2], w[3])
+    }
+
+    pub fn to_cols(&self) -> [[T; 4]; 4] {
+        [
+            [self.x1, self.x2, self.x3, self.x4],
+            [self.y1, self.y2, self.y3, self.y4],
+            [self.z1, self.z2, self.z3, self.z4],
+            [self.w1, self.w2, self.w3, self.w4],
+        ]
+    }
+
+    // Elements in memory order, as expected by shaders
+    pub fn to_array(&self) -> [T; 16] {
+        let [x, y, z, w] = self.to_cols();
+        [
+            x[0], x[1], x[2], x[3],
+            y[0], y[1], y[2], y[3],
+            z[0], z[1], z[2], z[3],
+            w[0], w[1], w[2], w[3],
+        ]
+    }
+
+    pub fn col(&self, index: usize) -> Vec4<T> {
+        let [x, y, z, w] = self.to_cols()[index];
+        Vec4::new(x, y, z, w)
+    }
+
+    pub fn row(&self, index: usize) -> Vec4<T> {
+        let cols = self.to_cols();
+        Vec4::new(cols[0][index], cols[1][index], cols[2][index], cols[3][index])
+    }
+
+    pub fn transpose(&self) -> Self {
+        let [x, y, z, w] = self.to_cols();
+        Self::new(
+            x[0], y[0], z[0], w[0],
+            x[1], y[1], z[1], w[1],
+            x[2], y[2], z[2], w[2],
+            x[3], y[3], z[3], w[3])
+    }
+}
+
+// 2x2 minors of the two first and two last columns, used to compute the determinant and the inverse
+struct Minors<T> {
+    s: [T; 6],
+    c: [T; 6],
+}
+
+impl<T: Scalar> Minors<T> {
+    fn new(a: &[[T; 4]; 4]) -> Self {
+        Self {
+            s: [
+                a[0][0] * a[1][1] - a[1][0] * a[0][1],
+                a[0][0] * a[1][2] - a[1][0] * a[0][2],
+                a[0][0] * a[1][3] - a[1][0] * a[0][3],
+                a[0][1] * a[1][2] - a[1][1] * a[0][2],
+                a[0][1] * a[1][3] - a[1][1] * a[0][3],
+                a[0][2] * a[1][3] - a[1][2] * a[0][3],
+            ],
+            c: [
+                a[2][0] * a[3][1] - a[3][0] * a[2][1],
+                a[2][0] * a[3][2] - a[3][0] * a[2][2],
+                a[2][0] * a[3][3] - a[3][0] * a[2][3],
+                a[2][1] * a[3][2] - a[3][1] * a[2][2],
+                a[2][1] * a[3][3] - a[3][1] * a[2][3],
+                a[2][2] * a[3][3] - a[3][2] * a[2][3],
+            ],
+        }
+    }
+
+    fn determinant(&self) -> T {
+        let Self { s, c } = self;
+        s[0] * c[5] - s[1] * c[4] + s[2] * c[3] + s[3] * c[2] - s[4] * c[1] + s[5] * c[0]
+    }
+}
+
+impl<T: Scalar> Mat4<T> {
+    pub fn determinant(&self) -> T {
+        Minors::new(&self.to_cols()).determinant()
+    }
+
+    // None if the matrix is singular
+    pub fn inverse(&self) -> Option<Self> {
+        let a = self.to_cols();
+        let minors = Minors::new(&a);
+        let determinant = minors.determinant();
+        if determinant == T::zero() {
+            return None;
+        }
+        let Minors { s, c } = minors;
+        let inverse = Self::from_cols([
+            [
+                a[1][1] * c[5] - a[1][2] * c[4] + a[1][3] * c[3],
+                -a[0][1] * c[5] + a[0][2] * c[4] - a[0][3] * c[3],
+                a[3][1] * s[5] - a[3][2] * s[4] + a[3][3] * s[3],
+                -a[2][1] * s[5] + a[2][2] * s[4] - a[2][3] * s[3],
+            ],
+            [
+                -a[1][0] * c[5] + a[1][2] * c[2] - a[1][3] * c[1],
+                a[0][0] * c[5] - a[0][2] * c[2] + a[0][3] * c[1],
+                -a[3][0] * s[5] + a[3][2] * s[2] - a[3][3] * s[1],
+                a[2][0] * s[5] - a[2][2] * s[2] + a[2][3] * s[1],
+            ],
+            [
+                a[1][0] * c[4] - a[1][1] * c[2] + a[1][3] * c[0],
+                -a[0][0] * c[4] + a[0][1] * c[2] - a[0][3] * c[0],
+                a[3][0] * s[4] - a[3][1] * s[2] + a[3][3] * s[0],
+                -a[2][0] * s[4] + a[2][1] * s[2] - a[2][3] * s[0],
+            ],
+            [
+                -a[1][0] * c[3] + a[1][1] * c[1] - a[1][2] * c[0],
+                a[0][0] * c[3] - a[0][1] * c[1] + a[0][2] * c[0],
+                -a[3][0] * s[3] + a[3][1] * s[1] - a[3][2] * s[0],
+                a[2][0] * s[3] - a[2][1] * s[1] + a[2][2] * s[0],
+            ],
+        ]);
+        Some(inverse * (T::one() / determinant))
+    }
+}
+
+impl<T: Default + Copy + ops::Add<Output=T> + ops::Mul<Output=T> + 'static> ops::Mul<Mat4<T>> for Mat4<T> {
+    type Output = Mat4<T>;
+
+    fn mul(self, rhs: Mat4<T>) -> Self::Output {
+        if let Some(result) = simd::mat4_mul(self, rhs) {
+            return result;
+        }
+        let (lhs, rhs) = (self.to_cols(), rhs.to_cols());
+        let mut result = [[T::default(); 4]; 4];
+        for (col, rhs_col) in rhs.iter().enumerate() {
+            for row in 0..4 {
+                result[col][row] = lhs[0][row] * rhs_col[0] + lhs[1][row] * rhs_col[1] + lhs[2][row] * rhs_col[2] + lhs[3][row] * rhs_col[3];
+            }
+        }
+        Self::from_cols(result)
+    }
+}
+
+impl<T: Default + Copy + ops::Add<Output=T> + ops::Mul<Output=T> + 'static> ops::Mul<Vec4<T>> for Mat4<T> {
+    type Output = Vec4<T>;
+
+    fn mul(self, rhs: Vec4<T>) -> Self::Output {
+        if let Some(result) = simd::mat4_transform(self, rhs) {
+            return result;
+        }
+        Vec4::new(
+            self.x1 * rhs.x + self.y1 * rhs.y + self.z1 * rhs.z + self.w1 * rhs.w,
+            self.x2 * rhs.x + self.y2 * rhs.y + self.z2 * rhs.z + self.w2 * rhs.w,
+            self.x3 * rhs.x + self.y3 * rhs.y + self.z3 * rhs.z + self.w3 * rhs.w,
+            self.x4 * rhs.x + self.y4 * rhs.y + self.z4 * rhs.z + self.w4 * rhs.w)
+    }
+}
+
+impl<T: Default + Copy + ops::Mul<Output=T>> ops::Mul<T> for Mat4<T> {
+    type Output = Mat4<T>;
+
+    fn mul(self, rhs: T) -> Self::Output {
+        Self::from_cols(self.to_cols().map(|col| col.map(|value| value * rhs)))
+    }
+}
+
+// Embed a 3x3 matrix, without translation
+impl<T: Default + Copy + From<u8>> From<Mat3<T>> for Mat4<T> {
+    fn from(m: Mat3<T>) -> Self {
+        let (zero, one) = (T::from(0), T::from(1));
+        Self::new(
+            m.x1, m.x2, m.x3, zero,
+            m.y1, m.y2, m.y3, zero,
+            m.z1, m.z2, m.z3, zero,
+            zero, zero, zero, one)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::mem::{size_of, size_of_val};
+
+    use crate::mat3::Mat3F64;
+    use crate::mat4::{Mat4F32, Mat4F64};
+    use crate::vec4::Vec4;
+
+    fn assert_near(a: Mat4F64, b: Mat4F64) {
+        assert!(a.to_array().iter().zip(b.to_array()).all(|(a, b)| (a - b).abs() < 1e-9), "{a:?} != {b:?}");
+    }
+
+    fn translation(x: f64, y: f64, z: f64) -> Mat4F64 {
+        Mat4F64::from_cols([[1.0, 0.0, 0.0, 0.0], [0.0, 1.0, 0.0, 0.0], [0.0, 0.0, 1.0, 0.0], [x, y, z, 1.0]])
+    }
+
+    fn sample() -> Mat4F64 {
+        Mat4F64::from_cols([[2.0, 1.0, 0.0, 3.0], [0.0, 1.0, 4.0, 1.0], [1.0, 0.0, 2.0, 0.0], [5.0, -1.0, 0.0, 1.0]])
+    }
+
+    #[test]
+    fn layout_test() {
+        // Columns are contiguous, as expected by push constants and uniform buffers
+        let m = Mat4F32::from_cols([[0.0, 1.0, 2.0, 3.0], [4.0, 5.0, 6.0, 7.0], [8.0, 9.0, 10.0, 11.0], [12.0, 13.0, 14.0, 15.0]]);
+        assert_eq!(size_of::<Mat4F32>(), 64);
+        let memory: [f32; 16] = unsafe { std::mem::transmute(m) };
+        assert_eq!(memory, m.to_array());
+        assert_eq!(memory, std::array::from_fn(|i| i as f32));
+        assert_eq!(size_of_val(&m.to_array()), size_of::<Mat4F32>());
+    }
+
+    #[test]
+    fn mul_test() {
+        let m = sample();
+        assert_eq!(m * Mat4F64::identity(), m);
+        assert_eq!(Mat4F64::identity() * m, m);
+        assert_eq!(m.row(1), Vec4::new(1.0, 1.0, 0.0, -1.0));
+        assert_eq!(m.col(3), Vec4::new(5.0, -1.0, 0.0, 1.0));
+        assert_eq!(m * Vec4::new(0.0, 0.0, 1.0, 0.0), m.col(2));
+        assert_eq!(m * Vec4::new(1.0, 1.0, 1.0, 1.0), Vec4::new(8.0, 1.0, 6.0, 5.0));
+
+        // Right-most transform is applied first
+        let point = Vec4::new(1.0, 2.0, 3.0, 1.0);
+        assert_eq!(translation(1.0, 0.0, 0.0) * Mat4F64::from(Mat3F64::identity() * 2.0) * point, Vec4::new(3.0, 4.0, 6.0, 1.0));
+        assert_eq!((m * translation(1.0, 2.0, 3.0)) * point, m * (translation(1.0, 2.0, 3.0) * point));
+        assert_eq!((m * translation(1.0, 2.0, 3.0)).transpose(), translation(1.0, 2.0, 3.0).transpose() * m.transpose());
+        assert_eq!(m * 2.0, m + m);
+        assert_eq!(m.transpose().transpose(), m);
+    }
+
+    #[test]
+    fn inverse_test() {
+        let m = sample();
+        assert_eq!(Mat4F64::identity().determinant(), 1.0);
+        assert_eq!(m.determinant(), m.transpose().determinant());
+        assert_eq!((m * translation(1.0, 2.0, 3.0)).determinant(), m.determinant());
+        assert_eq!(m.determinant(), 4.0);
+        assert_near(m * m.inverse().unwrap(), Mat4F64::identity());
+        assert_near(m.inverse().unwrap() * m, Mat4F64::identity());
+        assert_near(m.inverse().unwrap().inverse().unwrap(), m);
+        assert_eq!(translation(1.0, 2.0, 3.0).inverse(), Some(translation(-1.0, -2.0, -3.0)));
+        assert!(Mat4F64::from(Mat3F64::from_cols([[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [1.0, 1.0, 0.0]])).inverse().is_none());
+
+        let f = Mat4F32::from_cols([[1.0, 2.0, 0.0, 0.0], [0.0, 1.0, 0.0, 0.0], [0.0, 0.0, 4.0, 0.0], [3.0, 0.0, 0.0, 1.0]]);
+        let identity = f * f.inverse().unwrap();
+        assert!(identity.to_array().iter().zip(Mat4F32::identity().to_array()).all(|(a, b)| (a - b).abs() < 1e-6));
+    }
+}
@@ -0,0 +1,290 @@
+use crate::scalar::Float;
+
+// Coherent noise in 2 to 4 dimensions. Lattice values come from integer hashing and only IEEE exact float
+// operations are used (no trigonometry), so outputs are identical on every platform for a given seed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Noise {
+    seed: u32,
+}
+
+impl Noise {
+    pub fn new(seed: u32) -> Self {
+        Self { seed }
+    }
+
+    pub fn seed(&self) -> u32 {
+        self.seed
+    }
+
+    // Smoothly interpolated random values at integer coordinates, in [-1, 1]
+    pub fn value<T: Float, const N: usize>(&self, point: [T; N]) -> T {
+        let (cell, offset) = split(point);
+        lattice(offset, |corner| {
+            let hash = self.hash(add(cell, corner));
+            T::from_f64(hash as f64 / u32::MAX as f64) * T::from(2) - T::one()
+        })
+    }
+
+    // Gradient noise in [-1, 1], zero at integer coordinates
+    pub fn perlin<T: Float, const N: usize>(&self, point: [T; N]) -> T {
+        let (cell, offset) = split(point);
+        let value = lattice(offset, |corner| {
+            let gradient = gradient::<T, N>(self.hash(add(cell, corner)));
+            (0..N).fold(T::zero(), |sum, i| sum + gradient[i] * (offset[i] - T::from_f64(corner[i] as f64)))
+        });
+        // 4D gradients have three +-1 components against two below, so dot products can be sqrt(3/2) times
+        // larger. 0.85 (a bit above 1 / sqrt(3/2)) brings the sampled peaks back to those of 3D.
+        let value = if N == 4 { value * T::from_f64(0.85) } else { value };
+        // Peaks are not bounded exactly by these factors, the rare values past 1 are clamped
+        clamp_unit(value)
+    }
+
+    // Gradient noise on a simplex grid : fewer artifacts and cheaper than Perlin in higher dimensions.
+    // In [-1, 1].
+    pub fn simplex<T: Float, const N: usize>(&self, point: [T; N]) -> T {
+        let n = T::from_f64(N as f64);
+        let root = (n + T::one()).sqrt();
+        let skew = (root - T::one()) / n;
+        let unskew = (T::one() - T::one() / root) / n;
+
+        // Simplex cell containing the point, and position relative to its first vertex
+        let sum = point.iter().fold(T::zero(), |sum, value| sum + *value);
+        let cell = point.map(|value| floor(value + sum * skew));
+        let cell_sum = cell.iter().fold(T::zero(), |sum, value| sum + T::from_f64(*value as f64));
+        let mut origin = point;
+        for i in 0..N {
+            origin[i] = point[i] - (T::from_f64(cell[i] as f64) - cell_sum * unskew);
+        }
+
+        // Vertices are reached by stepping along the axes, largest offset first
+        let mut order: [usize; N] = std::array::from_fn(|i| i);
+        order.sort_by(|a, b| origin[*b].partial_cmp(&origin[*a]).unwrap_or(std::cmp::Ordering::Equal).then(a.cmp(b)));
+
+        let radius = T::from_f64(if N == 2 { 0.5 } else { 0.6 });
+        let mut corner = [0i64; N];
+        let mut value = T::zero();
+        for vertex in 0..=N {
+            if vertex > 0 {
+                corner[order[vertex - 1]] = 1;
+            }
+            let mut position = origin;
+            for i in 0..N {
+                position[i] = origin[i] - T::from_f64(corner[i] as f64) + T::from_f64(vertex as f64) * unskew;
+            }
+            let falloff = radius - position.iter().fold(T::zero(), |sum, value| sum + *value * *value);
+            if falloff > T::zero() {
+                let gradient = gradient::<T, N>(self.hash(add(cell, corner)));
+                let dot = (0..N).fold(T::zero(), |sum, i| sum + gradient[i] * position[i]);
+                let falloff = falloff * falloff;
+                value = value + falloff * falloff * dot;
+            }
+        }
+        // Normalization factors of the reference implementation (Gustavson, "Simplex noise demystified"),
+        // measured so that peaks get close to 1. They are not an exact bound : the result is clamped.
+        let scale = match N { 2 => { 70.0 } 3 => { 32.0 } _ => { 27.0 } };
+        clamp_unit(value * T::from_f64(scale))
+    }
+
+    // Cellular noise : distance to the nearest of random feature points, one per cell. In [0, sqrt(N)].
+    pub fn worley<T: Float, const N: usize>(&self, point: [T; N]) -> T {
+        let (cell, offset) = split(point);
+        let mut nearest = T::INFINITY;
+        for neighbour in 0..3usize.pow(N as u32) {
+            // Base 3 digits give the neighbour offset in [-1, 1] on each axis
+            let corner: [i64; N] = std::array::from_fn(|i| (neighbour / 3usize.pow(i as u32) % 3) as i64 - 1);
+            let hash = self.hash(add(cell, corner));
+            let mut distance = T::zero();
+            for i in 0..N {
+                let feature = T::from_f64(mix(hash.wrapping_add(i as u32)) as f64 / u32::MAX as f64);
+                let delta = T::from_f64(corner[i] as f64) + feature - offset[i];
+                distance = distance + delta * delta;
+            }
+            if distance < nearest {
+                nearest = distance;
+            }
+        }
+        nearest.sqrt()
+    }
+
+    fn hash<const N: usize>(&self, cell: [i64; N]) -> u32 {
+        cell.iter().fold(mix(self.seed), |hash, coordinate| mix(hash ^ *coordinate as u32))
+    }
+}
+
+// Integer hash with good avalanche, from https://nullprogram.com/blog/2018/07/31
+fn mix(mut x: u32) -> u32 {
+    x ^= x >> 16;
+    x = x.wrapping_mul(0x7feb352d);
+    x ^= x >> 15;
+    x = x.wrapping_mul(0x846ca68b);
+    x ^= x >> 16;
+    x
+}
+
+fn clamp_unit<T: Float>(value: T) -> T {
+    if value > T::one() {
+        T::one()
+    } else if value < -T::one() {
+        -T::one()
+    } else {
+        value
+    }
+}
+
+fn floor<T: Float>(value: T) -> i64 {
+    value.floor().to_f64() as i64
+}
+
+fn split<T: Float, const N: usize>(point: [T; N]) -> ([i64; N], [T; N]) {
+    let cell = point.map(floor);
+    let mut offset = point;
+    for i in 0..N {
+        offset[i] = point[i] - T::from_f64(cell[i] as f64);
+    }
+    (cell, offset)
+}
+
+fn add<const N: usize>(a: [i64; N], b: [i64; N]) -> [i64; N] {
+    std::array::from_fn(|i| a[i] + b[i])
+}
+
+// Gradient with +-1 components. In 3D and more, one component is zero : the directions point to the cube edges.
+fn gradient<T: Float, const N: usize>(hash: u32) -> [T; N] {
+    let zero = if N > 2 { (hash >> N) as usize % N } else { N };
+    std::array::from_fn(|i| {
+        if i == zero {
+            T::zero()
+        } else if hash >> i & 1 == 1 {
+            -T::one()
+        } else {
+            T::one()
+        }
+    })
+}
+
+// Blend the values at the 2^N corners of a cell, with a C2 continuous fade
+fn lattice<T: Float, const N: usize>(offset: [T; N], corner_value: impl Fn([i64; N]) -> T) -> T {
+    const { assert!(N >= 2 && N <= 4, "noise is defined in 2 to 4 dimensions") };
+    let mut values = [T::zero(); 16];
+    for (index, value) in values.iter_mut().enumerate().take(1 << N) {
+        *value = corner_value(std::array::from_fn(|i| (index >> i & 1) as i64));
+    }
+    // Collapse the highest axis first : pairs of corners differing on it are 2^axis apart
+    for axis in (0..N).rev() {
+        let t = fade(offset[axis]);
+        for index in 0..1 << axis {
+            values[index] = values[index] + (values[index + (1 << axis)] - values[index]) * t;
+        }
+    }
+    values[0]
+}
+
+fn fade<T: Float>(t: T) -> T {
+    t * t * t * (t * (t * T::from(6) - T::from(15)) + T::from(10))
+}
+
+/*
+FRACTAL
+ */
+
+// Sum of octaves of a noise, each one with a higher frequency and a lower amplitude
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Fractal {
+    pub octaves: u32,
+    // Frequency multiplier between octaves
+    pub lacunarity: f64,
+    // Amplitude multiplier between octaves
+    pub gain: f64,
+}
+
+impl Default for Fractal {
+    fn default() -> Self {
+        Self { octaves: 5, lacunarity: 2.0, gain: 0.5 }
+    }
+}
+
+impl Fractal {
+    // Fractional Brownian motion. Normalized : stays in the range of the noise.
+    pub fn fbm<T: Float, const N: usize>(&self, point: [T; N], noise: impl Fn([T; N]) -> T) -> T {
+        self.sum(point, noise)
+    }
+
+    // Sharp crests where the noise crosses zero, in [0, 1] for a noise in [-1, 1]
+    pub fn ridged<T: Float, const N: usize>(&self, point: [T; N], noise: impl Fn([T; N]) -> T) -> T {
+        self.sum(point, |point| {
+            let ridge = T::one() - noise(point).abs();
+            ridge * ridge
+        })
+    }
+
+    fn sum<T: Float, const N: usize>(&self, point: [T; N], octave: impl Fn([T; N]) -> T) -> T {
+        let (mut frequency, mut amplitude) = (T::one(), T::one());
+        let (mut sum, mut total) = (T::zero(), T::zero());
+        for _ in 0..self.octaves.max(1) {
+            sum = sum + octave(point.map(|value| value * frequency)) * amplitude;
+            total = total + amplitude;
+            frequency = frequency * T::from_f64(self.lacunarity);
+            amplitude = amplitude * T::from_f64(self.gain);
+        }
+        sum / total
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::noise::{Fractal, Noise};
+    use crate::random::Pcg32;
+
+    fn points<const N: usize>(count: usize) -> Vec<[f64; N]> {
+        let mut rng = Pcg32::from_seed(3);
+        (0..count).map(|_| std::array::from_fn(|_| rng.range_f64(-50.0..50.0))).collect()
+    }
+
+    fn check_range<const N: usize>(noise: &Noise) {
+        for point in points::<N>(5000) {
+            for value in [noise.value(point), noise.perlin(point), noise.simplex(point)] {
+                assert!((-1.0..=1.0).contains(&value), "{value} at {point:?}");
+            }
+            let worley = noise.worley(point);
+            assert!(worley >= 0.0 && worley <= (N as f64).sqrt(), "{worley} at {point:?}");
+        }
+    }
+
+    #[test]
+    fn range_test() {
+        let noise = Noise::new(12);
+        check_range::<2>(&noise);
+        check_range::<3>(&noise);
+        check_range::<4>(&noise);
+
+        // Perlin is zero on the lattice, value noise is continuous across cells
+        assert_eq!(noise.perlin([3.0, -2.0, 7.0]), 0.0);
+        let (a, b) = (noise.value([1.0f64 - 1e-9, 0.5]), noise.value([1.0 + 1e-9, 0.5]));
+        assert!((a - b).abs() < 1e-6);
+
+        let fractal = Fractal::default();
+        for point in points::<3>(500) {
+            assert!((-1.0..=1.0).contains(&fractal.fbm(point, |p| noise.simplex(p))));
+            assert!((0.0..=1.0).contains(&fractal.ridged(point, |p| noise.perlin(p))));
+        }
+    }
+
+    #[test]
+    fn determinism_test() {
+        // Generated once : any change here breaks saved procedural content
+        let noise = Noise::new(1234);
+        let values = [
+            noise.value([0.3, 1.7]),
+            noise.perlin([0.3, 1.7, -2.2]),
+            noise.simplex([0.3, 1.7, -2.2, 5.1]),
+            noise.worley([0.3, 1.7, -2.2]),
+            Fractal::default().fbm([0.3f32, 1.7], |p| noise.simplex(p)) as f64,
+        ];
+        let bits: Vec<u64> = values.iter().map(|value| value.to_bits()).collect();
+        assert_eq!(bits, vec![13822610238635480690, 13826137042501470067, 4580964860307191950, 4603649111067624271, 4596230408424652800]);
+
+        assert_ne!(Noise::new(1).simplex([0.3, 1.7]), Noise::new(2).simplex([0.3, 1.7]));
+    }
+}
@@ -0,0 +1,193 @@
+use crate::rect2d::RectU32;
+
+// Packs rectangles in a fixed area, for texture atlases or font glyphs. Skyline bottom-left heuristic :
+// the top of the placed rects is kept as a list of horizontal segments, and each rect goes where its
+// bottom edge ends up the lowest (y grows downward). Fast, and tight when rects have similar heights.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SkylinePacker {
+    width: u32,
+    height: u32,
+    // Empty space between packed rects
+    padding: u32,
+    skyline: Vec<Segment>,
+    used_area: u64,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Segment {
+    x: u32,
+    y: u32,
+    width: u32,
+}
+
+impl SkylinePacker {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self::with_padding(width, height, 0)
+    }
+
+    // Keeps the given space between rects, to avoid bleeding when sampling with filtering
+    pub fn with_padding(width: u32, height: u32, padding: u32) -> Self {
+        Self { width, height, padding, skyline: vec![Segment { x: 0, y: 0, width }], used_area: 0 }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::with_padding(self.width, self.height, self.padding);
+    }
+
+    // Fraction of the area covered by packed rects, padding excluded
+    pub fn occupancy(&self) -> f32 {
+        self.used_area as f32 / (self.width as u64 * self.height as u64).max(1) as f32
+    }
+
+    // None if there is no room left for the rect. Empty rects (like a space glyph) take no room.
+    pub fn pack(&mut self, width: u32, height: u32) -> Option<RectU32> {
+        if width == 0 || height == 0 {
+            return Some(RectU32::rect(0, 0, width, height));
+        }
+        let (padded_width, padded_height) = (width.checked_add(self.padding)?, height.checked_add(self.padding)?);
+        let mut best: Option<(usize, u32)> = None;
+        for index in 0..self.skyline.len() {
+            let Some(y) = self.fit(index, padded_width, padded_height) else { continue; };
+            // Lowest bottom edge, then narrowest segment to waste less space
+            let better = match best {
+                None => { true }
+                Some((best_index, best_y)) => {
+                    y < best_y || (y == best_y && self.skyline[index].width < self.skyline[best_index].width)
+                }
+            };
+            if better {
+                best = Some((index, y));
+            }
+        }
+
+        let (index, y) = best?;
+        let x = self.skyline[index].x;
+        self.insert(index, Segment { x, y: y + padded_height, width: padded_width });
+        self.used_area += width as u64 * height as u64;
+        Some(RectU32::rect(x, y, width, height))
+    }
+
+    // Pack many rects, taller ones first for a tighter result. Placements are returned in input order.
+    pub fn pack_all(&mut self, sizes: &[(u32, u32)]) -> Vec<Option<RectU32>> {
+        let mut order: Vec<usize> = (0..sizes.len()).collect();
+        order.sort_by_key(|index| std::cmp::Reverse((sizes[*index].1, sizes[*index].0)));
+        let mut rects = vec![None; sizes.len()];
+        for index in order {
+            let (width, height) = sizes[index];
+            rects[index] = self.pack(width, height);
+        }
+        rects
+    }
+
+    // Top of a rect whose left edge is at the start of the segment, if it fits
+    fn fit(&self, index: usize, width: u32, height: u32) -> Option<u32> {
+        let x = self.skyline[index].x;
+        // The padding is not needed past the right and bottom borders
+        let right = x.checked_add(width).filter(|right| *right as u64 <= self.width as u64 + self.padding as u64)?;
+        let mut y = 0;
+        for segment in self.skyline[index..].iter().take_while(|segment| segment.x < right) {
+            y = y.max(segment.y);
+        }
+        y.checked_add(height).filter(|bottom| *bottom as u64 <= self.height as u64 + self.padding as u64).map(|_| y)
+    }
+
+    fn insert(&mut self, index: usize, segment: Segment) {
+        self.skyline.insert(index, segment);
+        let right = segment.x + segment.width;
+
+        // Cut the segments now under the new one
+        let next = index + 1;
+        while next < self.skyline.len() && self.skyline[next].x < right {
+            let covered = right - self.skyline[next].x;
+            if covered < self.skyline[next].width {
+                self.skyline[next].x = right;
+                self.skyline[next].width -= covered;
+                break;
+            }
+            self.skyline.remove(next);
+        }
+
+        // Merge neighbours at the same height
+        self.skyline.dedup_by(|next, previous| {
+            if previous.y == next.y {
+                previous.width += next.width;
+                true
+            } else {
+                false
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::packer::SkylinePacker;
+    use crate::random::Pcg32;
+    use crate::rect2d::RectU32;
+
+    fn check_layout(packer: &SkylinePacker, rects: &[RectU32], padding: u32) {
+        let bounds = RectU32::rect(0, 0, packer.width(), packer.height());
+        for (i, a) in rects.iter().enumerate() {
+            assert!(bounds.contains(a), "{a:?} out of bounds");
+            for b in &rects[i + 1..] {
+                let padded = RectU32::new(b.min_x().saturating_sub(padding), b.min_y().saturating_sub(padding), b.max_x() + padding, b.max_y() + padding);
+                assert!(!a.intersects(&padded), "{a:?} is too close to {b:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn packer_test() {
+        // Exact tiling
+        let mut packer = SkylinePacker::new(64, 64);
+        let rects: Vec<RectU32> = (0..4).map(|_| packer.pack(32, 32).unwrap()).collect();
+        check_layout(&packer, &rects, 0);
+        assert_eq!(packer.occupancy(), 1.0);
+        assert!(packer.pack(1, 1).is_none());
+
+        packer.clear();
+        assert!(packer.pack(65, 1).is_none());
+        assert_eq!(packer.pack(0, 8), Some(RectU32::rect(0, 0, 0, 8)));
+        assert_eq!(packer.pack(64, 10), Some(RectU32::rect(0, 0, 64, 10)));
+        // Goes on the lowest spot, at the left
+        assert_eq!(packer.pack(10, 10), Some(RectU32::rect(0, 10, 10, 10)));
+        assert_eq!(packer.pack(20, 5), Some(RectU32::rect(10, 10, 20, 5)));
+
+        // Sizes close to the integer limit
+        let mut packer = SkylinePacker::with_padding(u32::MAX, 16, 2);
+        assert_eq!(packer.pack(10, 10), Some(RectU32::rect(0, 0, 10, 10)));
+        assert_eq!(packer.pack(u32::MAX - 5, 3), Some(RectU32::rect(0, 12, u32::MAX - 5, 3)));
+        assert!(packer.pack(u32::MAX - 5, 3).is_none());
+        let mut packer = SkylinePacker::with_padding(16, u32::MAX, 2);
+        assert_eq!(packer.pack(10, 10), Some(RectU32::rect(0, 0, 10, 10)));
+        assert_eq!(packer.pack(3, u32::MAX - 5), Some(RectU32::rect(12, 0, 3, u32::MAX - 5)));
+        assert!(packer.pack(3, u32::MAX - 5).is_none());
+    }
+
+    #[test]
+    fn random_packer_test() {
+        let mut rng = Pcg32::from_seed(5);
+        let sizes: Vec<(u32, u32)> = (0..300).map(|_| (rng.range_u32(1..40), rng.range_u32(1..40))).collect();
+        for padding in [0, 2] {
+            let mut packer = SkylinePacker::with_padding(512, 512, padding);
+            let rects = packer.pack_all(&sizes);
+            assert!(rects.iter().all(|rect| rect.is_some()));
+            let rects: Vec<RectU32> = rects.into_iter().flatten().collect();
+            for (rect, size) in rects.iter().zip(&sizes) {
+                assert_eq!((rect.width(), rect.height()), *size);
+            }
+            check_layout(&packer, &rects, padding);
+            assert!(packer.occupancy() > 0.4);
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use crate::mat4::Mat4;
+use crate::scalar::Float;
+use crate::vec3::Vec3;
+
+// Camera matrices using Vulkan clip space : depth goes from 0 to 1. View space is right-handed, looking toward -z
+// with y up. The viewport is flipped by the renderer, so y is not inverted here. Angles are in radians.
+impl<T: Float> Mat4<T> {
+    // Near plane maps to depth 0, far plane to 1
+    pub fn perspective(fov_y: T, aspect: T, near: T, far: T) -> Self {
+        let (x, y) = Self::focal(fov_y, aspect);
+        let range = near - far;
+        Self::projection(x, y, far / range, near * far / range)
+    }
+
+    // Near plane maps to depth 1, far plane to 0 : better float precision, to use with a 'greater' depth test
+    pub fn perspective_reversed(fov_y: T, aspect: T, near: T, far: T) -> Self {
+        let (x, y) = Self::focal(fov_y, aspect);
+        let range = far - near;
+        Self::projection(x, y, near / range, near * far / range)
+    }
+
+    // Far plane at infinity, near plane maps to depth 0
+    pub fn perspective_infinite(fov_y: T, aspect: T, near: T) -> Self {
+        let (x, y) = Self::focal(fov_y, aspect);
+        Self::projection(x, y, -T::one(), -near)
+    }
+
+    // Near plane maps to depth 1, infinity to 0
+    pub fn perspective_infinite_reversed(fov_y: T, aspect: T, near: T) -> Self {
+        let (x, y) = Self::focal(fov_y, aspect);
+        Self::projection(x, y, T::zero(), near)
+    }
+
+    // Near plane maps to depth 0, far plane to 1
+    pub fn orthographic(left: T, right: T, bottom: T, top: T, near: T, far: T) -> Self {
+        let (zero, one, two) = (T::zero(), T::one(), T::from(2));
+        let (width, height, depth) = (right - left, top - bottom, near - far);
+        Self::new(
+            two / width, zero, zero, zero,
+            zero, two / height, zero, zero,
+            zero, zero, one / depth, zero,
+            -(right + left) / width, -(top + bottom) / height, near / depth, one)
+    }
+
+    // View matrix of a camera at 'eye' looking at 'target'. 'up' must not be parallel to the view direction.
+    pub fn look_at(eye: Vec3<T>, target: Vec3<T>, up: Vec3<T>) -> Self {
+        Self::look_to(eye, target - eye, up)
+    }
+
+    pub fn look_to(eye: Vec3<T>, direction: Vec3<T>, up: Vec3<T>) -> Self {
+        let forward = direction.normalize();
+        let side = forward.cross(up).normalize();
+        let up = side.cross(forward);
+        let zero = T::zero();
+        Self::new(
+            side.x, up.x, -forward.x, zero,
+            side.y, up.y, -forward.y, zero,
+            side.z, up.z, -forward.z, zero,
+            -side.dot(eye), -up.dot(eye), forward.dot(eye), T::one())
+    }
+
+    fn focal(fov_y: T, aspect: T) -> (T, T) {
+        let y = T::one() / (fov_y / T::from(2)).tan();
+        (y / aspect, y)
+    }
+
+    // Perspective projection writing -z to w
+    fn projection(x: T, y: T, depth_scale: T, depth_offset: T) -> Self {
+        let zero = T::zero();
+        Self::new(
+            x, zero, zero, zero,
+            zero, y, zero, zero,
+            zero, zero, depth_scale, -T::one(),
+            zero, zero, depth_offset, zero)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::FRAC_PI_2;
+
+    use crate::mat4::Mat4F64;
+    use crate::vec3::{Vec3, Vec3F64};
+    use crate::vec4::Vec4;
+
+    // Normalized device coordinates of a view space point
+    fn project(m: &Mat4F64, x: f64, y: f64, z: f64) -> Vec3F64 {
+        let clip = *m * Vec4::new(x, y, z, 1.0);
+        Vec3::new(clip.x / clip.w, clip.y / clip.w, clip.z / clip.w)
+    }
+
+    fn assert_vec_near(a: Vec3F64, b: Vec3F64) {
+        assert!((a.x - b.x).abs() < 1e-9 && (a.y - b.y).abs() < 1e-9 && (a.z - b.z).abs() < 1e-9, "{a:?} != {b:?}");
+    }
+
+    #[test]
+    fn perspective_test() {
+        let (near, far) = (0.1, 100.0);
+        let m = Mat4F64::perspective(FRAC_PI_2, 2.0, near, far);
+        assert_vec_near(project(&m, 0.0, 0.0, -near), Vec3::new(0.0, 0.0, 0.0));
+        assert_vec_near(project(&m, 0.0, 0.0, -far), Vec3::new(0.0, 0.0, 1.0));
+        // 90 degrees vertical fov : the frustum edge is at y = -z. Up stays up.
+        assert_vec_near(project(&m, 2.0, 1.0, -1.0), Vec3::new(1.0, 1.0, project(&m, 0.0, 0.0, -1.0).z));
+
+        let reversed = Mat4F64::perspective_reversed(FRAC_PI_2, 2.0, near, far);
+        assert_vec_near(project(&reversed, 0.0, 0.0, -near), Vec3::new(0.0, 0.0, 1.0));
+        assert_vec_near(project(&reversed, 0.0, 0.0, -far), Vec3::new(0.0, 0.0, 0.0));
+        assert_vec_near(project(&reversed, -2.0, -1.0, -1.0), Vec3::new(-1.0, -1.0, project(&reversed, 0.0, 0.0, -1.0).z));
+
+        // Depth is monotonic
+        let (a, b) = (project(&m, 0.0, 0.0, -5.0).z, project(&m, 0.0, 0.0, -6.0).z);
+        assert!(a < b);
+        let (a, b) = (project(&reversed, 0.0, 0.0, -5.0).z, project(&reversed, 0.0, 0.0, -6.0).z);
+        assert!(a > b);
+    }
+
+    #[test]
+    fn infinite_test() {
+        let near = 0.5;
+        let m = Mat4F64::perspective_infinite(FRAC_PI_2, 1.0, near);
+        assert_vec_near(project(&m, 0.0, 0.0, -near), Vec3::new(0.0, 0.0, 0.0));
+        assert!(project(&m, 0.0, 0.0, -1e9).z < 1.0);
+        assert!((project(&m, 0.0, 0.0, -1e9).z - 1.0).abs() < 1e-6);
+
+        let reversed = Mat4F64::perspective_infinite_reversed(FRAC_PI_2, 1.0, near);
+        assert_vec_near(project(&reversed, 0.0, 0.0, -near), Vec3::new(0.0, 0.0, 1.0));
+        assert!(project(&reversed, 0.0, 0.0, -1e9).z > 0.0);
+        assert!(project(&reversed, 0.0, 0.0, -1e9).z < 1e-6);
+
+        // Matches the finite projection with a far plane far away
+        let finite = Mat4F64::perspective(FRAC_PI_2, 1.0, near, 1e12);
+        assert_vec_near(project(&finite, 3.0, -2.0, -10.0), project(&m, 3.0, -2.0, -10.0));
+    }
+
+    #[test]
+    fn orthographic_test() {
+        let m = Mat4F64::orthographic(-4.0, 2.0, -1.0, 3.0, 1.0, 11.0);
+        assert_vec_near(project(&m, -4.0, -1.0, -1.0), Vec3::new(-1.0, -1.0, 0.0));
+        assert_vec_near(project(&m, 2.0, 3.0, -11.0), Vec3::new(1.0, 1.0, 1.0));
+        assert_vec_near(project(&m, -1.0, 1.0, -6.0), Vec3::new(0.0, 0.0, 0.5));
+    }
+
+    #[test]
+    fn look_at_test() {
+        let eye = Vec3::new(1.0, 2.0, 3.0);
+        let view = Mat4F64::look_at(eye, Vec3::new(1.0, 2.0, -7.0), Vec3::new(0.0, 1.0, 0.0));
+        // Same orientation as view space : only a translation
+        assert_vec_near(project(&view, 1.0, 2.0, 3.0), Vec3::new(0.0, 0.0, 0.0));
+        assert_vec_near(project(&view, 2.0, 3.0, 0.0), Vec3::new(1.0, 1.0, -3.0));
+
+        // Looking toward +x : right is +z
+        let view = Mat4F64::look_to(eye, Vec3::new(2.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+        assert_vec_near(project(&view, 6.0, 2.0, 3.0), Vec3::new(0.0, 0.0, -5.0));
+        assert_vec_near(project(&view, 1.0, 4.0, 4.0), Vec3::new(1.0, 2.0, 0.0));
+        assert!((view.determinant() - 1.0).abs() < 1e-9);
+
+        // Target ends up in front of the camera, at the center of the screen
+        let target = Vec3::new(-3.0, 0.5, 2.0);
+        let view = Mat4F64::look_at(eye, target, Vec3::new(0.0, 1.0, 0.0));
+        let projection = Mat4F64::perspective(1.0, 1.5, 0.1, 100.0);
+        let ndc = project(&(projection * view), target.x, target.y, target.z);
+        assert!(ndc.x.abs() < 1e-9 && ndc.y.abs() < 1e-9 && ndc.z > 0.0 && ndc.z < 1.0);
+    }
+}
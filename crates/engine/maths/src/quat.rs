@@ -0,0 +1,311 @@
+use std::ops;
+use macros::*;
+
+use crate::mat3::Mat3;
+use crate::mat4::Mat4;
+use crate::scalar::{Float, Scalar};
+use crate::simd;
+use crate::vec3::Vec3;
+
+// Rotation stored as x * i + y * j + z * k + w. Angles are in radians, positive angles rotate counterclockwise
+// when looking toward the origin from the positive side of the axis.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, OpsAdd, OpsSub, DefaultConstruct)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Quat<T: Default> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+    pub w: T,
+}
+
+pub type QuatF32 = Quat<f32>;
+pub type QuatF64 = Quat<f64>;
+
+impl<T: Scalar> Quat<T> {
+    pub fn identity() -> Self {
+        Self::new(T::zero(), T::zero(), T::zero(), T::one())
+    }
+
+    pub fn dot(&self, other: &Self) -> T {
+        self.x * other.x + self.y * other.y + self.z * other.z + self.w * other.w
+    }
+
+    pub fn length_squared(&self) -> T {
+        self.dot(self)
+    }
+
+    // Inverse of a unit quaternion
+    pub fn conjugate(&self) -> Self {
+        Self::new(-self.x, -self.y, -self.z, self.w)
+    }
+
+    // None for the zero quaternion
+    pub fn inverse(&self) -> Option<Self> {
+        let length_squared = self.length_squared();
+        if length_squared == T::zero() {
+            return None;
+        }
+        Some(self.conjugate() * (T::one() / length_squared))
+    }
+
+    // The quaternion must be normalized
+    pub fn to_mat3(&self) -> Mat3<T> {
+        let Self { x, y, z, w } = *self;
+        let (one, two) = (T::one(), T::from(2));
+        Mat3::new(
+            one - two * (y * y + z * z), two * (x * y + w * z), two * (x * z - w * y),
+            two * (x * y - w * z), one - two * (x * x + z * z), two * (y * z + w * x),
+            two * (x * z + w * y), two * (y * z - w * x), one - two * (x * x + y * y))
+    }
+
+    pub fn to_mat4(&self) -> Mat4<T> {
+        Mat4::from(self.to_mat3())
+    }
+}
+
+impl<T: Float> Quat<T> {
+    pub fn from_axis_angle(axis: Vec3<T>, angle: T) -> Self {
+        let length = axis.length();
+        let half_angle = angle / T::from(2);
+        let s = half_angle.sin() / length;
+        Self::new(axis.x * s, axis.y * s, axis.z * s, half_angle.cos())
+    }
+
+    pub fn from_rotation_x(angle: T) -> Self {
+        Self::from_axis_angle(Vec3::new(T::one(), T::zero(), T::zero()), angle)
+    }
+
+    pub fn from_rotation_y(angle: T) -> Self {
+        Self::from_axis_angle(Vec3::new(T::zero(), T::one(), T::zero()), angle)
+    }
+
+    pub fn from_rotation_z(angle: T) -> Self {
+        Self::from_axis_angle(Vec3::new(T::zero(), T::zero(), T::one()), angle)
+    }
+
+    // Rotate around the x axis, then y, then z (extrinsic rotations)
+    pub fn from_euler(x: T, y: T, z: T) -> Self {
+        Self::from_rotation_z(z) * Self::from_rotation_y(y) * Self::from_rotation_x(x)
+    }
+
+    // Angles given to from_euler. y is kept in [-pi/2, pi/2] : other angles are in [-pi, pi].
+    pub fn to_euler(&self) -> Vec3<T> {
+        let m = self.to_mat3();
+        let sin_y = -m.x3;
+        if sin_y.abs() >= T::one() - T::from_f64(1e-6) {
+            // Gimbal lock : only x - z or x + z can be recovered, put everything in z
+            let y = if sin_y > T::zero() { T::PI / T::from(2) } else { -T::PI / T::from(2) };
+            return Vec3::new(T::zero(), y, (-m.y1).atan2(m.y2));
+        }
+        Vec3::new(m.y3.atan2(m.z3), sin_y.asin(), m.x2.atan2(m.x1))
+    }
+
+    // The matrix must be a pure rotation
+    pub fn from_mat3(m: &Mat3<T>) -> Self {
+        let (one, two, four) = (T::one(), T::from(2), T::from(4));
+        let trace = m.x1 + m.y2 + m.z3;
+        let quat = if trace > T::zero() {
+            let s = (trace + one).sqrt() * two;
+            Self::new((m.y3 - m.z2) / s, (m.z1 - m.x3) / s, (m.x2 - m.y1) / s, s / four)
+        } else if m.x1 > m.y2 && m.x1 > m.z3 {
+            let s = (one + m.x1 - m.y2 - m.z3).sqrt() * two;
+            Self::new(s / four, (m.y1 + m.x2) / s, (m.z1 + m.x3) / s, (m.y3 - m.z2) / s)
+        } else if m.y2 > m.z3 {
+            let s = (one + m.y2 - m.x1 - m.z3).sqrt() * two;
+            Self::new((m.y1 + m.x2) / s, s / four, (m.z2 + m.y3) / s, (m.z1 - m.x3) / s)
+        } else {
+            let s = (one + m.z3 - m.x1 - m.y2).sqrt() * two;
+            Self::new((m.z1 + m.x3) / s, (m.z2 + m.y3) / s, s / four, (m.x2 - m.y1) / s)
+        };
+        quat.normalize()
+    }
+
+    pub fn length(&self) -> T {
+        self.length_squared().sqrt()
+    }
+
+    pub fn normalize(&self) -> Self {
+        *self * (T::one() / self.length())
+    }
+
+    // Angle of the rotation, in [0, 2pi]
+    pub fn angle(&self) -> T {
+        T::from(2) * clamp_unit(self.w).acos()
+    }
+
+    // Normalized linear interpolation, following the shortest path
+    pub fn nlerp(&self, other: &Self, t: T) -> Self {
+        let other = if self.dot(other) < T::zero() { -*other } else { *other };
+        (*self * (T::one() - t) + other * t).normalize()
+    }
+
+    // Spherical interpolation at constant angular speed, following the shortest path
+    pub fn slerp(&self, other: &Self, t: T) -> Self {
+        let mut cos = self.dot(other);
+        let mut other = *other;
+        if cos < T::zero() {
+            cos = -cos;
+            other = -other;
+        }
+        // Nearly identical rotations : avoid the division by sin(angle)
+        if cos > T::one() - T::from_f64(1e-4) {
+            return self.nlerp(&other, t);
+        }
+        let angle = cos.acos();
+        let sin = angle.sin();
+        let a = ((T::one() - t) * angle).sin() / sin;
+        let b = (t * angle).sin() / sin;
+        (*self * a + other * b).normalize()
+    }
+}
+
+// Clamp rounding errors before acos
+fn clamp_unit<T: Float>(value: T) -> T {
+    if value > T::one() { T::one() } else if value < -T::one() { -T::one() } else { value }
+}
+
+// Apply the right-hand side rotation first
+impl<T: Scalar> ops::Mul<Quat<T>> for Quat<T> {
+    type Output = Quat<T>;
+
+    fn mul(self, rhs: Quat<T>) -> Self::Output {
+        if let Some(result) = simd::quat_mul(self, rhs) {
+            return result;
+        }
+        Self::new(
+            self.w * rhs.x + self.x * rhs.w + self.y * rhs.z - self.z * rhs.y,
+            self.w * rhs.y - self.x * rhs.z + self.y * rhs.w + self.z * rhs.x,
+            self.w * rhs.z + self.x * rhs.y - self.y * rhs.x + self.z * rhs.w,
+            self.w * rhs.w - self.x * rhs.x - self.y * rhs.y - self.z * rhs.z)
+    }
+}
+
+// Rotate a vector. The quaternion must be normalized.
+impl<T: Scalar> ops::Mul<Vec3<T>> for Quat<T> {
+    type Output = Vec3<T>;
+
+    fn mul(self, rhs: Vec3<T>) -> Self::Output {
+        if let Some(result) = simd::quat_rotate(self, rhs) {
+            return result;
+        }
+        // v + 2w(q x v) + 2q x (q x v)
+        let q = Vec3::new(self.x, self.y, self.z);
+        let t = q.cross(rhs) * T::from(2);
+        rhs + t * self.w + q.cross(t)
+    }
+}
+
+impl<T: Default + Copy + ops::Mul<Output=T>> ops::Mul<T> for Quat<T> {
+    type Output = Quat<T>;
+
+    fn mul(self, rhs: T) -> Self::Output {
+        Self::new(self.x * rhs, self.y * rhs, self.z * rhs, self.w * rhs)
+    }
+}
+
+// Same rotation
+impl<T: Default + ops::Neg<Output=T>> ops::Neg for Quat<T> {
+    type Output = Quat<T>;
+
+    fn neg(self) -> Self::Output {
+        Self::new(-self.x, -self.y, -self.z, -self.w)
+    }
+}
+
+impl<T: Scalar> From<Quat<T>> for Mat3<T> {
+    fn from(quat: Quat<T>) -> Self {
+        quat.to_mat3()
+    }
+}
+
+impl<T: Scalar> From<Quat<T>> for Mat4<T> {
+    fn from(quat: Quat<T>) -> Self {
+        quat.to_mat4()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+
+    use crate::mat3::Mat3F64;
+    use crate::quat::QuatF64;
+    use crate::vec3::{Vec3, Vec3F64};
+
+    fn near(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    fn assert_vec_near(a: Vec3F64, b: Vec3F64) {
+        assert!(near(a.x, b.x) && near(a.y, b.y) && near(a.z, b.z), "{a:?} != {b:?}");
+    }
+
+    // q and -q are the same rotation
+    fn assert_rotation_near(a: QuatF64, b: QuatF64) {
+        assert!(near(a.dot(&b).abs(), 1.0), "{a:?} != {b:?}");
+    }
+
+    #[test]
+    fn rotation_test() {
+        let x = Vec3::new(1.0, 0.0, 0.0);
+        let y = Vec3::new(0.0, 1.0, 0.0);
+        let z = Vec3::new(0.0, 0.0, 1.0);
+        assert_vec_near(QuatF64::from_rotation_z(FRAC_PI_2) * x, y);
+        assert_vec_near(QuatF64::from_rotation_x(FRAC_PI_2) * y, z);
+        assert_vec_near(QuatF64::from_rotation_y(FRAC_PI_2) * z, x);
+        assert_vec_near(QuatF64::from_axis_angle(Vec3::new(0.0, 0.0, 5.0), PI) * x, x * -1.0);
+        assert_eq!(QuatF64::identity() * Vec3::new(1.0, 2.0, 3.0), Vec3::new(1.0, 2.0, 3.0));
+
+        // Composition applies the right-hand side first
+        let q = QuatF64::from_rotation_x(FRAC_PI_2) * QuatF64::from_rotation_z(FRAC_PI_2);
+        assert_vec_near(q * x, z);
+        assert_vec_near(q.conjugate() * (q * Vec3::new(1.0, 2.0, 3.0)), Vec3::new(1.0, 2.0, 3.0));
+        assert_rotation_near(q * q.inverse().unwrap(), QuatF64::identity());
+        assert!(near(QuatF64::from_axis_angle(Vec3::new(1.0, 1.0, 0.0), 1.5).angle(), 1.5));
+    }
+
+    #[test]
+    fn matrix_test() {
+        let q = QuatF64::from_euler(0.3, -1.2, 2.5);
+        let m = q.to_mat3();
+        let v = Vec3::new(1.0, -2.0, 0.5);
+        assert_vec_near(m * v, q * v);
+        assert!(near(m.determinant(), 1.0));
+        let product = m * m.transpose();
+        assert!(product.to_array().iter().zip(Mat3F64::identity().to_array()).all(|(a, b)| near(*a, b)));
+
+        // Every branch of the matrix conversion
+        for q in [q, QuatF64::from_rotation_x(PI), QuatF64::from_rotation_y(PI * 0.9), QuatF64::from_rotation_z(-PI * 0.95), QuatF64::identity()] {
+            assert_rotation_near(QuatF64::from_mat3(&q.to_mat3()), q);
+        }
+    }
+
+    #[test]
+    fn euler_test() {
+        let (x, y, z) = (0.3, -1.2, 2.5);
+        let q = QuatF64::from_euler(x, y, z);
+        assert_rotation_near(q, QuatF64::from_rotation_z(z) * QuatF64::from_rotation_y(y) * QuatF64::from_rotation_x(x));
+        assert_vec_near(q.to_euler(), Vec3::new(x, y, z));
+
+        // Gimbal lock still gives an equivalent rotation
+        let locked = QuatF64::from_euler(0.4, FRAC_PI_2, 0.1);
+        let angles = locked.to_euler();
+        assert_rotation_near(QuatF64::from_euler(angles.x, angles.y, angles.z), locked);
+    }
+
+    #[test]
+    fn slerp_test() {
+        let a = QuatF64::identity();
+        let b = QuatF64::from_rotation_y(FRAC_PI_2);
+        assert_rotation_near(a.slerp(&b, 0.0), a);
+        assert_rotation_near(a.slerp(&b, 1.0), b);
+        assert_rotation_near(a.slerp(&b, 0.5), QuatF64::from_rotation_y(FRAC_PI_4));
+        // Constant angular speed
+        assert!(near(a.slerp(&b, 0.25).angle(), FRAC_PI_2 * 0.25));
+        // Shortest path, even if the target has a negative w
+        assert_rotation_near(a.slerp(&-b, 0.5), QuatF64::from_rotation_y(FRAC_PI_4));
+        assert_rotation_near(b.slerp(&b, 0.3), b);
+        assert!(near(a.nlerp(&b, 0.5).length(), 1.0));
+    }
+}
@@ -0,0 +1,135 @@
+use std::ops::Range;
+
+// PCG32 (XSH RR variant) : small, fast and reproducible on every platform. Not suited for cryptography.
+// See https://www.pcg-random.org
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Pcg32 {
+    state: u64,
+    increment: u64,
+}
+
+const MULTIPLIER: u64 = 6364136223846793005;
+
+impl Pcg32 {
+    // Generators with different streams give independent sequences for the same seed
+    pub fn new(seed: u64, stream: u64) -> Self {
+        let mut rng = Self { state: 0, increment: (stream << 1) | 1 };
+        rng.step();
+        rng.state = rng.state.wrapping_add(seed);
+        rng.step();
+        rng
+    }
+
+    pub fn from_seed(seed: u64) -> Self {
+        Self::new(seed, 0)
+    }
+
+    fn step(&mut self) {
+        self.state = self.state.wrapping_mul(MULTIPLIER).wrapping_add(self.increment);
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let state = self.state;
+        self.step();
+        let xor_shifted = (((state >> 18) ^ state) >> 27) as u32;
+        xor_shifted.rotate_right((state >> 59) as u32)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        ((self.next_u32() as u64) << 32) | self.next_u32() as u64
+    }
+
+    // In [0, 1), with every representable step equally likely
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u32() >> 8) as f32 / (1u32 << 24) as f32
+    }
+
+    // In [0, 1)
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    pub fn next_bool(&mut self) -> bool {
+        self.next_u32() >> 31 == 1
+    }
+
+    // Uniform in the range, without modulo bias. Panics if the range is empty.
+    pub fn range_u32(&mut self, range: Range<u32>) -> u32 {
+        assert!(range.start < range.end, "empty range");
+        let span = range.end - range.start;
+        // Reject the values of the last incomplete span
+        let threshold = span.wrapping_neg() % span;
+        loop {
+            let value = self.next_u32();
+            if value >= threshold {
+                return range.start + value % span;
+            }
+        }
+    }
+
+    pub fn range_i32(&mut self, range: Range<i32>) -> i32 {
+        assert!(range.start < range.end, "empty range");
+        let span = range.end.wrapping_sub(range.start) as u32;
+        range.start.wrapping_add(self.range_u32(0..span) as i32)
+    }
+
+    pub fn range_f32(&mut self, range: Range<f32>) -> f32 {
+        range.start + (range.end - range.start) * self.next_f32()
+    }
+
+    pub fn range_f64(&mut self, range: Range<f64>) -> f64 {
+        range.start + (range.end - range.start) * self.next_f64()
+    }
+
+    // Fisher-Yates
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.range_u32(0..i as u32 + 1) as usize;
+            items.swap(i, j);
+        }
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        if items.is_empty() {
+            return None;
+        }
+        items.get(self.range_u32(0..items.len() as u32) as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::random::Pcg32;
+
+    #[test]
+    fn pcg_test() {
+        // Reference output of the PCG32 demo
+        let mut rng = Pcg32::new(42, 54);
+        let values: Vec<u32> = (0..6).map(|_| rng.next_u32()).collect();
+        assert_eq!(values, vec![0xa15c02b7, 0x7b47f409, 0xba1d3330, 0x83d2f293, 0xbfa4784b, 0xcbed606e]);
+
+        let (mut a, mut b) = (Pcg32::from_seed(7), Pcg32::from_seed(7));
+        assert_eq!((0..100).map(|_| a.next_u64()).collect::<Vec<_>>(), (0..100).map(|_| b.next_u64()).collect::<Vec<_>>());
+        assert_ne!(Pcg32::new(7, 1).next_u32(), Pcg32::new(7, 2).next_u32());
+
+        let mut rng = Pcg32::from_seed(1);
+        let mut counts = [0; 6];
+        for _ in 0..6000 {
+            let value = rng.range_f64(-1.0..1.0);
+            assert!((-1.0..1.0).contains(&value));
+            assert!((0.0..1.0).contains(&rng.next_f32()));
+            counts[rng.range_u32(0..6) as usize] += 1;
+            assert!((-3..2).contains(&rng.range_i32(-3..2)));
+        }
+        assert!(counts.iter().all(|count| (800..1200).contains(count)), "{counts:?}");
+
+        let mut items: Vec<u32> = (0..10).collect();
+        rng.shuffle(&mut items);
+        assert_ne!(items, (0..10).collect::<Vec<_>>());
+        items.sort();
+        assert_eq!(items, (0..10).collect::<Vec<_>>());
+        assert!(rng.choose::<u32>(&[]).is_none());
+        assert!(items.contains(rng.choose(&items).unwrap()));
+    }
+}
@@ -1,8 +1,14 @@
 ﻿
 use std::{fmt, ops};
+use std::num::TryFromIntError;
 use macros::*;
 
-#[derive(Debug, Copy, Clone, OpsAdd, OpsSub, DefaultConstruct)]
+use crate::scalar::Scalar;
+use crate::vec2::Vec2;
+
+// Min is inclusive and max exclusive : a rect of width 0 is empty
+#[derive(Debug, Copy, Clone, PartialEq, OpsAdd, OpsSub, DefaultConstruct)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Rect2D<T: Default> {
     _min_x: T,
     _min_y: T,
@@ -12,38 +18,42 @@ pub struct Rect2D<T: Default> {
 
 impl<T: Default + ops::Add + ops::Sub + Copy + PartialOrd> Rect2D<T> where <T as ops::Sub>::Output: Into<T>, <T as ops::Add>::Output: Into<T> {
     pub fn width(&self) -> T {
-        return (self.max_x() - self.min_x()).into()
+        (self.max_x() - self.min_x()).into()
     }
     pub fn height(&self) -> T {
-        return (self.max_y() - self.min_y()).into()
+        (self.max_y() - self.min_y()).into()
     }
 
     pub fn min_x(&self) -> T {
         if self._min_x > self._max_x {
-            return self._max_x
+            self._max_x
+        } else {
+            self._min_x
         }
-        return self._min_x
     }
     
     pub fn min_y(&self) -> T {
         if self._min_y > self._max_y {
-            return self._max_y
+            self._max_y
+        } else {
+            self._min_y
         }
-        return self._min_y
     }
 
     pub fn max_x(&self) -> T {
         if self._max_x < self._min_x {
-            return self._min_x
+            self._min_x
+        } else {
+            self._max_x
         }
-        return self._max_x
     }
 
     pub fn max_y(&self) -> T {
         if self._max_y < self._min_y {
-            return self._min_y
+            self._min_y
+        } else {
+            self._max_y
         }
-        return self._max_y
     }
     
     pub fn rect(x: T, y: T, width : T, height : T) -> Rect2D<T> {
@@ -56,9 +66,144 @@ impl<T: Default + ops::Add + ops::Sub + Copy + PartialOrd> Rect2D<T> where <T as
     }
 }
 
-impl<T: Default + fmt::Display + ops::Add + ops::Sub + Copy + PartialOrd> ToString for Rect2D<T> where <T as ops::Sub>::Output: Into<T>, <T as ops::Add>::Output: Into<T> {
-    fn to_string(&self) -> String {
-        format!("x={}, y={}, res={}x{}", self.min_x(), self.min_y(), self.width(), self.height())
+impl<T: Default + Copy + PartialOrd + ops::Add<Output=T> + ops::Sub<Output=T>> Rect2D<T> {
+    pub fn from_min_max(min: Vec2<T>, max: Vec2<T>) -> Self {
+        Self::new(min.x, min.y, max.x, max.y)
+    }
+
+    pub fn min(&self) -> Vec2<T> {
+        Vec2::new(self.min_x(), self.min_y())
+    }
+
+    pub fn max(&self) -> Vec2<T> {
+        Vec2::new(self.max_x(), self.max_y())
+    }
+
+    pub fn size(&self) -> Vec2<T> {
+        Vec2::new(self.width(), self.height())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min_x() == self.max_x() || self.min_y() == self.max_y()
+    }
+
+    pub fn contains_point(&self, point: Vec2<T>) -> bool {
+        point.x >= self.min_x() && point.x < self.max_x() && point.y >= self.min_y() && point.y < self.max_y()
+    }
+
+    pub fn contains(&self, other: &Self) -> bool {
+        other.min_x() >= self.min_x() && other.max_x() <= self.max_x() && other.min_y() >= self.min_y() && other.max_y() <= self.max_y()
+    }
+
+    // Rects only touching by an edge do not intersect
+    pub fn intersects(&self, other: &Self) -> bool {
+        self.min_x() < other.max_x() && other.min_x() < self.max_x() && self.min_y() < other.max_y() && other.min_y() < self.max_y()
+    }
+
+    // None if the rects do not intersect
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        if !self.intersects(other) {
+            return None;
+        }
+        Some(Self::from_min_max(self.min().max(other.min()), self.max().min(other.max())))
+    }
+
+    // Smallest rect containing both
+    pub fn union(&self, other: &Self) -> Self {
+        Self::from_min_max(self.min().min(other.min()), self.max().max(other.max()))
+    }
+
+    pub fn translate(&self, offset: Vec2<T>) -> Self {
+        Self::from_min_max(self.min() + offset, self.max() + offset)
+    }
+
+    pub fn clamp_point(&self, point: Vec2<T>) -> Vec2<T> {
+        point.clamp(self.min(), self.max())
+    }
+
+    // Part of the rect inside the bounds. Outside rects collapse to an empty rect on the closest edge.
+    pub fn clamp(&self, bounds: &Self) -> Self {
+        Self::from_min_max(bounds.clamp_point(self.min()), bounds.clamp_point(self.max()))
+    }
+
+    // Left part of the given width and the remaining right part. The width is clamped to the rect.
+    pub fn split_x(&self, width: T) -> (Self, Self) {
+        let x = if width < self.width() { self.min_x() + width } else { self.max_x() };
+        (Self::new(self.min_x(), self.min_y(), x, self.max_y()), Self::new(x, self.min_y(), self.max_x(), self.max_y()))
+    }
+
+    // Top part of the given height and the remaining bottom part. The height is clamped to the rect.
+    pub fn split_y(&self, height: T) -> (Self, Self) {
+        let y = if height < self.height() { self.min_y() + height } else { self.max_y() };
+        (Self::new(self.min_x(), self.min_y(), self.max_x(), y), Self::new(self.min_x(), y, self.max_x(), self.max_y()))
+    }
+}
+
+impl<T: Default + Copy + PartialOrd + From<u8> + ops::Add<Output=T> + ops::Sub<Output=T> + ops::Div<Output=T>> Rect2D<T> {
+    pub fn center(&self) -> Vec2<T> {
+        let two = T::from(2);
+        Vec2::new(self.min_x() + self.width() / two, self.min_y() + self.height() / two)
+    }
+
+    // Move each edge inward. A rect too small collapses to its center.
+    pub fn shrink(&self, amount: T) -> Self {
+        let center = self.center();
+        let clamp = |min: T, max: T, center: T| {
+            if max - min > amount + amount { (min + amount, max - amount) } else { (center, center) }
+        };
+        let (min_x, max_x) = clamp(self.min_x(), self.max_x(), center.x);
+        let (min_y, max_y) = clamp(self.min_y(), self.max_y(), center.y);
+        Self::new(min_x, min_y, max_x, max_y)
+    }
+}
+
+impl<T: Scalar> Rect2D<T> {
+    // Move each edge outward. Not available for unsigned types, which could underflow.
+    pub fn expand(&self, amount: T) -> Self {
+        Self::new(self.min_x() - amount, self.min_y() - amount, self.max_x() + amount, self.max_y() + amount)
+    }
+}
+
+impl RectF32 {
+    // Smallest integer rect covering this one
+    pub fn round_out(&self) -> RectI32 {
+        RectI32::new(self.min_x().floor() as i32, self.min_y().floor() as i32, self.max_x().ceil() as i32, self.max_y().ceil() as i32)
+    }
+}
+
+impl RectI32 {
+    pub fn to_f32(&self) -> RectF32 {
+        RectF32::new(self.min_x() as f32, self.min_y() as f32, self.max_x() as f32, self.max_y() as f32)
+    }
+}
+
+impl RectU32 {
+    pub fn to_f32(&self) -> RectF32 {
+        RectF32::new(self.min_x() as f32, self.min_y() as f32, self.max_x() as f32, self.max_y() as f32)
+    }
+}
+
+// Fails on negative coordinates
+impl TryFrom<RectI32> for RectU32 {
+    type Error = TryFromIntError;
+
+    fn try_from(rect: RectI32) -> Result<Self, Self::Error> {
+        Ok(Self::new(rect.min_x().try_into()?, rect.min_y().try_into()?, rect.max_x().try_into()?, rect.max_y().try_into()?))
+    }
+}
+
+// Fails on coordinates above i32::MAX
+impl TryFrom<RectU32> for RectI32 {
+    type Error = TryFromIntError;
+
+    fn try_from(rect: RectU32) -> Result<Self, Self::Error> {
+        Ok(Self::new(rect.min_x().try_into()?, rect.min_y().try_into()?, rect.max_x().try_into()?, rect.max_y().try_into()?))
+    }
+}
+
+impl<T: Default + fmt::Display + ops::Add + ops::Sub + Copy + PartialOrd> fmt::Display for Rect2D<T> where <T as ops::Sub>::Output: Into<T>, <T as ops::Add>::Output: Into<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "x={}, y={}, res={}x{}", self.min_x(), self.min_y(), self.width(), self.height())
     }
 }
 
@@ -69,4 +214,52 @@ pub type RectI32 = Rect2D<i32>;
 pub type RectI64 = Rect2D<i64>;
 
 pub type RectU32 = Rect2D<u32>;
-pub type RectU64 = Rect2D<u64>;
\ No newline at end of file
+pub type RectU64 = Rect2D<u64>;
+
+#[cfg(test)]
+mod tests {
+    use crate::rect2d::{Rect2D, RectF32, RectI32, RectU32};
+    use crate::vec2::Vec2;
+
+    #[test]
+    fn rect_test() {
+        let a = RectI32::rect(0, 0, 10, 10);
+        let b = RectI32::rect(5, -5, 10, 10);
+        assert_eq!(a.intersection(&b), Some(RectI32::new(5, 0, 10, 5)));
+        assert_eq!(a.union(&b), RectI32::new(0, -5, 15, 10));
+        // Touching edges
+        assert!(a.intersection(&RectI32::rect(10, 0, 5, 5)).is_none());
+
+        assert!(a.contains_point(Vec2::new(0, 9)));
+        assert!(!a.contains_point(Vec2::new(10, 5)));
+        assert!(a.contains(&RectI32::rect(2, 2, 8, 8)));
+        assert!(!a.contains(&b));
+        assert!(RectI32::rect(3, 3, 0, 4).is_empty());
+
+        assert_eq!(a.expand(2), RectI32::new(-2, -2, 12, 12));
+        assert_eq!(a.shrink(2), RectI32::new(2, 2, 8, 8));
+        assert_eq!(a.shrink(6), RectI32::new(5, 5, 5, 5));
+        assert_eq!(RectU32::rect(0, 0, 4, 4).shrink(1), RectU32::new(1, 1, 3, 3));
+
+        let (left, right) = a.split_x(3);
+        assert_eq!((left.width(), right.width()), (3, 7));
+        let (top, bottom) = a.split_y(20);
+        assert_eq!((top, bottom.height()), (a, 0));
+
+        assert_eq!(b.clamp(&a), RectI32::new(5, 0, 10, 5));
+        assert!(RectI32::rect(20, 20, 5, 5).clamp(&a).is_empty());
+        assert_eq!(a.clamp_point(Vec2::new(-4, 30)), Vec2::new(0, 10));
+        assert_eq!(a.translate(Vec2::new(1, 2)).min(), Vec2::new(1, 2));
+        assert_eq!(a.center(), Vec2::new(5, 5));
+    }
+
+    #[test]
+    fn conversion_test() {
+        let rect = RectF32::new(-0.5, 1.2, 3.5, 4.0);
+        assert_eq!(rect.round_out(), RectI32::new(-1, 1, 4, 4));
+        assert_eq!(RectI32::rect(1, 2, 3, 4).to_f32(), RectF32::new(1.0, 2.0, 4.0, 6.0));
+        assert_eq!(RectU32::try_from(RectI32::rect(1, 2, 3, 4)), Ok(RectU32::new(1, 2, 4, 6)));
+        assert!(RectU32::try_from(rect.round_out()).is_err());
+        assert!(RectI32::try_from(Rect2D::new(0, 0, u32::MAX, 1)).is_err());
+    }
+}
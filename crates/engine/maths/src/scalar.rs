@@ -0,0 +1,108 @@
+use std::ops;
+
+// Signed numbers supporting the arithmetic needed by vectors and matrices. 'static lets f32 operations use the simd module.
+pub trait Scalar: 'static + Default + Copy + PartialEq + PartialOrd + From<u8>
++ ops::Add<Output=Self> + ops::Sub<Output=Self> + ops::Mul<Output=Self> + ops::Div<Output=Self> + ops::Neg<Output=Self> {
+    fn zero() -> Self {
+        Self::from(0)
+    }
+
+    fn one() -> Self {
+        Self::from(1)
+    }
+}
+
+impl<T: 'static + Default + Copy + PartialEq + PartialOrd + From<u8>
++ ops::Add<Output=T> + ops::Sub<Output=T> + ops::Mul<Output=T> + ops::Div<Output=T> + ops::Neg<Output=T>> Scalar for T {}
+
+// Floating point numbers, required by rotations and normalization
+pub trait Float: Scalar {
+    const EPSILON: Self;
+    const PI: Self;
+    const INFINITY: Self;
+
+    fn from_f64(value: f64) -> Self;
+    fn to_f64(self) -> f64;
+    fn sqrt(self) -> Self;
+    fn abs(self) -> Self;
+    fn sin(self) -> Self;
+    fn cos(self) -> Self;
+    fn tan(self) -> Self;
+    fn asin(self) -> Self;
+    fn acos(self) -> Self;
+    fn atan2(self, other: Self) -> Self;
+    fn powf(self, exponent: Self) -> Self;
+    fn floor(self) -> Self;
+    fn exp(self) -> Self;
+    fn ln(self) -> Self;
+}
+
+macro_rules! impl_float {
+    ($($ty:ident),*) => {
+        $(
+        impl Float for $ty {
+            const EPSILON: Self = $ty::EPSILON;
+            const PI: Self = std::$ty::consts::PI;
+            const INFINITY: Self = $ty::INFINITY;
+
+            fn from_f64(value: f64) -> Self {
+                value as $ty
+            }
+
+            fn to_f64(self) -> f64 {
+                self as f64
+            }
+
+            fn sqrt(self) -> Self {
+                $ty::sqrt(self)
+            }
+
+            fn abs(self) -> Self {
+                $ty::abs(self)
+            }
+
+            fn sin(self) -> Self {
+                $ty::sin(self)
+            }
+
+            fn cos(self) -> Self {
+                $ty::cos(self)
+            }
+
+            fn tan(self) -> Self {
+                $ty::tan(self)
+            }
+
+            fn asin(self) -> Self {
+                $ty::asin(self)
+            }
+
+            fn acos(self) -> Self {
+                $ty::acos(self)
+            }
+
+            fn atan2(self, other: Self) -> Self {
+                $ty::atan2(self, other)
+            }
+
+            fn powf(self, exponent: Self) -> Self {
+                $ty::powf(self, exponent)
+            }
+
+            fn floor(self) -> Self {
+                $ty::floor(self)
+            }
+
+            fn exp(self) -> Self {
+                $ty::exp(self)
+            }
+
+            fn ln(self) -> Self {
+                $ty::ln(self)
+            }
+        }
+        )*
+    };
+}
+
+impl_float!(f32, f64);
@@ -0,0 +1,477 @@
+use std::any::Any;
+use std::fmt::{Debug, Formatter};
+use std::ops;
+
+use crate::mat4::{Mat4, Mat4F32};
+use crate::quat::{Quat, QuatF32};
+use crate::vec3::{Vec3, Vec3F32};
+use crate::vec4::{Vec4, Vec4F32};
+
+// 4 lanes f32 register : SSE2 or NEON with the 'simd' feature, portable code otherwise.
+// Both instruction sets are part of the baseline of their architecture, no runtime detection is needed.
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+mod sse2;
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+use sse2::F32x4;
+
+#[cfg(all(feature = "simd", target_arch = "aarch64"))]
+mod neon;
+#[cfg(all(feature = "simd", target_arch = "aarch64"))]
+use neon::F32x4;
+
+#[cfg(not(all(feature = "simd", any(target_arch = "x86_64", target_arch = "aarch64"))))]
+mod scalar;
+#[cfg(not(all(feature = "simd", any(target_arch = "x86_64", target_arch = "aarch64"))))]
+use scalar::F32x4;
+
+pub const fn is_accelerated() -> bool {
+    cfg!(all(feature = "simd", any(target_arch = "x86_64", target_arch = "aarch64")))
+}
+
+// a x b, w is zero when both w are equal
+#[inline]
+fn cross(a: F32x4, b: F32x4) -> F32x4 {
+    a.yzxw() * b.zxyw() - a.zxyw() * b.yzxw()
+}
+
+/*
+GENERIC TYPES
+ */
+
+// Products of Mat4F32 and QuatF32 go through the registers below when accelerated. The generic types keep their layout :
+// Vec4A, Mat4A and QuatA avoid converting the operands on every operation, e.g. for batches of points.
+
+// Some(value) when both types are the same, which is known at compile time
+#[inline]
+fn cast<A: 'static, B: 'static>(value: A) -> Option<B> {
+    let mut value = Some(value);
+    (&mut value as &mut dyn Any).downcast_mut::<Option<B>>().and_then(Option::take)
+}
+
+#[inline]
+pub(crate) fn mat4_mul<T: Default + Copy + 'static>(lhs: Mat4<T>, rhs: Mat4<T>) -> Option<Mat4<T>> {
+    if !is_accelerated() {
+        return None;
+    }
+    let (lhs, rhs) = (cast::<_, Mat4F32>(lhs)?, cast::<_, Mat4F32>(rhs)?);
+    cast(Mat4F32::from(Mat4A::from(lhs) * Mat4A::from(rhs)))
+}
+
+#[inline]
+pub(crate) fn mat4_transform<T: Default + Copy + 'static>(lhs: Mat4<T>, rhs: Vec4<T>) -> Option<Vec4<T>> {
+    if !is_accelerated() {
+        return None;
+    }
+    let (lhs, rhs) = (cast::<_, Mat4F32>(lhs)?, cast::<_, Vec4F32>(rhs)?);
+    cast(Vec4F32::from(Mat4A::from(lhs) * Vec4A::from(rhs)))
+}
+
+#[inline]
+pub(crate) fn quat_mul<T: Default + Copy + 'static>(lhs: Quat<T>, rhs: Quat<T>) -> Option<Quat<T>> {
+    if !is_accelerated() {
+        return None;
+    }
+    let (lhs, rhs) = (cast::<_, QuatF32>(lhs)?, cast::<_, QuatF32>(rhs)?);
+    cast(QuatF32::from(QuatA::from(lhs) * QuatA::from(rhs)))
+}
+
+#[inline]
+pub(crate) fn quat_rotate<T: Default + Copy + 'static>(lhs: Quat<T>, rhs: Vec3<T>) -> Option<Vec3<T>> {
+    if !is_accelerated() {
+        return None;
+    }
+    let (lhs, rhs) = (cast::<_, QuatF32>(lhs)?, cast::<_, Vec3F32>(rhs)?);
+    cast(QuatA::from(lhs).rotate(rhs))
+}
+
+/*
+VECTOR
+ */
+
+// 16 bytes aligned counterpart of Vec4F32. Results may differ from the generic version by rounding.
+#[derive(Copy, Clone)]
+#[repr(transparent)]
+pub struct Vec4A(F32x4);
+
+impl Vec4A {
+    #[inline]
+    pub fn new(x: f32, y: f32, z: f32, w: f32) -> Self {
+        Self(F32x4::new(x, y, z, w))
+    }
+
+    #[inline]
+    pub fn splat(value: f32) -> Self {
+        Self(F32x4::splat(value))
+    }
+
+    #[inline]
+    pub fn to_array(self) -> [f32; 4] {
+        self.0.to_array()
+    }
+
+    #[inline]
+    pub fn dot(self, other: Self) -> f32 {
+        self.0.dot(other.0)
+    }
+
+    #[inline]
+    pub fn length(self) -> f32 {
+        self.dot(self).sqrt()
+    }
+
+    #[inline]
+    pub fn normalize(self) -> Self {
+        Self(self.0 / F32x4::splat(self.length()))
+    }
+
+    #[inline]
+    pub fn min(self, other: Self) -> Self {
+        Self(self.0.min(other.0))
+    }
+
+    #[inline]
+    pub fn max(self, other: Self) -> Self {
+        Self(self.0.max(other.0))
+    }
+}
+
+impl From<Vec4F32> for Vec4A {
+    #[inline]
+    fn from(v: Vec4F32) -> Self {
+        Self::new(v.x, v.y, v.z, v.w)
+    }
+}
+
+impl From<Vec4A> for Vec4F32 {
+    #[inline]
+    fn from(v: Vec4A) -> Self {
+        let [x, y, z, w] = v.to_array();
+        Vec4F32::new(x, y, z, w)
+    }
+}
+
+impl PartialEq for Vec4A {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_array() == other.to_array()
+    }
+}
+
+impl Debug for Vec4A {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Vec4A").field(&self.to_array()).finish()
+    }
+}
+
+impl ops::Add for Vec4A {
+    type Output = Vec4A;
+
+    #[inline]
+    fn add(self, rhs: Vec4A) -> Self::Output {
+        Self(self.0 + rhs.0)
+    }
+}
+
+impl ops::Sub for Vec4A {
+    type Output = Vec4A;
+
+    #[inline]
+    fn sub(self, rhs: Vec4A) -> Self::Output {
+        Self(self.0 - rhs.0)
+    }
+}
+
+impl ops::Mul for Vec4A {
+    type Output = Vec4A;
+
+    #[inline]
+    fn mul(self, rhs: Vec4A) -> Self::Output {
+        Self(self.0 * rhs.0)
+    }
+}
+
+impl ops::Mul<f32> for Vec4A {
+    type Output = Vec4A;
+
+    #[inline]
+    fn mul(self, rhs: f32) -> Self::Output {
+        Self(self.0 * F32x4::splat(rhs))
+    }
+}
+
+impl ops::Div<f32> for Vec4A {
+    type Output = Vec4A;
+
+    #[inline]
+    fn div(self, rhs: f32) -> Self::Output {
+        Self(self.0 / F32x4::splat(rhs))
+    }
+}
+
+impl ops::Neg for Vec4A {
+    type Output = Vec4A;
+
+    #[inline]
+    fn neg(self) -> Self::Output {
+        Self(-self.0)
+    }
+}
+
+/*
+MATRIX
+ */
+
+// Column major counterpart of Mat4F32
+#[derive(Copy, Clone)]
+pub struct Mat4A {
+    cols: [F32x4; 4],
+}
+
+impl Mat4A {
+    pub fn identity() -> Self {
+        Self::from(Mat4F32::identity())
+    }
+
+    #[inline]
+    pub fn col(&self, index: usize) -> Vec4A {
+        Vec4A(self.cols[index])
+    }
+
+    #[inline]
+    fn transform(&self, v: F32x4) -> F32x4 {
+        let [x, y, z, w] = self.cols;
+        x * v.xxxx() + y * v.yyyy() + z * v.zzzz() + w * v.wwww()
+    }
+
+    // Same as multiplying each point, without converting the matrix every time
+    pub fn transform_points(&self, points: &[Vec4F32], out: &mut [Vec4F32]) {
+        assert_eq!(points.len(), out.len(), "output length differs from input length");
+        for (point, out) in points.iter().zip(out.iter_mut()) {
+            *out = Vec4A(self.transform(Vec4A::from(*point).0)).into();
+        }
+    }
+}
+
+impl From<Mat4F32> for Mat4A {
+    #[inline]
+    fn from(m: Mat4F32) -> Self {
+        Self { cols: m.to_cols().map(|[x, y, z, w]| F32x4::new(x, y, z, w)) }
+    }
+}
+
+impl From<Mat4A> for Mat4F32 {
+    #[inline]
+    fn from(m: Mat4A) -> Self {
+        Mat4F32::from_cols(m.cols.map(F32x4::to_array))
+    }
+}
+
+impl PartialEq for Mat4A {
+    fn eq(&self, other: &Self) -> bool {
+        Mat4F32::from(*self) == Mat4F32::from(*other)
+    }
+}
+
+impl Debug for Mat4A {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Mat4A").field(&self.cols.map(F32x4::to_array)).finish()
+    }
+}
+
+impl ops::Mul<Vec4A> for Mat4A {
+    type Output = Vec4A;
+
+    #[inline]
+    fn mul(self, rhs: Vec4A) -> Self::Output {
+        Vec4A(self.transform(rhs.0))
+    }
+}
+
+impl ops::Mul<Mat4A> for Mat4A {
+    type Output = Mat4A;
+
+    #[inline]
+    fn mul(self, rhs: Mat4A) -> Self::Output {
+        Self { cols: rhs.cols.map(|col| self.transform(col)) }
+    }
+}
+
+/*
+QUATERNION
+ */
+
+// Counterpart of QuatF32, stored as x y z w
+#[derive(Copy, Clone)]
+#[repr(transparent)]
+pub struct QuatA(F32x4);
+
+impl QuatA {
+    pub fn identity() -> Self {
+        Self(F32x4::new(0.0, 0.0, 0.0, 1.0))
+    }
+
+    #[inline]
+    pub fn dot(self, other: Self) -> f32 {
+        self.0.dot(other.0)
+    }
+
+    #[inline]
+    pub fn conjugate(self) -> Self {
+        Self(self.0 * F32x4::new(-1.0, -1.0, -1.0, 1.0))
+    }
+
+    #[inline]
+    pub fn normalize(self) -> Self {
+        Self(self.0 / F32x4::splat(self.dot(self).sqrt()))
+    }
+
+    // The quaternion must be normalized
+    #[inline]
+    pub fn rotate(self, v: Vec3F32) -> Vec3F32 {
+        // v + 2w(q x v) + 2q x (q x v)
+        let vector = F32x4::new(v.x, v.y, v.z, 0.0);
+        let t = cross(self.0, vector) * F32x4::splat(2.0);
+        let [x, y, z, _] = (vector + self.0.wwww() * t + cross(self.0, t)).to_array();
+        Vec3F32::new(x, y, z)
+    }
+}
+
+impl From<QuatF32> for QuatA {
+    #[inline]
+    fn from(q: QuatF32) -> Self {
+        Self(F32x4::new(q.x, q.y, q.z, q.w))
+    }
+}
+
+impl From<QuatA> for QuatF32 {
+    #[inline]
+    fn from(q: QuatA) -> Self {
+        let [x, y, z, w] = q.0.to_array();
+        QuatF32::new(x, y, z, w)
+    }
+}
+
+impl PartialEq for QuatA {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.to_array() == other.0.to_array()
+    }
+}
+
+impl Debug for QuatA {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("QuatA").field(&self.0.to_array()).finish()
+    }
+}
+
+// Apply the right-hand side rotation first
+impl ops::Mul<QuatA> for QuatA {
+    type Output = QuatA;
+
+    #[inline]
+    fn mul(self, rhs: QuatA) -> Self::Output {
+        let (a, b) = (self.0, rhs.0);
+        Self(a.wwww() * b
+            + a.xxxx() * b.wzyx() * F32x4::new(1.0, -1.0, 1.0, -1.0)
+            + a.yyyy() * b.zwxy() * F32x4::new(1.0, 1.0, -1.0, -1.0)
+            + a.zzzz() * b.yxwz() * F32x4::new(-1.0, 1.0, 1.0, -1.0))
+    }
+}
+
+impl ops::Mul<Vec3F32> for QuatA {
+    type Output = Vec3F32;
+
+    #[inline]
+    fn mul(self, rhs: Vec3F32) -> Self::Output {
+        self.rotate(rhs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mat4::{Mat4F32, Mat4F64};
+    use crate::quat::{QuatF32, QuatF64};
+    use crate::simd::{Mat4A, QuatA, Vec4A};
+    use crate::vec3::{Vec3, Vec3F32};
+    use crate::vec4::{Vec4, Vec4F32};
+
+    fn assert_near(a: &[f32], b: &[f32]) {
+        assert!(a.iter().zip(b).all(|(a, b)| (a - b).abs() <= 1e-5 * (1.0 + b.abs())), "{a:?} != {b:?}");
+    }
+
+    fn vec3(v: Vec3F32) -> [f32; 3] {
+        [v.x, v.y, v.z]
+    }
+
+    // f64 operations are never accelerated
+    fn mat_f64(m: Mat4F32) -> Mat4F64 {
+        Mat4F64::from_cols(m.to_cols().map(|col| col.map(f64::from)))
+    }
+
+    fn quat_f64(q: QuatF32) -> QuatF64 {
+        QuatF64::new(q.x as f64, q.y as f64, q.z as f64, q.w as f64)
+    }
+
+    fn to_f32<const N: usize>(values: [f64; N]) -> [f32; N] {
+        values.map(|value| value as f32)
+    }
+
+    fn sample() -> Mat4F32 {
+        Mat4F32::perspective(1.2, 1.5, 0.1, 50.0) * Mat4F32::look_at(Vec3::new(1.0, 2.0, 3.0), Vec3::new(-1.0, 0.5, 0.0), Vec3::new(0.0, 1.0, 0.0))
+    }
+
+    #[test]
+    fn vector_test() {
+        let (a, b) = (Vec4::new(1.0, -2.0, 3.5, 0.25), Vec4::new(-4.0, 0.5, 2.0, 8.0));
+        let (sa, sb) = (Vec4A::from(a), Vec4A::from(b));
+        assert_eq!(Vec4F32::from(sa), a);
+        assert_eq!(Vec4F32::from(sa + sb), a + b);
+        assert_eq!(Vec4F32::from(sa - sb), a - b);
+        assert_eq!(Vec4F32::from(sa * sb), a * b);
+        assert_eq!(Vec4F32::from(sa * 3.0), a * 3.0);
+        assert_eq!(Vec4F32::from(-sa), -a);
+        assert_eq!(Vec4F32::from(sa.min(sb)), a.min(b));
+        assert_eq!(Vec4F32::from(sa.max(sb)), a.max(b));
+        assert_near(&[sa.dot(sb), sa.length()], &[a.dot(b), a.length()]);
+        assert_near(&sa.normalize().to_array(), &Vec4A::from(a.normalize()).to_array());
+    }
+
+    #[test]
+    fn matrix_test() {
+        let (m, n) = (sample(), Mat4F32::look_at(Vec3::new(-3.0, 1.0, 0.5), Vec3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 1.0)));
+        let (sm, sn) = (Mat4A::from(m), Mat4A::from(n));
+        assert_eq!(Mat4F32::from(sm), m);
+        assert_eq!(Mat4A::identity() * sm, sm);
+        let product = to_f32((mat_f64(m) * mat_f64(n)).to_array());
+        assert_near(&Mat4F32::from(sm * sn).to_array(), &product);
+        assert_near(&(m * n).to_array(), &product);
+
+        let points: Vec<Vec4F32> = (0..32).map(|i| Vec4::new(i as f32, (i * 7 % 5) as f32 - 2.0, -(i as f32) * 0.5, 1.0)).collect();
+        let mut out = vec![Vec4::splat(0.0); points.len()];
+        sm.transform_points(&points, &mut out);
+        for (point, out) in points.iter().zip(&out) {
+            assert_near(&Vec4A::from(*out).to_array(), &Vec4A::from(m * *point).to_array());
+            assert_eq!(*out, Vec4F32::from(sm * Vec4A::from(*point)));
+            let expected = mat_f64(m) * Vec4::new(point.x as f64, point.y as f64, point.z as f64, point.w as f64);
+            assert_near(&(m * *point).to_array(), &to_f32(expected.to_array()));
+        }
+    }
+
+    #[test]
+    fn quaternion_test() {
+        let (a, b) = (QuatF32::from_euler(0.3, -1.2, 2.0), QuatF32::from_axis_angle(Vec3::new(1.0, 1.0, -0.5), 0.7));
+        let (sa, sb) = (QuatA::from(a), QuatA::from(b));
+        assert_eq!(QuatF32::from(sa), a);
+        assert_eq!(QuatF32::from(QuatA::identity()), QuatF32::identity());
+        let expected = { let q = quat_f64(a) * quat_f64(b); to_f32([q.x, q.y, q.z, q.w]) };
+        for product in [QuatF32::from(sa * sb), a * b] {
+            assert_near(&[product.x, product.y, product.z, product.w], &expected);
+        }
+        assert_near(&[sa.dot(sb)], &[a.dot(&b)]);
+        assert_eq!(QuatF32::from(sa.conjugate()), a.conjugate());
+
+        let v = Vec3::new(0.5, -3.0, 2.0);
+        let expected = quat_f64(a) * Vec3::new(0.5, -3.0, 2.0);
+        assert_near(&vec3(sa * v), &to_f32([expected.x, expected.y, expected.z]));
+        assert_near(&vec3(a * v), &vec3(sa * v));
+        assert_near(&vec3(sa.normalize().rotate(v)), &vec3(a.normalize() * v));
+    }
+}
@@ -0,0 +1,122 @@
+// Intrinsics of a baseline target feature are safe to call on recent compilers
+#![allow(unused_unsafe)]
+
+use std::arch::aarch64::*;
+use std::ops;
+
+#[derive(Copy, Clone)]
+#[repr(transparent)]
+pub struct F32x4(float32x4_t);
+
+impl F32x4 {
+    #[inline]
+    pub fn new(x: f32, y: f32, z: f32, w: f32) -> Self {
+        let array = [x, y, z, w];
+        Self(unsafe { vld1q_f32(array.as_ptr()) })
+    }
+
+    #[inline]
+    pub fn splat(value: f32) -> Self {
+        Self(unsafe { vdupq_n_f32(value) })
+    }
+
+    #[inline]
+    pub fn to_array(self) -> [f32; 4] {
+        let mut array = [0.0; 4];
+        unsafe { vst1q_f32(array.as_mut_ptr(), self.0) };
+        array
+    }
+
+    #[inline]
+    pub fn min(self, other: Self) -> Self {
+        Self(unsafe { vminq_f32(self.0, other.0) })
+    }
+
+    #[inline]
+    pub fn max(self, other: Self) -> Self {
+        Self(unsafe { vmaxq_f32(self.0, other.0) })
+    }
+
+    #[inline]
+    pub fn dot(self, other: Self) -> f32 {
+        unsafe { vaddvq_f32(vmulq_f32(self.0, other.0)) }
+    }
+
+    #[inline] pub fn xxxx(self) -> Self { Self(unsafe { vdupq_laneq_f32::<0>(self.0) }) }
+    #[inline] pub fn yyyy(self) -> Self { Self(unsafe { vdupq_laneq_f32::<1>(self.0) }) }
+    #[inline] pub fn zzzz(self) -> Self { Self(unsafe { vdupq_laneq_f32::<2>(self.0) }) }
+    #[inline] pub fn wwww(self) -> Self { Self(unsafe { vdupq_laneq_f32::<3>(self.0) }) }
+    #[inline] pub fn yxwz(self) -> Self { Self(unsafe { vrev64q_f32(self.0) }) }
+    #[inline] pub fn zwxy(self) -> Self { Self(unsafe { vextq_f32::<2>(self.0, self.0) }) }
+
+    #[inline]
+    pub fn wzyx(self) -> Self {
+        self.yxwz().zwxy()
+    }
+
+    #[inline]
+    pub fn yzxw(self) -> Self {
+        unsafe {
+            // y z w x, then x and w are put back in place
+            let rotated = vextq_f32::<1>(self.0, self.0);
+            let rotated = vcopyq_laneq_f32::<2, 3>(rotated, rotated);
+            Self(vcopyq_laneq_f32::<3, 3>(rotated, self.0))
+        }
+    }
+
+    #[inline]
+    pub fn zxyw(self) -> Self {
+        unsafe {
+            // z w x y, then x, y and w are put back in place
+            let rotated = vextq_f32::<2>(self.0, self.0);
+            let rotated = vcopyq_laneq_f32::<1, 2>(rotated, rotated);
+            let rotated = vcopyq_laneq_f32::<2, 1>(rotated, self.0);
+            Self(vcopyq_laneq_f32::<3, 3>(rotated, self.0))
+        }
+    }
+}
+
+impl ops::Add for F32x4 {
+    type Output = F32x4;
+
+    #[inline]
+    fn add(self, rhs: F32x4) -> Self::Output {
+        Self(unsafe { vaddq_f32(self.0, rhs.0) })
+    }
+}
+
+impl ops::Sub for F32x4 {
+    type Output = F32x4;
+
+    #[inline]
+    fn sub(self, rhs: F32x4) -> Self::Output {
+        Self(unsafe { vsubq_f32(self.0, rhs.0) })
+    }
+}
+
+impl ops::Mul for F32x4 {
+    type Output = F32x4;
+
+    #[inline]
+    fn mul(self, rhs: F32x4) -> Self::Output {
+        Self(unsafe { vmulq_f32(self.0, rhs.0) })
+    }
+}
+
+impl ops::Div for F32x4 {
+    type Output = F32x4;
+
+    #[inline]
+    fn div(self, rhs: F32x4) -> Self::Output {
+        Self(unsafe { vdivq_f32(self.0, rhs.0) })
+    }
+}
+
+impl ops::Neg for F32x4 {
+    type Output = F32x4;
+
+    #[inline]
+    fn neg(self) -> Self::Output {
+        Self(unsafe { vnegq_f32(self.0) })
+    }
+}
@@ -0,0 +1,82 @@
+use std::ops;
+
+// Portable fallback, with the same layout as the SIMD registers
+#[derive(Copy, Clone)]
+#[repr(C, align(16))]
+pub struct F32x4([f32; 4]);
+
+macro_rules! shuffle {
+    ($v:expr, $a:literal, $b:literal, $c:literal, $d:literal) => {
+        F32x4([$v.0[$a], $v.0[$b], $v.0[$c], $v.0[$d]])
+    };
+}
+
+impl F32x4 {
+    #[inline]
+    pub fn new(x: f32, y: f32, z: f32, w: f32) -> Self {
+        Self([x, y, z, w])
+    }
+
+    #[inline]
+    pub fn splat(value: f32) -> Self {
+        Self([value; 4])
+    }
+
+    #[inline]
+    pub fn to_array(self) -> [f32; 4] {
+        self.0
+    }
+
+    #[inline]
+    pub fn min(self, other: Self) -> Self {
+        Self(std::array::from_fn(|i| self.0[i].min(other.0[i])))
+    }
+
+    #[inline]
+    pub fn max(self, other: Self) -> Self {
+        Self(std::array::from_fn(|i| self.0[i].max(other.0[i])))
+    }
+
+    #[inline]
+    pub fn dot(self, other: Self) -> f32 {
+        let product = self * other;
+        (product.0[0] + product.0[1]) + (product.0[2] + product.0[3])
+    }
+
+    #[inline] pub fn xxxx(self) -> Self { shuffle!(self, 0, 0, 0, 0) }
+    #[inline] pub fn yyyy(self) -> Self { shuffle!(self, 1, 1, 1, 1) }
+    #[inline] pub fn zzzz(self) -> Self { shuffle!(self, 2, 2, 2, 2) }
+    #[inline] pub fn wwww(self) -> Self { shuffle!(self, 3, 3, 3, 3) }
+    #[inline] pub fn yxwz(self) -> Self { shuffle!(self, 1, 0, 3, 2) }
+    #[inline] pub fn zwxy(self) -> Self { shuffle!(self, 2, 3, 0, 1) }
+    #[inline] pub fn wzyx(self) -> Self { shuffle!(self, 3, 2, 1, 0) }
+    #[inline] pub fn yzxw(self) -> Self { shuffle!(self, 1, 2, 0, 3) }
+    #[inline] pub fn zxyw(self) -> Self { shuffle!(self, 2, 0, 1, 3) }
+}
+
+macro_rules! impl_op {
+    ($trait:ident, $fn:ident, $op:tt) => {
+        impl ops::$trait for F32x4 {
+            type Output = F32x4;
+
+            #[inline]
+            fn $fn(self, rhs: F32x4) -> Self::Output {
+                Self(std::array::from_fn(|i| self.0[i] $op rhs.0[i]))
+            }
+        }
+    };
+}
+
+impl_op!(Add, add, +);
+impl_op!(Sub, sub, -);
+impl_op!(Mul, mul, *);
+impl_op!(Div, div, /);
+
+impl ops::Neg for F32x4 {
+    type Output = F32x4;
+
+    #[inline]
+    fn neg(self) -> Self::Output {
+        Self(self.0.map(|value| -value))
+    }
+}
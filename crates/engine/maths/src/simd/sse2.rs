@@ -0,0 +1,110 @@
+// Intrinsics of a baseline target feature are safe to call on recent compilers
+#![allow(unused_unsafe)]
+
+use std::arch::x86_64::*;
+use std::ops;
+
+#[derive(Copy, Clone)]
+#[repr(transparent)]
+pub struct F32x4(__m128);
+
+// Lane a of the result is lane 'a' of the source, and so on
+macro_rules! shuffle {
+    ($v:expr, $a:literal, $b:literal, $c:literal, $d:literal) => {
+        F32x4(unsafe { _mm_shuffle_ps::<{ $a | $b << 2 | $c << 4 | $d << 6 }>($v.0, $v.0) })
+    };
+}
+
+impl F32x4 {
+    #[inline]
+    pub fn new(x: f32, y: f32, z: f32, w: f32) -> Self {
+        Self(unsafe { _mm_setr_ps(x, y, z, w) })
+    }
+
+    #[inline]
+    pub fn splat(value: f32) -> Self {
+        Self(unsafe { _mm_set1_ps(value) })
+    }
+
+    #[inline]
+    pub fn to_array(self) -> [f32; 4] {
+        let mut array = [0.0; 4];
+        unsafe { _mm_storeu_ps(array.as_mut_ptr(), self.0) };
+        array
+    }
+
+    #[inline]
+    pub fn min(self, other: Self) -> Self {
+        Self(unsafe { _mm_min_ps(self.0, other.0) })
+    }
+
+    #[inline]
+    pub fn max(self, other: Self) -> Self {
+        Self(unsafe { _mm_max_ps(self.0, other.0) })
+    }
+
+    #[inline]
+    pub fn dot(self, other: Self) -> f32 {
+        unsafe {
+            let product = _mm_mul_ps(self.0, other.0);
+            let pairs = _mm_add_ps(product, _mm_shuffle_ps::<0b10_11_00_01>(product, product));
+            _mm_cvtss_f32(_mm_add_ss(pairs, _mm_movehl_ps(pairs, pairs)))
+        }
+    }
+
+    #[inline] pub fn xxxx(self) -> Self { shuffle!(self, 0, 0, 0, 0) }
+    #[inline] pub fn yyyy(self) -> Self { shuffle!(self, 1, 1, 1, 1) }
+    #[inline] pub fn zzzz(self) -> Self { shuffle!(self, 2, 2, 2, 2) }
+    #[inline] pub fn wwww(self) -> Self { shuffle!(self, 3, 3, 3, 3) }
+    #[inline] pub fn yxwz(self) -> Self { shuffle!(self, 1, 0, 3, 2) }
+    #[inline] pub fn zwxy(self) -> Self { shuffle!(self, 2, 3, 0, 1) }
+    #[inline] pub fn wzyx(self) -> Self { shuffle!(self, 3, 2, 1, 0) }
+    #[inline] pub fn yzxw(self) -> Self { shuffle!(self, 1, 2, 0, 3) }
+    #[inline] pub fn zxyw(self) -> Self { shuffle!(self, 2, 0, 1, 3) }
+}
+
+impl ops::Add for F32x4 {
+    type Output = F32x4;
+
+    #[inline]
+    fn add(self, rhs: F32x4) -> Self::Output {
+        Self(unsafe { _mm_add_ps(self.0, rhs.0) })
+    }
+}
+
+impl ops::Sub for F32x4 {
+    type Output = F32x4;
+
+    #[inline]
+    fn sub(self, rhs: F32x4) -> Self::Output {
+        Self(unsafe { _mm_sub_ps(self.0, rhs.0) })
+    }
+}
+
+impl ops::Mul for F32x4 {
+    type Output = F32x4;
+
+    #[inline]
+    fn mul(self, rhs: F32x4) -> Self::Output {
+        Self(unsafe { _mm_mul_ps(self.0, rhs.0) })
+    }
+}
+
+impl ops::Div for F32x4 {
+    type Output = F32x4;
+
+    #[inline]
+    fn div(self, rhs: F32x4) -> Self::Output {
+        Self(unsafe { _mm_div_ps(self.0, rhs.0) })
+    }
+}
+
+impl ops::Neg for F32x4 {
+    type Output = F32x4;
+
+    #[inline]
+    fn neg(self) -> Self::Output {
+        // Flip the sign bits
+        Self(unsafe { _mm_xor_ps(self.0, _mm_set1_ps(-0.0)) })
+    }
+}
@@ -0,0 +1,302 @@
+use std::ops;
+
+use crate::scalar::Float;
+use crate::vec2::Vec2;
+use crate::vec3::Vec3;
+use crate::vec4::Vec4;
+
+/*
+POINTS
+ */
+
+// Values curves interpolate : vectors and scalars
+pub trait CurvePoint<T>: Copy + ops::Add<Output=Self> + ops::Sub<Output=Self> + ops::Mul<T, Output=Self> {
+    fn distance(&self, other: &Self) -> T;
+}
+
+impl<T: Float> CurvePoint<T> for Vec2<T> {
+    fn distance(&self, other: &Self) -> T {
+        Vec2::distance(self, *other)
+    }
+}
+
+impl<T: Float> CurvePoint<T> for Vec3<T> {
+    fn distance(&self, other: &Self) -> T {
+        Vec3::distance(self, *other)
+    }
+}
+
+impl<T: Float> CurvePoint<T> for Vec4<T> {
+    fn distance(&self, other: &Self) -> T {
+        Vec4::distance(self, *other)
+    }
+}
+
+impl CurvePoint<f32> for f32 {
+    fn distance(&self, other: &Self) -> f32 {
+        (self - other).abs()
+    }
+}
+
+impl CurvePoint<f64> for f64 {
+    fn distance(&self, other: &Self) -> f64 {
+        (self - other).abs()
+    }
+}
+
+// Cubic going from p0 to p1 with tangents m0 and m1, for t in [0, 1]
+pub fn hermite<T: Float, P: CurvePoint<T>>(p0: P, m0: P, p1: P, m1: P, t: T) -> P {
+    let (t2, t3) = (t * t, t * t * t);
+    let (two, three) = (T::from(2), T::from(3));
+    p0 * (two * t3 - three * t2 + T::one()) + m0 * (t3 - two * t2 + t) + p1 * (three * t2 - two * t3) + m1 * (t3 - t2)
+}
+
+pub fn hermite_derivative<T: Float, P: CurvePoint<T>>(p0: P, m0: P, p1: P, m1: P, t: T) -> P {
+    let t2 = t * t;
+    let (two, three, four, six) = (T::from(2), T::from(3), T::from(4), T::from(6));
+    p0 * (six * t2 - six * t) + m0 * (three * t2 - four * t + T::one()) + p1 * (six * t - six * t2) + m1 * (three * t2 - two * t)
+}
+
+/*
+SPLINES
+ */
+
+// Piecewise cubic curves. The parameter goes from 0 to 1 over the whole curve, each segment getting an equal share.
+pub trait Spline<T: Float> {
+    type Point: CurvePoint<T>;
+
+    fn segment_count(&self) -> usize;
+
+    // Hermite form of a segment : start, start tangent, end, end tangent
+    fn segment(&self, index: usize) -> [Self::Point; 4];
+
+    fn sample(&self, t: T) -> Self::Point {
+        let (index, t) = self.locate(t);
+        let [p0, m0, p1, m1] = self.segment(index);
+        hermite(p0, m0, p1, m1, t)
+    }
+
+    // Derivative with respect to the global parameter
+    fn derivative(&self, t: T) -> Self::Point {
+        let (index, t) = self.locate(t);
+        let [p0, m0, p1, m1] = self.segment(index);
+        hermite_derivative(p0, m0, p1, m1, t) * T::from_f64(self.segment_count() as f64)
+    }
+
+    // Segment index and local parameter of a global parameter, clamped to the curve
+    fn locate(&self, t: T) -> (usize, T) {
+        let count = self.segment_count();
+        let t = if t < T::zero() { T::zero() } else if t > T::one() { T::one() } else { t };
+        let scaled = t * T::from_f64(count as f64);
+        let index = (scaled.floor().to_f64() as usize).min(count - 1);
+        (index, scaled - T::from_f64(index as f64))
+    }
+}
+
+// Cubic Bezier segments sharing their end points : p0 c0 c1 p1 c2 c3 p2 ...
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Bezier<P> {
+    points: Vec<P>,
+}
+
+impl<P> Bezier<P> {
+    // None unless there are 3n + 1 points, with n > 0
+    pub fn new(points: Vec<P>) -> Option<Self> {
+        (points.len() >= 4 && points.len() % 3 == 1).then_some(Self { points })
+    }
+
+    pub fn points(&self) -> &[P] {
+        &self.points
+    }
+}
+
+impl<T: Float, P: CurvePoint<T>> Spline<T> for Bezier<P> {
+    type Point = P;
+
+    fn segment_count(&self) -> usize {
+        self.points.len() / 3
+    }
+
+    fn segment(&self, index: usize) -> [P; 4] {
+        let [p0, c0, c1, p1] = [0, 1, 2, 3].map(|i| self.points[index * 3 + i]);
+        let three = T::from(3);
+        [p0, (c0 - p0) * three, p1, (p1 - c1) * three]
+    }
+}
+
+// Goes through every point. Tangents are given by the neighbours, one-sided at the ends.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CatmullRom<P> {
+    points: Vec<P>,
+}
+
+impl<P> CatmullRom<P> {
+    // None if there are less than 2 points
+    pub fn new(points: Vec<P>) -> Option<Self> {
+        (points.len() >= 2).then_some(Self { points })
+    }
+
+    pub fn points(&self) -> &[P] {
+        &self.points
+    }
+}
+
+impl<P> CatmullRom<P> {
+    fn tangent<T: Float>(&self, index: usize) -> P where P: CurvePoint<T> {
+        let last = self.points.len() - 1;
+        match index {
+            0 => { self.points[1] - self.points[0] }
+            i if i == last => { self.points[last] - self.points[last - 1] }
+            i => { (self.points[i + 1] - self.points[i - 1]) * T::from_f64(0.5) }
+        }
+    }
+}
+
+impl<T: Float, P: CurvePoint<T>> Spline<T> for CatmullRom<P> {
+    type Point = P;
+
+    fn segment_count(&self) -> usize {
+        self.points.len() - 1
+    }
+
+    fn segment(&self, index: usize) -> [P; 4] {
+        [self.points[index], self.tangent(index), self.points[index + 1], self.tangent(index + 1)]
+    }
+}
+
+// Points with explicit tangents
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Hermite<P> {
+    // Point and tangent
+    keys: Vec<(P, P)>,
+}
+
+impl<P> Hermite<P> {
+    // None if there are less than 2 keys
+    pub fn new(keys: Vec<(P, P)>) -> Option<Self> {
+        (keys.len() >= 2).then_some(Self { keys })
+    }
+
+    pub fn keys(&self) -> &[(P, P)] {
+        &self.keys
+    }
+}
+
+impl<T: Float, P: CurvePoint<T>> Spline<T> for Hermite<P> {
+    type Point = P;
+
+    fn segment_count(&self) -> usize {
+        self.keys.len() - 1
+    }
+
+    fn segment(&self, index: usize) -> [P; 4] {
+        let ((p0, m0), (p1, m1)) = (self.keys[index], self.keys[index + 1]);
+        [p0, m0, p1, m1]
+    }
+}
+
+/*
+ARC LENGTH
+ */
+
+// Distance along a spline, approximated by a polyline. Used to move at constant speed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArcLength<T> {
+    // Cumulated length at each sample, with evenly spaced parameters
+    lengths: Vec<T>,
+}
+
+impl<T: Float> ArcLength<T> {
+    pub fn new<S: Spline<T>>(spline: &S, samples: usize) -> Self {
+        let samples = samples.max(1);
+        let mut lengths = Vec::with_capacity(samples + 1);
+        let mut previous = spline.sample(T::zero());
+        let mut length = T::zero();
+        lengths.push(length);
+        for i in 1..=samples {
+            let point = spline.sample(T::from_f64(i as f64 / samples as f64));
+            length = length + point.distance(&previous);
+            lengths.push(length);
+            previous = point;
+        }
+        Self { lengths }
+    }
+
+    pub fn length(&self) -> T {
+        *self.lengths.last().unwrap()
+    }
+
+    // Spline parameter at a distance from the start, clamped to the spline
+    pub fn parameter(&self, distance: T) -> T {
+        let last = self.lengths.len() - 1;
+        if distance <= T::zero() {
+            return T::zero();
+        }
+        if distance >= self.length() {
+            return T::one();
+        }
+        // First sample past the distance
+        let index = self.lengths.partition_point(|length| *length < distance).clamp(1, last);
+        let (start, end) = (self.lengths[index - 1], self.lengths[index]);
+        let local = if end > start { (distance - start) / (end - start) } else { T::zero() };
+        (T::from_f64((index - 1) as f64) + local) / T::from_f64(last as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::spline::{ArcLength, Bezier, CatmullRom, Hermite, Spline};
+    use crate::vec2::{Vec2, Vec2f64};
+    use crate::vec3::{Vec3, Vec3F64};
+
+    fn assert_vec_near(a: Vec3F64, b: Vec3F64) {
+        assert!(a.distance(b) < 1e-9, "{a:?} != {b:?}");
+    }
+
+    #[test]
+    fn spline_test() {
+        let points = vec![Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 2.0, 0.0), Vec3::new(3.0, 2.0, 1.0), Vec3::new(4.0, 0.0, 1.0)];
+        let bezier = Bezier::new(points.clone()).unwrap();
+        assert!(Bezier::new(points[..3].to_vec()).is_none());
+        assert_vec_near(bezier.sample(0.0), points[0]);
+        assert_vec_near(bezier.sample(1.0), points[3]);
+        // De Casteljau midpoint
+        assert_vec_near(bezier.sample(0.5), (points[0] + points[1] * 3.0 + points[2] * 3.0 + points[3]) * 0.125);
+        assert_vec_near(bezier.derivative(0.0), (points[1] - points[0]) * 3.0);
+
+        let catmull_rom = CatmullRom::new(points.clone()).unwrap();
+        for (i, point) in points.iter().enumerate() {
+            assert_vec_near(catmull_rom.sample(i as f64 / 3.0), *point);
+        }
+        assert_vec_near(catmull_rom.derivative(1.0 / 3.0), (points[2] - points[0]) * 0.5 * 3.0);
+        // Parameters are clamped
+        assert_vec_near(catmull_rom.sample(2.0), points[3]);
+
+        let hermite = Hermite::new(vec![(Vec2::new(0.0, 0.0), Vec2::new(1.0, 0.0)), (Vec2::new(1.0, 0.0), Vec2::new(1.0, 0.0))]).unwrap();
+        let sample: Vec2f64 = hermite.sample(0.25);
+        assert_eq!(sample, Vec2::new(0.25, 0.0));
+        assert!(Hermite::<Vec2f64>::new(vec![]).is_none());
+
+        // Scalars work too
+        let scalar = CatmullRom::new(vec![0.0f32, 10.0, 20.0]).unwrap();
+        assert_eq!(scalar.sample(0.5), 10.0);
+    }
+
+    #[test]
+    fn arc_length_test() {
+        // Straight line with uneven speed : the second point is near the start
+        let points: Vec<Vec2f64> = vec![Vec2::new(0.0, 0.0), Vec2::new(2.0, 0.0), Vec2::new(10.0, 0.0)];
+        let spline = CatmullRom::new(points).unwrap();
+        let arc = ArcLength::new(&spline, 1000);
+        assert!((arc.length() - 10.0).abs() < 1e-6);
+        assert_eq!(arc.parameter(-1.0), 0.0);
+        assert_eq!(arc.parameter(20.0), 1.0);
+        for distance in [0.5, 1.0, 5.0, 9.5] {
+            let point: Vec2f64 = spline.sample(arc.parameter(distance));
+            assert!((point.x - distance).abs() < 1e-3, "{point:?} at {distance}");
+        }
+    }
+}
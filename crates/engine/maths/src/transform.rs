@@ -0,0 +1,182 @@
+use std::ops;
+
+use crate::mat3::Mat3;
+use crate::mat4::Mat4;
+use crate::quat::Quat;
+use crate::scalar::Float;
+use crate::vec3::Vec3;
+
+// Scale, then rotation, then translation
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Transform<T: Default> {
+    pub translation: Vec3<T>,
+    pub rotation: Quat<T>,
+    pub scale: Vec3<T>,
+}
+
+pub type TransformF32 = Transform<f32>;
+pub type TransformF64 = Transform<f64>;
+
+impl<T: Float> Default for Transform<T> {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl<T: Float> Transform<T> {
+    pub fn new(translation: Vec3<T>, rotation: Quat<T>, scale: Vec3<T>) -> Self {
+        Self { translation, rotation, scale }
+    }
+
+    pub fn identity() -> Self {
+        Self::new(Vec3::new(T::zero(), T::zero(), T::zero()), Quat::identity(), Vec3::new(T::one(), T::one(), T::one()))
+    }
+
+    pub fn from_translation(translation: Vec3<T>) -> Self {
+        Self { translation, ..Self::identity() }
+    }
+
+    pub fn from_rotation(rotation: Quat<T>) -> Self {
+        Self { rotation, ..Self::identity() }
+    }
+
+    pub fn from_scale(scale: Vec3<T>) -> Self {
+        Self { scale, ..Self::identity() }
+    }
+
+    pub fn to_mat4(&self) -> Mat4<T> {
+        let rotation = self.rotation.to_mat3();
+        let (x, y, z) = (rotation.col(0) * self.scale.x, rotation.col(1) * self.scale.y, rotation.col(2) * self.scale.z);
+        let (zero, one) = (T::zero(), T::one());
+        Mat4::new(
+            x.x, x.y, x.z, zero,
+            y.x, y.y, y.z, zero,
+            z.x, z.y, z.z, zero,
+            self.translation.x, self.translation.y, self.translation.z, one)
+    }
+
+    // Split an affine matrix without shear or projection. A mirroring is stored as a negative x scale.
+    // None if a scale is zero.
+    pub fn from_mat4(m: &Mat4<T>) -> Option<Self> {
+        let linear = Mat3::from(*m);
+        let (x, y, z) = (linear.col(0), linear.col(1), linear.col(2));
+        let mut scale = Vec3::new(x.length(), y.length(), z.length());
+        if scale.x == T::zero() || scale.y == T::zero() || scale.z == T::zero() {
+            return None;
+        }
+        if linear.determinant() < T::zero() {
+            scale.x = -scale.x;
+        }
+        let (x, y, z) = (x / scale.x, y / scale.y, z / scale.z);
+        let rotation = Mat3::new(x.x, x.y, x.z, y.x, y.y, y.z, z.x, z.y, z.z);
+        Some(Self::new(Vec3::new(m.w1, m.w2, m.w3), Quat::from_mat3(&rotation), scale))
+    }
+
+    pub fn transform_point(&self, point: Vec3<T>) -> Vec3<T> {
+        self.rotation * (point * self.scale) + self.translation
+    }
+
+    // Directions are not affected by translation
+    pub fn transform_vector(&self, vector: Vec3<T>) -> Vec3<T> {
+        self.rotation * (vector * self.scale)
+    }
+
+    // Exact when the scale is uniform. Otherwise the result is the closest transform without shear.
+    pub fn inverse(&self) -> Self {
+        let rotation = self.rotation.conjugate();
+        let scale = Vec3::new(T::one() / self.scale.x, T::one() / self.scale.y, T::one() / self.scale.z);
+        let translation = rotation * (self.translation * -T::one()) * scale;
+        Self::new(translation, rotation, scale)
+    }
+
+    pub fn interpolate(&self, other: &Self, t: T) -> Self {
+        let lerp = |a: Vec3<T>, b: Vec3<T>| a + (b - a) * t;
+        Self::new(lerp(self.translation, other.translation), self.rotation.slerp(&other.rotation, t), lerp(self.scale, other.scale))
+    }
+}
+
+// Apply the right-hand side transform first, as for matrices.
+// Exact when the left-hand side scale is uniform, otherwise shear is dropped.
+impl<T: Float> ops::Mul<Transform<T>> for Transform<T> {
+    type Output = Transform<T>;
+
+    fn mul(self, rhs: Transform<T>) -> Self::Output {
+        Self::new(self.transform_point(rhs.translation), self.rotation * rhs.rotation, self.scale * rhs.scale)
+    }
+}
+
+impl<T: Float> From<Transform<T>> for Mat4<T> {
+    fn from(transform: Transform<T>) -> Self {
+        transform.to_mat4()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::FRAC_PI_2;
+
+    use crate::mat4::Mat4F64;
+    use crate::quat::QuatF64;
+    use crate::transform::TransformF64;
+    use crate::vec3::{Vec3, Vec3F64};
+    use crate::vec4::Vec4;
+
+    fn assert_vec_near(a: Vec3F64, b: Vec3F64) {
+        assert!((a.x - b.x).abs() < 1e-9 && (a.y - b.y).abs() < 1e-9 && (a.z - b.z).abs() < 1e-9, "{a:?} != {b:?}");
+    }
+
+    fn assert_mat_near(a: Mat4F64, b: Mat4F64) {
+        assert!(a.to_array().iter().zip(b.to_array()).all(|(a, b)| (a - b).abs() < 1e-9), "{a:?} != {b:?}");
+    }
+
+    fn sample() -> TransformF64 {
+        TransformF64::new(Vec3::new(1.0, -2.0, 3.0), QuatF64::from_euler(0.5, 1.0, -0.25), Vec3::new(2.0, 0.5, 3.0))
+    }
+
+    #[test]
+    fn matrix_test() {
+        let transform = sample();
+        let point = Vec3::new(0.5, 1.0, -1.5);
+        let transformed = transform.to_mat4() * Vec4::new(point.x, point.y, point.z, 1.0);
+        assert_vec_near(Vec3::from(transformed), transform.transform_point(point));
+        assert_eq!(TransformF64::identity().to_mat4(), Mat4F64::identity());
+
+        let scale = TransformF64::from_scale(Vec3::new(2.0, 2.0, 2.0));
+        let rotation = TransformF64::from_rotation(QuatF64::from_rotation_z(FRAC_PI_2));
+        let translation = TransformF64::from_translation(Vec3::new(0.0, 0.0, 1.0));
+        assert_vec_near((translation * rotation * scale).transform_point(Vec3::new(1.0, 0.0, 0.0)), Vec3::new(0.0, 2.0, 1.0));
+        assert_vec_near(translation.transform_vector(Vec3::new(1.0, 0.0, 0.0)), Vec3::new(1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn decompose_test() {
+        let transform = sample();
+        let decomposed = TransformF64::from_mat4(&transform.to_mat4()).unwrap();
+        assert_vec_near(decomposed.translation, transform.translation);
+        assert_vec_near(decomposed.scale, transform.scale);
+        assert!((decomposed.rotation.dot(&transform.rotation).abs() - 1.0).abs() < 1e-9);
+
+        // Mirroring is kept
+        let mirrored = TransformF64::from_scale(Vec3::new(1.0, -2.0, 1.0)).to_mat4();
+        assert_mat_near(TransformF64::from_mat4(&mirrored).unwrap().to_mat4(), mirrored);
+        assert!(TransformF64::from_mat4(&TransformF64::from_scale(Vec3::new(1.0, 0.0, 1.0)).to_mat4()).is_none());
+    }
+
+    #[test]
+    fn composition_test() {
+        let parent = TransformF64::new(Vec3::new(4.0, 0.0, -1.0), QuatF64::from_rotation_y(0.7), Vec3::new(2.0, 2.0, 2.0));
+        let child = sample();
+        assert_mat_near((parent * child).to_mat4(), parent.to_mat4() * child.to_mat4());
+
+        let uniform = TransformF64 { scale: Vec3::new(3.0, 3.0, 3.0), ..child };
+        assert_mat_near(uniform.inverse().to_mat4(), uniform.to_mat4().inverse().unwrap());
+        assert_mat_near((uniform * uniform.inverse()).to_mat4(), Mat4F64::identity());
+        let point = Vec3::new(1.0, 2.0, 3.0);
+        assert_vec_near(uniform.inverse().transform_point(uniform.transform_point(point)), point);
+
+        let halfway = TransformF64::identity().interpolate(&parent, 0.5);
+        assert_vec_near(halfway.translation, Vec3::new(2.0, 0.0, -0.5));
+        assert_vec_near(halfway.scale, Vec3::new(1.5, 1.5, 1.5));
+    }
+}
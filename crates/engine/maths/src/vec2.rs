@@ -2,7 +2,11 @@
 
 use macros::*;
 
-#[derive(Debug, Copy, Clone, PartialEq, OpsAdd, OpsSub, OpsMul, OpsDiv, DefaultConstruct)]
+use crate::vec3::Vec3;
+use crate::vec4::Vec4;
+
+#[derive(Debug, Copy, Clone, PartialEq, OpsAdd, OpsSub, OpsMul, OpsDiv, OpsNeg, DefaultConstruct, Swizzle)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Vec2<T: Default> {
     pub x: T,
     pub y: T,
@@ -15,6 +19,8 @@ pub type Vec2i64 = Vec2<i64>;
 pub type Vec2f32 = Vec2<f32>;
 pub type Vec2f64 = Vec2<f64>;
 
+impl_vector!(Vec2, 2, x, y);
+
 impl From<Vec2u64> for Vec2u32 { fn from(v: Vec2u64) -> Self { Vec2u32::new(v.x as u32, v.y as u32) } }
 impl From<Vec2i32> for Vec2u32 { fn from(v: Vec2i32) -> Self { Vec2u32::new(v.x as u32, v.y as u32) } }
 impl From<Vec2i64> for Vec2u32 { fn from(v: Vec2i64) -> Self { Vec2u32::new(v.x as u32, v.y as u32) } }
@@ -2,7 +2,12 @@
 use std::ops;
 use macros::*;
 
-#[derive(Debug, Copy, Clone, PartialEq, OpsAdd, OpsSub, OpsMul, OpsDiv, DefaultConstruct)]
+use crate::scalar::Scalar;
+use crate::vec2::Vec2;
+use crate::vec4::Vec4;
+
+#[derive(Debug, Copy, Clone, PartialEq, OpsAdd, OpsSub, OpsMul, OpsDiv, OpsNeg, DefaultConstruct, Swizzle)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Vec3<T: Default> {
     pub x: T,
     pub y: T,
@@ -13,4 +18,57 @@ pub type Vec3u32 = Vec3<u32>;
 pub type Vec3u64 = Vec3<u64>;
 pub type Vec3i32 = Vec3<i32>;
 pub type Vec3F32 = Vec3<f32>;
-pub type Vec3F64 = Vec3<f64>;
\ No newline at end of file
+pub type Vec3F64 = Vec3<f64>;
+
+impl_vector!(Vec3, 3, x, y, z);
+
+impl<T: Scalar> Vec3<T> {
+    // Right-handed : x cross y is z
+    pub fn cross(&self, other: Self) -> Self {
+        Self::new(
+            self.y * other.z - self.z * other.y,
+            self.z * other.x - self.x * other.z,
+            self.x * other.y - self.y * other.x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::vec2::Vec2;
+    use crate::vec3::{Vec3, Vec3F64};
+    use crate::vec4::Vec4;
+
+    #[test]
+    fn vector_test() {
+        let (a, b) = (Vec3F64::new(1.0, -2.0, 2.0), Vec3F64::new(3.0, 0.0, -4.0));
+        assert_eq!(a.dot(b), -5.0);
+        assert_eq!(a.length(), 3.0);
+        assert_eq!(b.normalize(), Vec3F64::new(0.6, 0.0, -0.8));
+        assert_eq!(Vec3F64::splat(0.0).normalize_or_zero(), Vec3F64::splat(0.0));
+        assert_eq!(a.distance(a + Vec3F64::new(0.0, 3.0, 4.0)), 5.0);
+        assert_eq!(a.lerp(b, 0.5), Vec3F64::new(2.0, -1.0, -1.0));
+        assert_eq!(a.min(b), Vec3F64::new(1.0, -2.0, -4.0));
+        assert_eq!(a.max(b), Vec3F64::new(3.0, 0.0, 2.0));
+        assert_eq!(b.clamp(Vec3F64::splat(-1.0), Vec3F64::splat(1.0)), Vec3F64::new(1.0, 0.0, -1.0));
+        assert_eq!(-a, a.abs() * Vec3F64::new(-1.0, 1.0, -1.0));
+
+        let (x, y, z) = (Vec3F64::new(1.0, 0.0, 0.0), Vec3F64::new(0.0, 1.0, 0.0), Vec3F64::new(0.0, 0.0, 1.0));
+        assert_eq!(x.cross(y), z);
+        assert_eq!(y.cross(x), -z);
+        assert_eq!(a.cross(b).dot(a), 0.0);
+
+        let mut c = a;
+        c[2] = 5.0;
+        assert_eq!((c[0], c[1], c[2]), (1.0, -2.0, 5.0));
+    }
+
+    #[test]
+    fn swizzle_test() {
+        let v = Vec4::new(1, 2, 3, 4);
+        assert_eq!(v.xy(), Vec2::new(1, 2));
+        assert_eq!(v.zyx(), Vec3::new(3, 2, 1));
+        assert_eq!(v.wzyx(), Vec4::new(4, 3, 2, 1));
+        assert_eq!(v.xxww(), Vec4::new(1, 1, 4, 4));
+        assert_eq!(Vec2::new(5, 6).yxy(), Vec3::new(6, 5, 6));
+    }
+}
@@ -2,7 +2,11 @@
 use std::ops;
 use macros::*;
 
-#[derive(Debug, Copy, Clone, OpsAdd, OpsSub, OpsMul, OpsDiv, DefaultConstruct)]
+use crate::vec2::Vec2;
+use crate::vec3::Vec3;
+
+#[derive(Debug, Copy, Clone, PartialEq, OpsAdd, OpsSub, OpsMul, OpsDiv, OpsNeg, DefaultConstruct, Swizzle)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Vec4<T: Default> {
     pub x: T,
     pub y: T,
@@ -14,4 +18,6 @@ pub type Vec4u32 = Vec4<u32>;
 pub type Vec4u64 = Vec4<u64>;
 pub type Vec4i32 = Vec4<i32>;
 pub type Vec4F32 = Vec4<f32>;
-pub type Vec4F64 = Vec4<f64>;
\ No newline at end of file
+pub type Vec4F64 = Vec4<f64>;
+
+impl_vector!(Vec4, 4, x, y, z, w);